@@ -2,7 +2,14 @@
 //! in a wide range of address spaces.
 
 use crate::{AcpiError, Handler, PhysicalMapping};
+#[cfg(feature = "alloc")]
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use bit_field::BitField;
 use log::warn;
+use pci_types::PciAddress;
 
 /// This is the raw form of a Generic Address Structure, and follows the layout found in the ACPI tables.
 #[derive(Clone, Copy, Debug)]
@@ -122,7 +129,31 @@ impl GenericAddress {
 pub struct MappedGas<H: Handler> {
     gas: GenericAddress,
     handler: H,
-    mapping: Option<PhysicalMapping<H, u8>>,
+    mapping: Option<GasMapping<H>>,
+}
+
+/// The mapping backing a `SystemMemory` GAS. This is either owned by the GAS, or shared with other
+/// GASes mapped by a [`GasMapper`].
+enum GasMapping<H: Handler> {
+    Owned(PhysicalMapping<H, u8>),
+    #[cfg(feature = "alloc")]
+    Shared {
+        mapping: Arc<PhysicalMapping<H, u8>>,
+        offset: usize,
+    },
+}
+
+impl<H> GasMapping<H>
+where
+    H: Handler,
+{
+    fn register_ptr(&self) -> *mut u8 {
+        match self {
+            GasMapping::Owned(mapping) => mapping.virtual_start.as_ptr(),
+            #[cfg(feature = "alloc")]
+            GasMapping::Shared { mapping, offset } => unsafe { mapping.virtual_start.as_ptr().byte_add(*offset) },
+        }
+    }
 }
 
 impl<H> MappedGas<H>
@@ -130,6 +161,8 @@ where
     H: Handler,
 {
     /// Map the given `GenericAddress`, giving a `MappedGas` that can be read from and written to.
+    /// For `SystemMemory` GASes, only the bytes that are accessed when using the register are
+    /// mapped. Use a [`GasMapper`] to map many registers that may share pages.
     ///
     /// ### Safety
     /// The supplied `GenericAddress` must be a valid GAS and all subsequent reads and writes must
@@ -137,11 +170,13 @@ where
     pub unsafe fn map_gas(gas: GenericAddress, handler: &H) -> Result<MappedGas<H>, AcpiError> {
         match gas.address_space {
             AddressSpace::SystemMemory => {
-                // TODO: how to know total size needed?
-                let mapping = unsafe { handler.map_physical_region(gas.address as usize, 0x1000) };
-                Ok(MappedGas { gas, handler: handler.clone(), mapping: Some(mapping) })
+                let size = gas_mapping_size(gas)?;
+                let mapping = unsafe { handler.map_physical_region(gas.address as usize, size) };
+                Ok(MappedGas { gas, handler: handler.clone(), mapping: Some(GasMapping::Owned(mapping)) })
+            }
+            AddressSpace::SystemIo | AddressSpace::PciConfigSpace | AddressSpace::FunctionalFixedHardware => {
+                Ok(MappedGas { gas, handler: handler.clone(), mapping: None })
             }
-            AddressSpace::SystemIo => Ok(MappedGas { gas, handler: handler.clone(), mapping: None }),
             other => {
                 warn!("Tried to map GAS of unsupported type {:?}", other);
                 Err(AcpiError::LibUnimplemented)
@@ -149,72 +184,216 @@ where
         }
     }
 
+    /// The `GenericAddress` that this register was mapped from.
+    pub fn gas(&self) -> GenericAddress {
+        self.gas
+    }
+
+    /// Read the register described by the GAS. Registers that can't be read with a single access
+    /// (e.g. a 64-bit register with a 32-bit access size) are read with successive accesses. The
+    /// result is shifted and masked according to the GAS's bit offset and bit width.
     pub fn read(&self) -> Result<u64, AcpiError> {
-        /*
-         * TODO: this is only correct for basic GASs that require a single access. Extend it to
-         * support bit offsets and multiple reads etc.
-         */
+        if self.gas.address_space == AddressSpace::FunctionalFixedHardware {
+            return self.handler.read_ffh(self.gas);
+        }
+
+        let access_size_bits = gas_decode_access_bit_width(self.gas)?;
+        let mut raw = 0u128;
+        for i in 0..gas_num_accesses(self.gas, access_size_bits)? {
+            raw |= (self.read_access(i, access_size_bits)? as u128) << (i * access_size_bits as usize);
+        }
+
+        Ok((raw >> self.gas.bit_offset) as u64 & bit_mask(gas_register_bit_width(self.gas, access_size_bits)))
+    }
+
+    /// Write to the register described by the GAS. The value is masked to the register's bit width
+    /// and shifted to its bit offset. Bits outside of the register are written as zero, as
+    /// required for registers where writing a `1` clears a status bit - if other bits must be
+    /// preserved, the register should be read first.
+    pub fn write(&self, value: u64) -> Result<(), AcpiError> {
+        if self.gas.address_space == AddressSpace::FunctionalFixedHardware {
+            return self.handler.write_ffh(self.gas, value);
+        }
+
         let access_size_bits = gas_decode_access_bit_width(self.gas)?;
+        let value = value & bit_mask(gas_register_bit_width(self.gas, access_size_bits));
+        let raw = (value as u128) << self.gas.bit_offset;
+        for i in 0..gas_num_accesses(self.gas, access_size_bits)? {
+            let access = (raw >> (i * access_size_bits as usize)) as u64 & bit_mask(access_size_bits);
+            self.write_access(i, access_size_bits, access)?;
+        }
+
+        Ok(())
+    }
+
+    /// Perform the `index`th access of a read of the register.
+    fn read_access(&self, index: usize, access_size_bits: u8) -> Result<u64, AcpiError> {
+        let offset = index * access_size_bits as usize / 8;
         match self.gas.address_space {
             AddressSpace::SystemMemory => {
-                let mapping = self.mapping.as_ref().unwrap();
+                let ptr = unsafe { self.mapping.as_ref().unwrap().register_ptr().byte_add(offset) };
                 let value = match access_size_bits {
-                    8 => unsafe { core::ptr::read_volatile(mapping.virtual_start.as_ptr() as *const u8) as u64 },
-                    16 => unsafe { core::ptr::read_volatile(mapping.virtual_start.as_ptr() as *const u16) as u64 },
-                    32 => unsafe { core::ptr::read_volatile(mapping.virtual_start.as_ptr() as *const u32) as u64 },
-                    64 => unsafe { core::ptr::read_volatile(mapping.virtual_start.as_ptr() as *const u64) },
-                    _ => panic!(),
+                    8 => unsafe { core::ptr::read_volatile(ptr) as u64 },
+                    16 => unsafe { core::ptr::read_volatile(ptr as *const u16) as u64 },
+                    32 => unsafe { core::ptr::read_volatile(ptr as *const u32) as u64 },
+                    64 => unsafe { core::ptr::read_volatile(ptr as *const u64) },
+                    _ => return Err(AcpiError::InvalidGenericAddress),
                 };
                 Ok(value)
             }
             AddressSpace::SystemIo => {
+                let port = gas_decode_io_port(self.gas, offset)?;
+                let value = match access_size_bits {
+                    8 => self.handler.read_io_u8(port) as u64,
+                    16 => self.handler.read_io_u16(port) as u64,
+                    32 => self.handler.read_io_u32(port) as u64,
+                    _ => return Err(AcpiError::InvalidGenericAddress),
+                };
+                Ok(value)
+            }
+            AddressSpace::PciConfigSpace => {
+                let (address, register) = gas_decode_pci_address(self.gas)?;
+                let register = register.checked_add(offset as u16).ok_or(AcpiError::InvalidGenericAddress)?;
                 let value = match access_size_bits {
-                    8 => self.handler.read_io_u8(self.gas.address as u16) as u64,
-                    16 => self.handler.read_io_u16(self.gas.address as u16) as u64,
-                    32 => self.handler.read_io_u32(self.gas.address as u16) as u64,
-                    _ => panic!(),
+                    8 => self.handler.read_pci_u8(address, register) as u64,
+                    16 => self.handler.read_pci_u16(address, register) as u64,
+                    32 => self.handler.read_pci_u32(address, register) as u64,
+                    _ => return Err(AcpiError::InvalidGenericAddress),
                 };
                 Ok(value)
             }
-            _ => unimplemented!(),
+            _ => Err(AcpiError::LibUnimplemented),
         }
     }
 
-    pub fn write(&self, value: u64) -> Result<(), AcpiError> {
-        // TODO: see above
-        let access_size_bits = gas_decode_access_bit_width(self.gas)?;
+    /// Perform the `index`th access of a write to the register.
+    fn write_access(&self, index: usize, access_size_bits: u8, value: u64) -> Result<(), AcpiError> {
+        let offset = index * access_size_bits as usize / 8;
         match self.gas.address_space {
             AddressSpace::SystemMemory => {
-                let mapping = self.mapping.as_ref().unwrap();
+                let ptr = unsafe { self.mapping.as_ref().unwrap().register_ptr().byte_add(offset) };
                 match access_size_bits {
-                    8 => unsafe {
-                        core::ptr::write_volatile(mapping.virtual_start.as_ptr(), value as u8);
-                    },
-                    16 => unsafe {
-                        core::ptr::write_volatile(mapping.virtual_start.as_ptr() as *mut u16, value as u16);
-                    },
-                    32 => unsafe {
-                        core::ptr::write_volatile(mapping.virtual_start.as_ptr() as *mut u32, value as u32);
-                    },
-                    64 => unsafe { core::ptr::write_volatile(mapping.virtual_start.as_ptr() as *mut u64, value) },
-                    _ => panic!(),
+                    8 => unsafe { core::ptr::write_volatile(ptr, value as u8) },
+                    16 => unsafe { core::ptr::write_volatile(ptr as *mut u16, value as u16) },
+                    32 => unsafe { core::ptr::write_volatile(ptr as *mut u32, value as u32) },
+                    64 => unsafe { core::ptr::write_volatile(ptr as *mut u64, value) },
+                    _ => return Err(AcpiError::InvalidGenericAddress),
                 }
                 Ok(())
             }
             AddressSpace::SystemIo => {
+                let port = gas_decode_io_port(self.gas, offset)?;
                 match access_size_bits {
-                    8 => self.handler.write_io_u8(self.gas.address as u16, value as u8),
-                    16 => self.handler.write_io_u16(self.gas.address as u16, value as u16),
-                    32 => self.handler.write_io_u32(self.gas.address as u16, value as u32),
-                    _ => panic!(),
+                    8 => self.handler.write_io_u8(port, value as u8),
+                    16 => self.handler.write_io_u16(port, value as u16),
+                    32 => self.handler.write_io_u32(port, value as u32),
+                    _ => return Err(AcpiError::InvalidGenericAddress),
                 }
                 Ok(())
             }
-            _ => unimplemented!(),
+            AddressSpace::PciConfigSpace => {
+                let (address, register) = gas_decode_pci_address(self.gas)?;
+                let register = register.checked_add(offset as u16).ok_or(AcpiError::InvalidGenericAddress)?;
+                match access_size_bits {
+                    8 => self.handler.write_pci_u8(address, register, value as u8),
+                    16 => self.handler.write_pci_u16(address, register, value as u16),
+                    32 => self.handler.write_pci_u32(address, register, value as u32),
+                    _ => return Err(AcpiError::InvalidGenericAddress),
+                }
+                Ok(())
+            }
+            _ => Err(AcpiError::LibUnimplemented),
         }
     }
 }
 
+/// Maps `SystemMemory` GASes so that registers in the same page share a single mapping. This is
+/// useful when many registers need to be mapped at once (e.g. the instructions of an APEI table),
+/// as they are often clustered together. Each mapping is unmapped once every `MappedGas` using it
+/// has been dropped.
+#[cfg(feature = "alloc")]
+pub struct GasMapper<H: Handler> {
+    handler: H,
+    mappings: Vec<Weak<PhysicalMapping<H, u8>>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<H> GasMapper<H>
+where
+    H: Handler,
+{
+    const PAGE_SIZE: usize = 0x1000;
+
+    pub fn new(handler: H) -> GasMapper<H> {
+        GasMapper { handler, mappings: Vec::new() }
+    }
+
+    /// Map the given `GenericAddress`, reusing an existing mapping if one covers the register.
+    ///
+    /// ### Safety
+    /// The supplied `GenericAddress` must be a valid GAS and all subsequent reads and writes must
+    /// be valid.
+    pub unsafe fn map_gas(&mut self, gas: GenericAddress) -> Result<MappedGas<H>, AcpiError> {
+        if gas.address_space != AddressSpace::SystemMemory {
+            return unsafe { MappedGas::map_gas(gas, &self.handler) };
+        }
+
+        let start = gas.address as usize;
+        let end = start.checked_add(gas_mapping_size(gas)?).ok_or(AcpiError::InvalidGenericAddress)?;
+
+        self.mappings.retain(|mapping| mapping.strong_count() > 0);
+        let existing = self.mappings.iter().filter_map(Weak::upgrade).find(|mapping| {
+            start >= mapping.physical_start && end <= mapping.physical_start + mapping.region_length
+        });
+
+        let mapping = match existing {
+            Some(mapping) => mapping,
+            None => {
+                /*
+                 * Map the whole of each page the register lies in, so that later registers in the
+                 * same pages can share the mapping.
+                 */
+                let map_start = start & !(Self::PAGE_SIZE - 1);
+                let map_end =
+                    end.checked_next_multiple_of(Self::PAGE_SIZE).ok_or(AcpiError::InvalidGenericAddress)?;
+                let mapping =
+                    Arc::new(unsafe { self.handler.map_physical_region::<u8>(map_start, map_end - map_start) });
+                self.mappings.push(Arc::downgrade(&mapping));
+                mapping
+            }
+        };
+
+        let offset = start - mapping.physical_start;
+        Ok(MappedGas { gas, handler: self.handler.clone(), mapping: Some(GasMapping::Shared { mapping, offset }) })
+    }
+}
+
+/// Returns the number of bytes that are accessed when reading or writing a `SystemMemory` GAS.
+fn gas_mapping_size(gas: GenericAddress) -> Result<usize, AcpiError> {
+    let access_size_bits = gas_decode_access_bit_width(gas)?;
+    Ok(gas_num_accesses(gas, access_size_bits)? * access_size_bits as usize / 8)
+}
+
+/// Find the port of the access at `offset` bytes into a GAS in the `SystemIo` address space.
+fn gas_decode_io_port(gas: GenericAddress, offset: usize) -> Result<u16, AcpiError> {
+    u16::try_from(gas.address)
+        .ok()
+        .and_then(|port| port.checked_add(offset as u16))
+        .ok_or(AcpiError::InvalidGenericAddress)
+}
+
+/// Decode the address of a GAS in the `PciConfigSpace` address space into the PCI device and the
+/// offset into its configuration space. These registers are always on segment `0`, bus `0`.
+fn gas_decode_pci_address(gas: GenericAddress) -> Result<(PciAddress, u16), AcpiError> {
+    let device = gas.address.get_bits(32..48);
+    let function = gas.address.get_bits(16..32);
+    let offset = gas.address.get_bits(0..16) as u16;
+    if device > 31 || function > 7 {
+        return Err(AcpiError::InvalidGenericAddress);
+    }
+    Ok((PciAddress::new(0, 0, device as u8, function as u8), offset))
+}
+
 /// Returns the access size that should be made for a given `GenericAddress`, in bits.
 fn gas_decode_access_bit_width(gas: GenericAddress) -> Result<u8, AcpiError> {
     /*
@@ -231,18 +410,97 @@ fn gas_decode_access_bit_width(gas: GenericAddress) -> Result<u8, AcpiError> {
      * often mean the GAS has come from APEI (ACPI Platform Error Interface), and so needs speical
      * handling.
      */
-    if gas.bit_offset == 0 && [8, 16, 32, 64].contains(&gas.bit_width) {
-        Ok(gas.bit_width)
+    let access_size_bits = if gas.bit_offset == 0 && [8, 16, 32, 64].contains(&gas.bit_width) {
+        gas.bit_width
     } else if gas.access_size != 0 {
         match gas.access_size {
-            1 => Ok(8),
-            2 => Ok(16),
-            3 => Ok(32),
-            4 => Ok(64),
-            _ => Err(AcpiError::InvalidGenericAddress),
+            1 => 8,
+            2 => 16,
+            3 => 32,
+            4 => 64,
+            _ => return Err(AcpiError::InvalidGenericAddress),
         }
     } else {
-        // TODO: work out access size based on alignment of the address
-        todo!()
+        /*
+         * Use the smallest access that covers the whole register, reduced until the address is
+         * naturally aligned for it.
+         */
+        let register_bits = gas.bit_offset as u32 + gas.bit_width as u32;
+        let mut access_size_bits = register_bits.next_power_of_two().clamp(8, 64) as u8;
+        while access_size_bits > 8 && !gas.address.is_multiple_of(access_size_bits as u64 / 8) {
+            access_size_bits /= 2;
+        }
+        access_size_bits
+    };
+
+    /*
+     * I/O ports and PCI configuration space can't be accessed with 64-bit accesses, so split them
+     * into two 32-bit accesses instead.
+     */
+    match gas.address_space {
+        AddressSpace::SystemIo | AddressSpace::PciConfigSpace => Ok(access_size_bits.min(32)),
+        _ => Ok(access_size_bits),
+    }
+}
+
+/// Returns the width of the register described by a `GenericAddress`, in bits. Some firmwares
+/// leave the bit width unspecified, in which case the register is assumed to fill a single access.
+fn gas_register_bit_width(gas: GenericAddress, access_size_bits: u8) -> u8 {
+    if gas.bit_width == 0 { access_size_bits } else { gas.bit_width.min(64) }
+}
+
+/// Returns the number of accesses needed to read or write the whole of a register.
+fn gas_num_accesses(gas: GenericAddress, access_size_bits: u8) -> Result<usize, AcpiError> {
+    let total_bits = gas.bit_offset as usize + gas_register_bit_width(gas, access_size_bits) as usize;
+    if total_bits > 128 {
+        return Err(AcpiError::InvalidGenericAddress);
+    }
+    Ok(total_bits.div_ceil(access_size_bits as usize))
+}
+
+fn bit_mask(bits: u8) -> u64 {
+    if bits >= 64 { u64::MAX } else { (1 << bits) - 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gas(
+        address_space: AddressSpace,
+        bit_width: u8,
+        bit_offset: u8,
+        access_size: u8,
+        address: u64,
+    ) -> GenericAddress {
+        GenericAddress { address_space, bit_width, bit_offset, access_size, address }
+    }
+
+    #[test]
+    fn test_access_width() {
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 32, 0, 0, 0x1000)).unwrap(), 32);
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 64, 0, 3, 0x1000)).unwrap(), 64);
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 3, 4, 1, 0x1000)).unwrap(), 8);
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemIo, 64, 0, 0, 0x400)).unwrap(), 32);
+
+        // Access size derived from the register width and the alignment of the address
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 12, 2, 0, 0x1000)).unwrap(), 16);
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 20, 4, 0, 0x1002)).unwrap(), 16);
+        assert_eq!(gas_decode_access_bit_width(gas(AddressSpace::SystemMemory, 20, 4, 0, 0x1001)).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_mapping_size() {
+        assert_eq!(gas_mapping_size(gas(AddressSpace::SystemMemory, 32, 0, 0, 0x1000)).unwrap(), 4);
+        assert_eq!(gas_mapping_size(gas(AddressSpace::SystemMemory, 64, 0, 3, 0x1000)).unwrap(), 8);
+        assert_eq!(gas_mapping_size(gas(AddressSpace::SystemMemory, 8, 4, 1, 0x1000)).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_num_accesses() {
+        assert_eq!(gas_num_accesses(gas(AddressSpace::SystemMemory, 32, 0, 0, 0), 32).unwrap(), 1);
+        assert_eq!(gas_num_accesses(gas(AddressSpace::SystemIo, 64, 0, 0, 0), 32).unwrap(), 2);
+        assert_eq!(gas_num_accesses(gas(AddressSpace::SystemMemory, 8, 4, 1, 0), 8).unwrap(), 2);
+        assert_eq!(gas_num_accesses(gas(AddressSpace::SystemMemory, 0, 0, 2, 0), 16).unwrap(), 1);
     }
 }
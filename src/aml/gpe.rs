@@ -0,0 +1,591 @@
+//! General-Purpose Events (GPEs) are the mechanism used by the platform to signal events that are
+//! handled by AML, such as a thermal zone crossing a trip point, a hot-plug event, or a device
+//! signalling that it should wake the system.
+//!
+//! GPEs are grouped into register blocks. Up to two blocks (`GPE0` and `GPE1`) can be described
+//! by the FADT - these share a single set of event numbers, and their handlers are `_Lxx` and
+//! `_Exx` methods under `\_GPE`. Further blocks can be described by `ACPI0006` GPE Block Devices
+//! in the namespace - each of these numbers its events from zero, and their handlers are methods
+//! under the block device itself.
+
+use crate::{
+    AcpiError,
+    Handler,
+    address::{AddressSpace, GenericAddress},
+    aml::{
+        AmlError,
+        Interpreter,
+        Operation,
+        namespace::{AmlName, NamespaceLevelKind},
+        object::{Object, WrappedObject},
+        resource::{self, AddressSpaceResourceType, MemoryRangeDescriptor, Resource},
+    },
+    registers::{FixedRegisters, GpeRegisterBlock, Pm1Event},
+};
+use alloc::{vec, vec::Vec};
+use core::str::FromStr;
+use log::{info, warn};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GpeTrigger {
+    /// The event is handled by an `_Exx` method. Its status is cleared before the handler runs.
+    Edge,
+    /// The event is handled by an `_Lxx` method. Its status is cleared after the handler has run
+    /// (and so has hopefully dealt with the cause of the event).
+    Level,
+}
+
+/// Identifies a single GPE.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GpeReference {
+    /// The path of the `ACPI0006` GPE Block Device that this event belongs to, or `None` if it
+    /// belongs to one of the blocks described by the FADT.
+    pub block_device: Option<AmlName>,
+    pub number: u16,
+}
+
+#[derive(Clone, Debug)]
+pub struct GpeEvent {
+    pub number: u16,
+    pub trigger: GpeTrigger,
+    /// The `_Lxx` or `_Exx` method that should be invoked when this event fires, if one exists.
+    pub handler: Option<AmlName>,
+    /// Whether this event is referenced by the `_PRW` of a device, and so can be used to wake the
+    /// system. Wake events are not enabled at runtime, and are instead armed before entering a
+    /// sleep state.
+    pub is_wake: bool,
+}
+
+pub struct GpeBlock<H: Handler> {
+    /// The path of the `ACPI0006` GPE Block Device that describes this block, or `None` for the
+    /// blocks described by the FADT.
+    pub device: Option<AmlName>,
+    /// The number of the first event in this block.
+    pub base: u16,
+    /// The interrupt that this block is wired to, if it is not the SCI. Only GPE Block Devices can
+    /// be wired to other interrupts.
+    pub interrupt: Option<u32>,
+    pub events: Vec<GpeEvent>,
+    registers: GpeBlockRegisters<H>,
+}
+
+enum GpeBlockRegisters<H: Handler> {
+    Gpe0,
+    Gpe1,
+    Device(GpeRegisterBlock<H>),
+}
+
+impl<H> GpeBlock<H>
+where
+    H: Handler,
+{
+    pub fn registers<'a>(&'a self, fixed: &'a FixedRegisters<H>) -> &'a GpeRegisterBlock<H> {
+        match self.registers {
+            GpeBlockRegisters::Gpe0 => fixed.gpe0.as_ref().unwrap(),
+            GpeBlockRegisters::Gpe1 => fixed.gpe1.as_ref().unwrap(),
+            GpeBlockRegisters::Device(ref registers) => registers,
+        }
+    }
+
+    /// Returns the index of the given event within this block, if this block contains it.
+    pub fn index_of(&self, gpe: &GpeReference, fixed: &FixedRegisters<H>) -> Option<usize> {
+        if gpe.block_device != self.device || gpe.number < self.base {
+            return None;
+        }
+
+        let index = (gpe.number - self.base) as usize;
+        if index < self.registers(fixed).num_events() { Some(index) } else { None }
+    }
+}
+
+/// The events that were handled by a call to [`Interpreter::handle_sci`].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct SciEvents {
+    /// The PM timer's most significant bit changed.
+    pub timer_overflow: bool,
+    /// The firmware released the global lock, after it was requested while the firmware owned
+    /// it.
+    pub global_lock_released: bool,
+    pub power_button: bool,
+    pub sleep_button: bool,
+    /// The RTC alarm fired.
+    pub rtc_alarm: bool,
+    /// The number of GPEs that were dispatched to their handlers.
+    pub gpes_handled: usize,
+}
+
+/// Describes a device that can wake the system, as reported by its `_PRW` object.
+#[derive(Clone, Debug)]
+pub struct WakeDevice {
+    pub device: AmlName,
+    /// The GPE that the device signals to wake the system.
+    pub gpe: GpeReference,
+    /// The deepest system sleep state (`Sx`) that the device can wake the system from.
+    pub deepest_sleep_state: u8,
+    /// Power resources that must be turned on for the device to be able to wake the system.
+    pub power_resources: Vec<AmlName>,
+}
+
+impl<H> Interpreter<H>
+where
+    H: Handler,
+{
+    /// Discover the system's GPE blocks and the events that are handled by AML, and enable them.
+    /// This should be called after the namespace has been initialized. This also parses the
+    /// `_PRW` objects of each device to find the GPEs that can be used to wake the system - these
+    /// are not enabled until [`Interpreter::arm_wake_gpes`] is called.
+    pub fn initialize_gpes(&self) -> Result<(), AcpiError> {
+        let gpe_scope = AmlName::from_str("\\_GPE").unwrap();
+
+        /*
+         * Do a single pass over the namespace to find each device, and any objects that look like
+         * GPE handlers. We can't evaluate objects while traversing, so this is deferred.
+         */
+        let mut devices = Vec::new();
+        let mut handlers = Vec::new();
+        self.namespace
+            .lock()
+            .traverse(|path, level| {
+                if level.kind == NamespaceLevelKind::Device {
+                    devices.push(path.clone());
+                }
+
+                for name in level.values.keys() {
+                    if let Some((trigger, number)) = parse_gpe_handler_name(name.as_str()) {
+                        handlers.push((
+                            path.clone(),
+                            trigger,
+                            number,
+                            AmlName::from_name_seg(*name).resolve(path)?,
+                        ));
+                    }
+                }
+
+                Ok(true)
+            })
+            .map_err(AcpiError::Aml)?;
+
+        let mut blocks = Vec::new();
+        if let Some(ref gpe0) = self.registers.gpe0 {
+            gpe0.disable_all();
+            blocks.push(GpeBlock {
+                device: None,
+                base: 0,
+                interrupt: None,
+                events: Vec::new(),
+                registers: GpeBlockRegisters::Gpe0,
+            });
+        }
+        if let Some(ref gpe1) = self.registers.gpe1 {
+            gpe1.disable_all();
+            blocks.push(GpeBlock {
+                device: None,
+                base: self.registers.gpe1_base as u16,
+                interrupt: None,
+                events: Vec::new(),
+                registers: GpeBlockRegisters::Gpe1,
+            });
+        }
+
+        let mut wake_devices = Vec::new();
+        for device in devices {
+            if self.is_gpe_block_device(&device) {
+                match self.create_gpe_block(&device) {
+                    Ok(block) => blocks.push(block),
+                    Err(err) => warn!("Failed to create GPE block for device {}: {:?}", device, err),
+                }
+            }
+
+            let prw_path = AmlName::from_str("_PRW").unwrap().resolve(&device).map_err(AcpiError::Aml)?;
+            match self.evaluate_if_present(prw_path.clone(), vec![]) {
+                Ok(Some(prw)) => match self.parse_prw(&device, &prw_path, prw) {
+                    Ok(wake_device) => wake_devices.push(wake_device),
+                    Err(err) => warn!("Failed to parse _PRW for device {}: {:?}", device, err),
+                },
+                Ok(None) => (),
+                Err(err) => warn!("Failed to evaluate _PRW for device {}: {:?}", device, err),
+            }
+        }
+
+        /*
+         * Register each handler with the block that contains its event. Handlers under `\_GPE`
+         * belong to the FADT blocks, and those under a GPE Block Device belong to its block.
+         */
+        for (scope, trigger, number, handler) in handlers {
+            let gpe = GpeReference {
+                block_device: if scope == gpe_scope { None } else { Some(scope.clone()) },
+                number: number as u16,
+            };
+            if gpe.block_device.is_some() && !blocks.iter().any(|block| block.device == gpe.block_device) {
+                continue;
+            }
+
+            let Some(block) = blocks.iter_mut().find(|block| block.index_of(&gpe, &self.registers).is_some())
+            else {
+                warn!("GPE handler {} does not correspond to an event in any GPE block", handler);
+                continue;
+            };
+
+            let is_wake = wake_devices.iter().any(|wake: &WakeDevice| wake.gpe == gpe);
+            block.events.push(GpeEvent { number: gpe.number, trigger, handler: Some(handler), is_wake });
+        }
+
+        /*
+         * Wake events do not always have a handler - they still need to be tracked so that they
+         * can be armed before entering a sleep state.
+         */
+        for wake_device in &wake_devices {
+            let Some(block) =
+                blocks.iter_mut().find(|block| block.index_of(&wake_device.gpe, &self.registers).is_some())
+            else {
+                warn!("Wake GPE for device {} does not exist in any GPE block", wake_device.device);
+                continue;
+            };
+
+            if !block.events.iter().any(|event| event.number == wake_device.gpe.number) {
+                block.events.push(GpeEvent {
+                    number: wake_device.gpe.number,
+                    trigger: GpeTrigger::Level,
+                    handler: None,
+                    is_wake: true,
+                });
+            }
+        }
+
+        let mut num_enabled = 0;
+        for block in &blocks {
+            let registers = block.registers(&self.registers);
+            for event in &block.events {
+                if event.handler.is_some() && !event.is_wake {
+                    registers.set_event_enabled((event.number - block.base) as usize, true)?;
+                    num_enabled += 1;
+                }
+            }
+        }
+        info!("Initialized {} GPE blocks. Enabled {} GPEs.", blocks.len(), num_enabled);
+
+        *self.gpe_blocks.lock() = blocks;
+        *self.wake_devices.lock() = wake_devices;
+        Ok(())
+    }
+
+    /// Handle a System Control Interrupt (SCI). This should be called by the host from its handler
+    /// for the interrupt reported by [`crate::platform::AcpiPlatform::sci_interrupt`].
+    ///
+    /// Any enabled fixed events that have fired are acknowledged and reported in the returned
+    /// [`SciEvents`] - it is the host's responsibility to act on them (e.g. by beginning a
+    /// shutdown when the power button is pressed). GPEs from blocks wired to the SCI are then
+    /// dispatched to their AML handlers.
+    pub fn handle_sci(&self) -> Result<SciEvents, AcpiError> {
+        let pm1 = &self.registers.pm1_event_registers;
+        let fire = |event: Pm1Event| -> Result<bool, AcpiError> {
+            if pm1.is_event_set(event)? && pm1.is_event_enabled(event)? {
+                pm1.clear_status(event)?;
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        };
+
+        let mut events = SciEvents {
+            timer_overflow: fire(Pm1Event::Timer)?,
+            global_lock_released: fire(Pm1Event::GlobalLock)?,
+            power_button: fire(Pm1Event::PowerButton)?,
+            sleep_button: fire(Pm1Event::SleepButton)?,
+            rtc_alarm: fire(Pm1Event::Rtc)?,
+            gpes_handled: 0,
+        };
+        events.gpes_handled = self.dispatch_gpes(|block| block.interrupt.is_none())?;
+
+        Ok(events)
+    }
+
+    /// Handle an interrupt from a GPE Block Device that is wired to an interrupt other than the
+    /// SCI, dispatching any of its GPEs that have fired. Returns the number of GPEs handled.
+    pub fn handle_gpe_interrupt(&self, interrupt: u32) -> Result<usize, AcpiError> {
+        self.dispatch_gpes(|block| block.interrupt == Some(interrupt))
+    }
+
+    /// Dispatch each pending GPE in the blocks selected by `filter` to its handler. Edge-triggered
+    /// events are acknowledged before their handler runs, so that a new edge arriving while it
+    /// runs is not lost. Level-triggered events are acknowledged afterwards, once the handler has
+    /// dealt with the source of the event.
+    ///
+    /// Handlers can run arbitrary AML, which may itself need to access the GPE blocks (e.g. to
+    /// enable another event), so they are run without the GPE blocks locked.
+    fn dispatch_gpes(&self, filter: impl Fn(&GpeBlock<H>) -> bool) -> Result<usize, AcpiError> {
+        let mut fired = Vec::new();
+
+        for (block_index, block) in self.gpe_blocks.lock().iter().enumerate().filter(|(_, block)| filter(block)) {
+            let registers = block.registers(&self.registers);
+            let pending: Vec<usize> = registers.pending_events().collect();
+
+            for index in pending {
+                let number = block.base + index as u16;
+                let Some(event) = block.events.iter().find(|event| event.number == number) else {
+                    /*
+                     * We only enable events that have handlers, so this shouldn't happen. Disable
+                     * the event so it can't cause an interrupt storm.
+                     */
+                    warn!("GPE {:#x} fired, but has no handler. Disabling.", number);
+                    registers.set_event_enabled(index, false)?;
+                    registers.clear_status(index)?;
+                    continue;
+                };
+
+                if event.trigger == GpeTrigger::Edge {
+                    registers.clear_status(index)?;
+                }
+                fired.push((block_index, index, number, event.handler.clone(), event.trigger));
+            }
+        }
+
+        for (block_index, index, number, handler, trigger) in &fired {
+            if let Some(handler) = handler
+                && let Err(err) = self.evaluate(handler.clone(), vec![])
+            {
+                warn!("Error in handler for GPE {:#x}: {:?}", number, err);
+            }
+            if *trigger == GpeTrigger::Level
+                && let Some(block) = self.gpe_blocks.lock().get(*block_index)
+            {
+                block.registers(&self.registers).clear_status(*index)?;
+            }
+        }
+
+        Ok(fired.len())
+    }
+
+    /// Get the devices that are capable of waking the system, as discovered by
+    /// [`Interpreter::initialize_gpes`].
+    pub fn wake_devices(&self) -> Vec<WakeDevice> {
+        self.wake_devices.lock().clone()
+    }
+
+    /// Prepare the system to be woken from the given sleep state. This disables all runtime GPEs,
+    /// and then enables the wake GPE of each device that can wake the system from the sleep
+    /// state, turning on its required power resources and evaluating its `_DSW` (or `_PSW`)
+    /// object.
+    ///
+    /// Like GPE handlers, the wake methods are run without the GPE blocks or wake devices locked.
+    pub fn arm_wake_gpes(&self, sleep_state: u8) -> Result<(), AcpiError> {
+        let wake_devices: Vec<WakeDevice> = self
+            .wake_devices
+            .lock()
+            .iter()
+            .filter(|wake_device| wake_device.deepest_sleep_state >= sleep_state)
+            .cloned()
+            .collect();
+
+        let mut wake_gpes = Vec::new();
+        for (block_index, block) in self.gpe_blocks.lock().iter().enumerate() {
+            block.registers(&self.registers).disable_all();
+            for wake_device in &wake_devices {
+                if let Some(index) = block.index_of(&wake_device.gpe, &self.registers) {
+                    wake_gpes.push((block_index, index));
+                }
+            }
+        }
+
+        for wake_device in &wake_devices {
+            for power_resource in &wake_device.power_resources {
+                let on = AmlName::from_str("_ON").unwrap().resolve(power_resource).map_err(AcpiError::Aml)?;
+                if let Err(err) = self.evaluate_if_present(on, vec![]) {
+                    warn!("Failed to turn on power resource {} for wake: {:?}", power_resource, err);
+                }
+            }
+            if let Err(err) = self.set_device_wake(&wake_device.device, true, sleep_state) {
+                warn!("Failed to enable wake for device {}: {:?}", wake_device.device, err);
+            }
+        }
+
+        let blocks = self.gpe_blocks.lock();
+        for (block_index, index) in wake_gpes {
+            if let Some(block) = blocks.get(block_index) {
+                block.registers(&self.registers).set_event_enabled(index, true)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Undo the effect of [`Interpreter::arm_wake_gpes`] after waking from a sleep state,
+    /// disabling wake GPEs and re-enabling runtime GPEs. Each device's wake is disabled before the
+    /// power resources it needs for wake are turned off.
+    pub fn disarm_wake_gpes(&self) -> Result<(), AcpiError> {
+        let wake_devices = self.wake_devices();
+        for wake_device in &wake_devices {
+            if let Err(err) = self.set_device_wake(&wake_device.device, false, 0) {
+                warn!("Failed to disable wake for device {}: {:?}", wake_device.device, err);
+            }
+            for power_resource in &wake_device.power_resources {
+                let off = AmlName::from_str("_OFF").unwrap().resolve(power_resource).map_err(AcpiError::Aml)?;
+                if let Err(err) = self.evaluate_if_present(off, vec![]) {
+                    warn!("Failed to turn off power resource {} after wake: {:?}", power_resource, err);
+                }
+            }
+        }
+
+        for block in self.gpe_blocks.lock().iter() {
+            let registers = block.registers(&self.registers);
+            registers.disable_all();
+            for event in &block.events {
+                if event.handler.is_some() && !event.is_wake {
+                    registers.set_event_enabled((event.number - block.base) as usize, true)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enable or disable a device's ability to wake the system, using `_DSW` if it exists, and
+    /// falling back to the deprecated `_PSW`.
+    fn set_device_wake(&self, device: &AmlName, enable: bool, sleep_state: u8) -> Result<(), AmlError> {
+        let enable = if enable { 1 } else { 0 };
+        let dsw = AmlName::from_str("_DSW").unwrap().resolve(device)?;
+        /*
+         * The third argument is the target device state. We don't track device power states, so
+         * tell the firmware the device will be in `D3`.
+         */
+        let args = vec![
+            Object::Integer(enable).wrap(),
+            Object::Integer(sleep_state as u64).wrap(),
+            Object::Integer(3).wrap(),
+        ];
+        if self.evaluate_if_present(dsw, args)?.is_none() {
+            let psw = AmlName::from_str("_PSW").unwrap().resolve(device)?;
+            self.evaluate_if_present(psw, vec![Object::Integer(enable).wrap()])?;
+        }
+        Ok(())
+    }
+
+    fn is_gpe_block_device(&self, device: &AmlName) -> bool {
+        let Ok(hid) = AmlName::from_str("_HID").unwrap().resolve(device) else { return false };
+        match self.evaluate_if_present(hid, vec![]) {
+            Ok(Some(hid)) => matches!(*hid, Object::String(ref hid) if hid == "ACPI0006"),
+            _ => false,
+        }
+    }
+
+    /// Create a [`GpeBlock`] for an `ACPI0006` GPE Block Device. The device's `_CRS` describes
+    /// the location of the block's registers, and optionally an interrupt it is wired to.
+    fn create_gpe_block(&self, device: &AmlName) -> Result<GpeBlock<H>, AcpiError> {
+        let crs_path = AmlName::from_str("_CRS").unwrap().resolve(device).map_err(AcpiError::Aml)?;
+        let crs = self.evaluate(crs_path, vec![]).map_err(AcpiError::Aml)?;
+        let resources = resource::resource_descriptor_list(crs).map_err(AcpiError::Aml)?;
+
+        let mut registers = None;
+        let mut interrupt = None;
+        for resource in resources {
+            match resource {
+                Resource::IOPort(descriptor) => {
+                    registers = Some((
+                        AddressSpace::SystemIo,
+                        descriptor.memory_range.0 as u64,
+                        descriptor.range_length as usize,
+                    ));
+                }
+                Resource::MemoryRange(MemoryRangeDescriptor::FixedLocation {
+                    base_address, range_length, ..
+                }) => {
+                    registers = Some((AddressSpace::SystemMemory, base_address as u64, range_length as usize));
+                }
+                Resource::AddressSpace(descriptor) => {
+                    let space = match descriptor.resource_type {
+                        AddressSpaceResourceType::MemoryRange => AddressSpace::SystemMemory,
+                        AddressSpaceResourceType::IORange => AddressSpace::SystemIo,
+                        AddressSpaceResourceType::BusNumberRange => continue,
+                    };
+                    registers = Some((space, descriptor.address_range.0, descriptor.length as usize));
+                }
+                Resource::Irq(descriptor) => interrupt = Some(descriptor.irq),
+                Resource::Dma(_) => (),
+            }
+        }
+
+        let Some((address_space, address, length)) = registers else {
+            return Err(AcpiError::Aml(AmlError::InvalidGpeBlockDevice));
+        };
+        let base = GenericAddress { address_space, bit_width: 8, bit_offset: 0, access_size: 1, address };
+        let registers = unsafe { GpeRegisterBlock::new(base, length, self.handler.clone())? };
+        registers.disable_all();
+
+        Ok(GpeBlock {
+            device: Some(device.clone()),
+            base: 0,
+            interrupt,
+            events: Vec::new(),
+            registers: GpeBlockRegisters::Device(registers),
+        })
+    }
+
+    /// Parse a `_PRW` (Power Resources for Wake) object. This is a package of the form:
+    /// ```ignore
+    /// Package {
+    ///     EventInfo,          // Integer GPE number, or Package { GpeBlockDevice, GpeNumber }
+    ///     DeepestSleepState,  // Integer
+    ///     PowerResource0,     // References to the power resources required for wake
+    ///     ...
+    /// }
+    /// ```
+    fn parse_prw(&self, device: &AmlName, prw_path: &AmlName, prw: WrappedObject) -> Result<WakeDevice, AmlError> {
+        let Object::Package(ref elements) = *prw else {
+            return Err(AmlError::InvalidOperationOnObject { op: Operation::DecodePrw, typ: prw.typ() });
+        };
+        if elements.len() < 2 {
+            return Err(AmlError::PrwInvalidEventInfo);
+        }
+
+        let gpe = match *elements[0] {
+            Object::Integer(number) => GpeReference { block_device: None, number: number as u16 },
+            Object::Package(ref event_info) => {
+                let (Some(block_device), Some(number)) = (event_info.first(), event_info.get(1)) else {
+                    return Err(AmlError::PrwInvalidEventInfo);
+                };
+                let Object::String(ref block_device) = **block_device else {
+                    return Err(AmlError::PrwInvalidEventInfo);
+                };
+                let Object::Integer(number) = **number else {
+                    return Err(AmlError::PrwInvalidEventInfo);
+                };
+                let block_device =
+                    self.namespace.lock().search_for_level(&AmlName::from_str(block_device)?, prw_path)?;
+                GpeReference { block_device: Some(block_device), number: number as u16 }
+            }
+            _ => return Err(AmlError::PrwInvalidEventInfo),
+        };
+
+        let Object::Integer(deepest_sleep_state) = *elements[1] else {
+            return Err(AmlError::PrwInvalidSleepState);
+        };
+
+        let mut power_resources = Vec::new();
+        for element in &elements[2..] {
+            if let Object::String(ref name) = **element {
+                power_resources.push(self.namespace.lock().search_for_level(&AmlName::from_str(name)?, prw_path)?);
+            } else {
+                warn!("Invalid power resource in _PRW of device {}: {}", device, **element);
+            }
+        }
+
+        Ok(WakeDevice {
+            device: device.clone(),
+            gpe,
+            deepest_sleep_state: deepest_sleep_state as u8,
+            power_resources,
+        })
+    }
+}
+
+/// Parse the name of a GPE handler method (`_Lxx` or `_Exx`, where `xx` is the event number in
+/// hex).
+fn parse_gpe_handler_name(name: &str) -> Option<(GpeTrigger, u8)> {
+    let trigger = match name.get(0..2)? {
+        "_L" => GpeTrigger::Level,
+        "_E" => GpeTrigger::Edge,
+        _ => return None,
+    };
+    let number = u8::from_str_radix(name.get(2..4)?, 16).ok()?;
+    Some((trigger, number))
+}
@@ -16,11 +16,13 @@
  *  - Fuzzing and guarantee panic-free interpretation
  */
 
+pub mod gpe;
 pub mod namespace;
 pub mod object;
 pub mod op_region;
 pub mod pci_routing;
 pub mod resource;
+pub mod sleep;
 
 use crate::{
     AcpiError,
@@ -47,6 +49,7 @@ use core::{
     str::FromStr,
     sync::atomic::{AtomicU64, Ordering},
 };
+use gpe::{GpeBlock, WakeDevice};
 use log::{info, trace, warn};
 use namespace::{AmlName, Namespace, NamespaceLevelKind};
 use object::{
@@ -83,6 +86,9 @@ where
     global_lock_mutex: Handle,
     registers: Arc<FixedRegisters<H>>,
     facs: Option<PhysicalMapping<H, Facs>>,
+
+    gpe_blocks: Spinlock<Vec<GpeBlock<H>>>,
+    wake_devices: Spinlock<Vec<WakeDevice>>,
 }
 
 unsafe impl<H> Send for Interpreter<H> where H: Handler + Send {}
@@ -118,6 +124,8 @@ where
             global_lock_mutex,
             registers,
             facs,
+            gpe_blocks: Spinlock::new(Vec::new()),
+            wake_devices: Spinlock::new(Vec::new()),
         }
     }
 
@@ -3279,6 +3287,7 @@ pub enum Operation {
     WriteBufferField,
     LogicalOp,
     DecodePrt,
+    DecodePrw,
     ParseResource,
 
     ResetEvent,
@@ -3332,6 +3341,11 @@ pub enum AmlError {
     PrtInvalidSource,
     PrtNoEntry,
 
+    PrwInvalidEventInfo,
+    PrwInvalidSleepState,
+    InvalidGpeBlockDevice,
+    InvalidSleepPackage,
+
     /// This is emitted to signal that the library does not support the requested behaviour. This
     /// should eventually never be emitted.
     LibUnimplemented,
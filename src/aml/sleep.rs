@@ -0,0 +1,230 @@
+//! Support for transitioning the system between sleep states. A transition to a sleep state is
+//! made in three steps:
+//!  1. [`Interpreter::prepare_for_sleep`] informs the firmware of the upcoming transition. The host
+//!     should then save any context that will be lost, and put devices into low-power states.
+//!  2. The host disables interrupts and flushes processor caches, and then calls
+//!     [`Interpreter::enter_sleep_state`]. For `S4` and `S5`, this should not return.
+//!  3. Once the system has woken (either by returning from `enter_sleep_state`, or by the firmware
+//!     jumping to the waking vector), the host calls [`Interpreter::leave_sleep_state`].
+
+use crate::{
+    AcpiError,
+    Handler,
+    aml::{AmlError, Interpreter, namespace::AmlName, object::Object},
+    sdt::{
+        Signature,
+        facs::{Facs, WakingVector},
+    },
+};
+use alloc::vec;
+use core::str::FromStr;
+use log::{info, warn};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SleepState {
+    /// Power-on suspend. Processor and device context is maintained.
+    S1,
+    /// Like `S1`, except processor context is lost.
+    S2,
+    /// Suspend-to-RAM. Only memory context is maintained.
+    S3,
+    /// Suspend-to-disk. All context is lost, and must be restored by OSPM from non-volatile storage.
+    S4,
+    /// Soft-off.
+    S5,
+}
+
+impl SleepState {
+    pub fn number(self) -> u8 {
+        match self {
+            SleepState::S1 => 1,
+            SleepState::S2 => 2,
+            SleepState::S3 => 3,
+            SleepState::S4 => 4,
+            SleepState::S5 => 5,
+        }
+    }
+}
+
+/// The values that must be written to the `SLP_TYP` fields of the `PM1a` and `PM1b` control
+/// registers to enter a sleep state. These are provided by the `\_Sx` object for each state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SleepType {
+    pub slp_typa: u8,
+    pub slp_typb: u8,
+}
+
+impl<H> Interpreter<H>
+where
+    H: Handler,
+{
+    /// Get the `SLP_TYP` values for the given sleep state. Returns
+    /// [`AcpiError::SleepStateUnsupported`] if the platform does not support the state.
+    pub fn sleep_type(&self, state: SleepState) -> Result<SleepType, AcpiError> {
+        self.sleep_type_for(state.number())
+    }
+
+    /// Prepare the system to enter the given sleep state, by evaluating `_TTS` and `_PTS`. This
+    /// should be called before device drivers are suspended.
+    pub fn prepare_for_sleep(&self, state: SleepState) -> Result<(), AcpiError> {
+        // Check the state is supported before telling the firmware we're transitioning to it.
+        self.sleep_type(state)?;
+
+        info!("Preparing to enter sleep state {:?}", state);
+        self.evaluate_sleep_method("\\_TTS", state.number())?;
+        self.evaluate_sleep_method("\\_PTS", state.number())?;
+        Ok(())
+    }
+
+    /// Enter the given sleep state. This arms the wake GPEs of devices that can wake the system
+    /// from the state, evaluates `_GTS`, and then writes `SLP_TYP` and `SLP_EN`. Interrupts should
+    /// be disabled, and the processor caches flushed, before calling this.
+    ///
+    /// For `S1`, and for `S2` and `S3` on platforms that resume execution after the write, this
+    /// waits for the system to wake and then returns. [`Interpreter::leave_sleep_state`] must
+    /// then be called. Returns [`AcpiError::Timeout`] if the system does not signal that it has
+    /// woken.
+    pub fn enter_sleep_state(&self, state: SleepState) -> Result<(), AcpiError> {
+        let sleep_type = self.sleep_type(state)?;
+
+        self.arm_wake_gpes(state.number())?;
+        self.evaluate_sleep_method("\\_GTS", state.number())?;
+
+        self.registers.enter_sleep(sleep_type.slp_typa, sleep_type.slp_typb)?;
+
+        /*
+         * Hardware-reduced platforms without a sleep status register have no way to signal that
+         * they have woken, so just assume execution resuming means the platform has woken.
+         */
+        if self.registers.sleep_control.is_some() && self.registers.sleep_status.is_none() {
+            return Ok(());
+        }
+
+        /*
+         * If the platform resumes execution after entering the sleep state, wait for it to signal
+         * that it has woken. The processor does not execute while the platform is asleep, so this
+         * only times out if the platform failed to enter the sleep state. We'll wait up to 1
+         * second.
+         */
+        let mut spinning = 1000 * 1000; // Microseconds
+        while !self.registers.is_waking()? {
+            if spinning == 0 {
+                return Err(AcpiError::Timeout);
+            }
+            spinning -= 10;
+            self.handler.stall(10);
+        }
+
+        Ok(())
+    }
+
+    /// Get the FACS, if the platform provides one.
+    pub fn facs(&self) -> Option<&Facs> {
+        self.facs.as_deref()
+    }
+
+    /// Program the waking vector in the FACS. This must be done before entering a sleep state in
+    /// which processor context is lost (e.g. `S3`), so the firmware can transfer control back to
+    /// OSPM when the system wakes. Returns [`AcpiError::UnsupportedWakingVector`] if the vector
+    /// can't be used on this platform.
+    pub fn set_waking_vector(&self, vector: WakingVector) -> Result<(), AcpiError> {
+        let Some(ref facs) = self.facs else {
+            return Err(AcpiError::TableNotFound(Signature::FACS));
+        };
+
+        let (firmware_waking_vector, x_firmware_waking_vector, wake_64bit) = match vector {
+            WakingVector::RealMode(address) => {
+                if address >= 0x10_0000 {
+                    return Err(AcpiError::UnsupportedWakingVector(vector));
+                }
+                (address, 0, false)
+            }
+            WakingVector::ProtectedMode(address) => {
+                if facs.version < 1 {
+                    return Err(AcpiError::UnsupportedWakingVector(vector));
+                }
+                (0, address as u64, false)
+            }
+            WakingVector::LongMode(address) => {
+                if !facs.supports_64bit_wake() {
+                    return Err(AcpiError::UnsupportedWakingVector(vector));
+                }
+                (0, address, true)
+            }
+        };
+
+        facs.set_waking_vector(firmware_waking_vector, x_firmware_waking_vector, wake_64bit);
+
+        Ok(())
+    }
+
+    /// Return the system to the working state after waking from the given sleep state, by
+    /// evaluating `_BFS` and `_WAK`. The wake GPEs armed before sleeping are disarmed, and
+    /// runtime GPEs are re-enabled.
+    pub fn leave_sleep_state(&self, state: SleepState) -> Result<(), AcpiError> {
+        info!("Leaving sleep state {:?}", state);
+
+        /*
+         * Restore the `S0` sleep type, if the platform provides one, so a stray write to `SLP_EN`
+         * does not re-enter the sleep state.
+         */
+        if self.registers.sleep_control.is_none() {
+            match self.sleep_type_for(0) {
+                Ok(s0) => self.registers.pm1_control_registers.set_sleep_types(s0.slp_typa, s0.slp_typb)?,
+                Err(AcpiError::SleepStateUnsupported(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.evaluate_sleep_method("\\_BFS", state.number())?;
+        self.evaluate_sleep_method("\\_WAK", state.number())?;
+        self.disarm_wake_gpes()?;
+        self.evaluate_sleep_method("\\_TTS", 0)?;
+
+        Ok(())
+    }
+
+    fn sleep_type_for(&self, state: u8) -> Result<SleepType, AcpiError> {
+        let path = AmlName::from_str(&alloc::format!("\\_S{}", state)).unwrap();
+        let Some(package) = self.evaluate_if_present(path, vec![]).map_err(AcpiError::Aml)? else {
+            return Err(AcpiError::SleepStateUnsupported(state));
+        };
+
+        /*
+         * The package should contain the values for `SLP_TYPa` and `SLP_TYPb`, followed by
+         * reserved elements. Some older firmware instead provides a single integer, with
+         * `SLP_TYPa` in the low byte and `SLP_TYPb` in the next byte.
+         */
+        let Object::Package(ref elements) = *package else {
+            return Err(AcpiError::Aml(AmlError::InvalidSleepPackage));
+        };
+        match elements.as_slice() {
+            [single] => {
+                let Object::Integer(value) = **single else {
+                    return Err(AcpiError::Aml(AmlError::InvalidSleepPackage));
+                };
+                Ok(SleepType { slp_typa: value as u8, slp_typb: (value >> 8) as u8 })
+            }
+            [a, b, ..] => {
+                let (Object::Integer(a), Object::Integer(b)) = (&**a, &**b) else {
+                    return Err(AcpiError::Aml(AmlError::InvalidSleepPackage));
+                };
+                Ok(SleepType { slp_typa: *a as u8, slp_typb: *b as u8 })
+            }
+            [] => Err(AcpiError::Aml(AmlError::InvalidSleepPackage)),
+        }
+    }
+
+    /// Evaluate one of the optional sleep methods under the root scope (e.g. `_PTS`), passing it
+    /// the sleep state number.
+    fn evaluate_sleep_method(&self, path: &str, state: u8) -> Result<(), AcpiError> {
+        let path = AmlName::from_str(path).unwrap();
+        match self.evaluate_if_present(path.clone(), vec![Object::Integer(state as u64).wrap()]) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                warn!("Error evaluating {}: {:?}", path, err);
+                Err(AcpiError::Aml(err))
+            }
+        }
+    }
+}
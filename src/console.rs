@@ -0,0 +1,310 @@
+//! Firmware describes the serial port it uses for console redirection in the SPCR, and the ports
+//! available for debugging in the DBG2. This module converts either description into a
+//! [`ConsoleDescriptor`], which can then be used to drive the port with a minimal polled
+//! [`SerialConsole`] - useful for getting output from early boot code before a real UART driver is
+//! available.
+
+use crate::{
+    AcpiError,
+    Handler,
+    PhysicalMapping,
+    address::{AddressSpace, GenericAddress},
+    sdt::{
+        dbg2::{Dbg2PortType, DeviceInfo},
+        spcr::{Spcr, SpcrFlowControl, SpcrInterfaceType},
+    },
+};
+use core::{fmt, num::NonZeroU32, ptr};
+use log::warn;
+
+/// The kind of UART used by a console, normalized from the various interface types described by
+/// the SPCR.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UartKind {
+    /// A 16550-compatible UART.
+    Uart16550,
+    /// A 16450-compatible UART. This is driven like a 16550, but does not have FIFOs.
+    Uart16450,
+    /// An Arm PL011 UART.
+    Pl011,
+    /// An Arm SBSA Generic UART, which implements a subset of the PL011.
+    SbsaGeneric,
+    /// An Arm SBSA Generic UART that only supports 32-bit accesses.
+    SbsaGeneric32Bit,
+    /// The Arm Debug Communications Channel, which is accessed through system registers.
+    ArmDcc,
+    /// The RISC-V SBI console, which is accessed through SBI calls.
+    RiscVSbi,
+    Other(SpcrInterfaceType),
+}
+
+impl From<SpcrInterfaceType> for UartKind {
+    fn from(interface_type: SpcrInterfaceType) -> Self {
+        match interface_type {
+            SpcrInterfaceType::Full16550
+            | SpcrInterfaceType::Nvidia16550
+            | SpcrInterfaceType::Generic16550
+            | SpcrInterfaceType::IntelLPSS => UartKind::Uart16550,
+            SpcrInterfaceType::Full16450 => UartKind::Uart16450,
+            SpcrInterfaceType::ArmPL011 => UartKind::Pl011,
+            SpcrInterfaceType::ArmSBSAGeneric => UartKind::SbsaGeneric,
+            SpcrInterfaceType::ArmSBSAGeneric32bit => UartKind::SbsaGeneric32Bit,
+            SpcrInterfaceType::ArmDCC => UartKind::ArmDcc,
+            SpcrInterfaceType::RiscVSbi => UartKind::RiscVSbi,
+            other => UartKind::Other(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Parity {
+    None,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopBits {
+    One,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConsoleInterrupt {
+    /// A PC-AT-compatible IRQ.
+    Irq(u8),
+    /// A Global System Interrupt.
+    Gsi(u32),
+}
+
+/// A normalized description of a serial console.
+#[derive(Clone, Copy, Debug)]
+pub struct ConsoleDescriptor {
+    pub kind: UartKind,
+    /// The UART's register block.
+    pub address: GenericAddress,
+    /// The baud rate the firmware configured the UART with. If this is `None`, the UART should be
+    /// used with its existing configuration.
+    pub baud_rate: Option<NonZeroU32>,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: SpcrFlowControl,
+    pub interrupt: Option<ConsoleInterrupt>,
+    /// The frequency of the UART's input clock, in Hz, if it is known.
+    pub clock_frequency: Option<NonZeroU32>,
+}
+
+impl ConsoleDescriptor {
+    /// Describe the console used by the firmware for console redirection. Returns `None` if
+    /// console redirection is disabled.
+    pub fn from_spcr(spcr: &Spcr) -> Result<Option<ConsoleDescriptor>, AcpiError> {
+        let Some(address) = spcr.base_address().transpose()? else {
+            return Ok(None);
+        };
+
+        let interrupt = match spcr.global_system_interrupt() {
+            Some(gsi) => Some(ConsoleInterrupt::Gsi(gsi)),
+            None => spcr.irq().map(ConsoleInterrupt::Irq),
+        };
+
+        Ok(Some(ConsoleDescriptor {
+            kind: UartKind::from(spcr.interface_type()),
+            address,
+            baud_rate: spcr.baud_rate(),
+            parity: match spcr.parity {
+                0 => Parity::None,
+                other => Parity::Reserved(other),
+            },
+            stop_bits: match spcr.stop_bits {
+                1 => StopBits::One,
+                other => StopBits::Reserved(other),
+            },
+            flow_control: spcr.flow_control(),
+            interrupt,
+            clock_frequency: spcr.uart_clock_frequency(),
+        }))
+    }
+
+    /// Describe a serial debug port from the DBG2. Returns `None` if the device is not a serial
+    /// port, or has no registers. The DBG2 does not describe how the port is configured, so it
+    /// should be used with its existing configuration.
+    pub fn from_dbg2_device(device: &DeviceInfo) -> Result<Option<ConsoleDescriptor>, AcpiError> {
+        let Dbg2PortType::Serial(interface_type) = device.port_type() else {
+            return Ok(None);
+        };
+        let Some(&address) = device.base_address_registers().first() else {
+            return Ok(None);
+        };
+
+        Ok(Some(ConsoleDescriptor {
+            kind: UartKind::from(interface_type),
+            address: GenericAddress::from_raw(address)?,
+            baud_rate: None,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: SpcrFlowControl::empty(),
+            interrupt: None,
+            clock_frequency: None,
+        }))
+    }
+
+    /// Map the console's registers, giving a [`SerialConsole`] that can be written to. Only
+    /// 16550-compatible and PL011-compatible UARTs are supported.
+    ///
+    /// ### Safety
+    /// `address` must be the address of the UART's registers, as the firmware reports it in the
+    /// SPCR or DBG2.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> Result<SerialConsole<H>, AcpiError> {
+        let uart = match self.kind {
+            UartKind::Uart16550 | UartKind::Uart16450 => Uart::Ns16550,
+            UartKind::Pl011 | UartKind::SbsaGeneric | UartKind::SbsaGeneric32Bit => Uart::Pl011,
+            other => {
+                warn!("Tried to map serial console of unsupported kind {:?}", other);
+                return Err(AcpiError::LibUnimplemented);
+            }
+        };
+
+        /*
+         * 16550 registers are one byte apart in I/O space, but often spaced out to the access
+         * width in memory. PL011 registers are always 32 bits wide.
+         */
+        let register_width = match (uart, self.address.access_size, self.address.bit_width) {
+            (Uart::Pl011, _, _) => 4,
+            (Uart::Ns16550, 3, _) | (Uart::Ns16550, 0, 32) => 4,
+            (Uart::Ns16550, 2, _) => 2,
+            _ => 1,
+        };
+
+        let registers = match self.address.address_space {
+            AddressSpace::SystemIo if uart == Uart::Ns16550 => Registers::Io(self.address.address as u16),
+            AddressSpace::SystemMemory => Registers::Memory(unsafe {
+                handler
+                    .map_physical_region(self.address.address as usize, uart.register_block_size(register_width))
+            }),
+            other => {
+                warn!("Tried to map serial console in unsupported address space {:?}", other);
+                return Err(AcpiError::LibUnimplemented);
+            }
+        };
+
+        Ok(SerialConsole { uart, registers, register_width, handler: handler.clone() })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Uart {
+    Ns16550,
+    Pl011,
+}
+
+impl Uart {
+    fn register_block_size(self, register_width: usize) -> usize {
+        match self {
+            Uart::Ns16550 => 8 * register_width,
+            Uart::Pl011 => 0x48,
+        }
+    }
+}
+
+enum Registers<H: Handler> {
+    Io(u16),
+    Memory(PhysicalMapping<H, u8>),
+}
+
+/// A minimal, polled, write-only driver for a 16550 or PL011 UART. The UART is expected to have
+/// already been configured by the firmware.
+pub struct SerialConsole<H: Handler> {
+    uart: Uart,
+    registers: Registers<H>,
+    register_width: usize,
+    handler: H,
+}
+
+impl<H> SerialConsole<H>
+where
+    H: Handler,
+{
+    const NS16550_THR: usize = 0;
+    const NS16550_LSR: usize = 5;
+    const NS16550_LSR_THRE: u32 = 1 << 5;
+    const PL011_DR: usize = 0x00;
+    const PL011_FR: usize = 0x18;
+    const PL011_FR_TXFF: u32 = 1 << 5;
+
+    /// Write a byte to the UART, waiting until it has space to accept it.
+    pub fn write_byte(&self, byte: u8) {
+        match self.uart {
+            Uart::Ns16550 => {
+                while self.read_register(Self::NS16550_LSR) & Self::NS16550_LSR_THRE == 0 {
+                    core::hint::spin_loop();
+                }
+                self.write_register(Self::NS16550_THR, byte as u32);
+            }
+            Uart::Pl011 => {
+                while self.read_register(Self::PL011_FR) & Self::PL011_FR_TXFF != 0 {
+                    core::hint::spin_loop();
+                }
+                self.write_register(Self::PL011_DR, byte as u32);
+            }
+        }
+    }
+
+    pub fn write_bytes(&self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_byte(byte);
+        }
+    }
+
+    /// Get the offset of a register from the base of the register block.
+    fn register_offset(&self, register: usize) -> usize {
+        match self.uart {
+            Uart::Ns16550 => register * self.register_width,
+            Uart::Pl011 => register,
+        }
+    }
+
+    fn read_register(&self, register: usize) -> u32 {
+        let offset = self.register_offset(register);
+        match &self.registers {
+            Registers::Io(port) => self.handler.read_io_u8(port + offset as u16) as u32,
+            Registers::Memory(mapping) => unsafe {
+                let ptr = mapping.virtual_start.as_ptr().add(offset);
+                match self.register_width {
+                    4 => ptr::read_volatile(ptr as *const u32),
+                    2 => ptr::read_volatile(ptr as *const u16) as u32,
+                    _ => ptr::read_volatile(ptr) as u32,
+                }
+            },
+        }
+    }
+
+    fn write_register(&self, register: usize, value: u32) {
+        let offset = self.register_offset(register);
+        match &self.registers {
+            Registers::Io(port) => self.handler.write_io_u8(port + offset as u16, value as u8),
+            Registers::Memory(mapping) => unsafe {
+                let ptr = mapping.virtual_start.as_ptr().add(offset);
+                match self.register_width {
+                    4 => ptr::write_volatile(ptr as *mut u32, value),
+                    2 => ptr::write_volatile(ptr as *mut u16, value as u16),
+                    _ => ptr::write_volatile(ptr, value as u8),
+                }
+            },
+        }
+    }
+}
+
+impl<H> fmt::Write for SerialConsole<H>
+where
+    H: Handler,
+{
+    /// Write a string to the UART, translating `\n` to `\r\n`.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            if byte == b'\n' {
+                self.write_byte(b'\r');
+            }
+            self.write_byte(byte);
+        }
+        Ok(())
+    }
+}
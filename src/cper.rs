@@ -0,0 +1,470 @@
+//! The UEFI Common Platform Error Record (CPER) format is used by firmware to describe hardware
+//! errors. Errors reported through APEI (e.g. by a GHES, or in the BERT's Boot Error Region) are
+//! made up of CPER sections, each identified by a GUID. This module decodes the most common section
+//! types.
+
+use crate::sdt::hest::GenericErrorData;
+use bit_field::BitField;
+use core::{mem, slice, str};
+use log::warn;
+use pci_types::PciAddress;
+
+/// Encodes a GUID in the mixed-endian byte order used by CPER.
+const fn guid(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> [u8; 16] {
+    let data1 = data1.to_le_bytes();
+    let data2 = data2.to_le_bytes();
+    let data3 = data3.to_le_bytes();
+    [
+        data1[0], data1[1], data1[2], data1[3], data2[0], data2[1], data3[0], data3[1], data4[0], data4[1],
+        data4[2], data4[3], data4[4], data4[5], data4[6], data4[7],
+    ]
+}
+
+pub const PROCESSOR_GENERIC_SECTION: [u8; 16] =
+    guid(0x9876ccad, 0x47b4, 0x4bdb, [0xb6, 0x5e, 0x16, 0xf1, 0x93, 0xc4, 0xf3, 0xdb]);
+pub const PLATFORM_MEMORY_SECTION: [u8; 16] =
+    guid(0xa5bc1114, 0x6f64, 0x4ede, [0xb8, 0x63, 0x3e, 0x83, 0xed, 0x7c, 0x83, 0xb1]);
+pub const PCIE_SECTION: [u8; 16] =
+    guid(0xd995e954, 0xbbc1, 0x430f, [0xad, 0x91, 0xb4, 0x4d, 0xcb, 0x3c, 0x6f, 0x35]);
+pub const ARM_PROCESSOR_SECTION: [u8; 16] =
+    guid(0xe19e3d16, 0xbc11, 0x11e4, [0x9c, 0xaa, 0xc2, 0x05, 0x1d, 0x5d, 0x46, 0xb0]);
+
+/// A decoded CPER section. Fixed-size sections are copied out of the error status block, so that
+/// they remain valid after the error has been acknowledged.
+#[derive(Clone, Copy, Debug)]
+pub enum CperSection<'a> {
+    ProcessorGeneric(ProcessorGenericSection),
+    Memory(MemoryErrorSection),
+    Pcie(PcieErrorSection),
+    Arm(ArmProcessorSection<'a>),
+    /// A section of a type that is not decoded by this library, or that is too short for its type.
+    Unknown {
+        section_type: [u8; 16],
+        data: &'a [u8],
+    },
+}
+
+impl<'a> CperSection<'a> {
+    pub fn parse(section_type: [u8; 16], data: &'a [u8]) -> CperSection<'a> {
+        let section = match section_type {
+            PROCESSOR_GENERIC_SECTION => read_section(data).map(CperSection::ProcessorGeneric),
+            PLATFORM_MEMORY_SECTION => read_section(data).map(CperSection::Memory),
+            PCIE_SECTION => read_section(data).map(CperSection::Pcie),
+            ARM_PROCESSOR_SECTION => ArmProcessorSection::parse(data).map(CperSection::Arm),
+            _ => return CperSection::Unknown { section_type, data },
+        };
+
+        section.unwrap_or_else(|| {
+            warn!("CPER section is too short for its type. Ignoring");
+            CperSection::Unknown { section_type, data }
+        })
+    }
+}
+
+impl<'a> GenericErrorData<'a> {
+    /// Decode the CPER section contained in this entry.
+    pub fn section(&self) -> CperSection<'a> {
+        CperSection::parse(self.header.section_type, self.data)
+    }
+}
+
+/// Copy a fixed-size section out of `data`. Sections may be longer than the structure we know
+/// about, if they were produced against a newer version of the specification.
+fn read_section<T: Copy>(data: &[u8]) -> Option<T> {
+    if data.len() < mem::size_of::<T>() {
+        return None;
+    }
+    Some(unsafe { (data.as_ptr() as *const T).read_unaligned() })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProcessorType {
+    Ia32X64,
+    Ia64,
+    Arm,
+    Reserved(u8),
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct ProcessorErrorType: u8 {
+        const CACHE = 1 << 0;
+        const TLB = 1 << 1;
+        const BUS = 1 << 2;
+        const MICRO_ARCHITECTURAL = 1 << 3;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ProcessorGenericSection {
+    pub validation_bits: u64,
+    pub processor_type: u8,
+    pub processor_isa: u8,
+    pub processor_error_type: u8,
+    pub operation: u8,
+    pub flags: u8,
+    pub level: u8,
+    _reserved: u16,
+    pub cpu_version_info: u64,
+    pub cpu_brand_string: [u8; 128],
+    pub processor_id: u64,
+    pub target_address: u64,
+    pub requestor_id: u64,
+    pub responder_id: u64,
+    pub instruction_ip: u64,
+}
+
+impl ProcessorGenericSection {
+    pub fn processor_type(&self) -> Option<ProcessorType> {
+        if !{ self.validation_bits }.get_bit(0) {
+            return None;
+        }
+        Some(match self.processor_type {
+            0 => ProcessorType::Ia32X64,
+            1 => ProcessorType::Ia64,
+            2 => ProcessorType::Arm,
+            other => ProcessorType::Reserved(other),
+        })
+    }
+
+    pub fn error_type(&self) -> Option<ProcessorErrorType> {
+        if { self.validation_bits }.get_bit(2) {
+            Some(ProcessorErrorType::from_bits_truncate(self.processor_error_type))
+        } else {
+            None
+        }
+    }
+
+    /// The ID of the processor that reported the error (e.g. the local APIC ID on x86, or the
+    /// `MPIDR_EL1` on Arm).
+    pub fn processor_id(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(8) { Some(self.processor_id) } else { None }
+    }
+
+    pub fn target_address(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(9) { Some(self.target_address) } else { None }
+    }
+
+    pub fn instruction_ip(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(12) { Some(self.instruction_ip) } else { None }
+    }
+
+    pub fn brand_string(&self) -> Option<&str> {
+        if !{ self.validation_bits }.get_bit(7) {
+            return None;
+        }
+        let length = self.cpu_brand_string.iter().position(|&b| b == 0).unwrap_or(self.cpu_brand_string.len());
+        str::from_utf8(&self.cpu_brand_string[0..length]).ok()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryErrorType {
+    Unknown,
+    NoError,
+    SingleBitEcc,
+    MultiBitEcc,
+    SingleSymbolChipkill,
+    MultiSymbolChipkill,
+    MasterAbort,
+    TargetAbort,
+    ParityError,
+    WatchdogTimeout,
+    InvalidAddress,
+    MirrorBroken,
+    MemorySparing,
+    ScrubCorrected,
+    ScrubUncorrected,
+    PhysicalMemoryMapOut,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryErrorSection {
+    pub validation_bits: u64,
+    pub error_status: u64,
+    pub physical_address: u64,
+    pub physical_address_mask: u64,
+    pub node: u16,
+    pub card: u16,
+    pub module: u16,
+    pub bank: u16,
+    pub device: u16,
+    pub row: u16,
+    pub column: u16,
+    pub bit_position: u16,
+    pub requestor_id: u64,
+    pub responder_id: u64,
+    pub target_id: u64,
+    pub memory_error_type: u8,
+    pub extended: u8,
+    pub rank_number: u16,
+    pub card_handle: u16,
+    pub module_handle: u16,
+}
+
+impl MemoryErrorSection {
+    pub fn physical_address(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(1) { Some(self.physical_address) } else { None }
+    }
+
+    pub fn physical_address_mask(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(2) { Some(self.physical_address_mask) } else { None }
+    }
+
+    pub fn node(&self) -> Option<u16> {
+        if { self.validation_bits }.get_bit(3) { Some(self.node) } else { None }
+    }
+
+    pub fn card(&self) -> Option<u16> {
+        if { self.validation_bits }.get_bit(4) { Some(self.card) } else { None }
+    }
+
+    pub fn module(&self) -> Option<u16> {
+        if { self.validation_bits }.get_bit(5) { Some(self.module) } else { None }
+    }
+
+    pub fn bank(&self) -> Option<u16> {
+        if { self.validation_bits }.get_bit(6) { Some(self.bank) } else { None }
+    }
+
+    pub fn memory_error_type(&self) -> Option<MemoryErrorType> {
+        if !{ self.validation_bits }.get_bit(14) {
+            return None;
+        }
+        Some(match self.memory_error_type {
+            0 => MemoryErrorType::Unknown,
+            1 => MemoryErrorType::NoError,
+            2 => MemoryErrorType::SingleBitEcc,
+            3 => MemoryErrorType::MultiBitEcc,
+            4 => MemoryErrorType::SingleSymbolChipkill,
+            5 => MemoryErrorType::MultiSymbolChipkill,
+            6 => MemoryErrorType::MasterAbort,
+            7 => MemoryErrorType::TargetAbort,
+            8 => MemoryErrorType::ParityError,
+            9 => MemoryErrorType::WatchdogTimeout,
+            10 => MemoryErrorType::InvalidAddress,
+            11 => MemoryErrorType::MirrorBroken,
+            12 => MemoryErrorType::MemorySparing,
+            13 => MemoryErrorType::ScrubCorrected,
+            14 => MemoryErrorType::ScrubUncorrected,
+            15 => MemoryErrorType::PhysicalMemoryMapOut,
+            other => MemoryErrorType::Reserved(other),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieDeviceId {
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class_code: [u8; 3],
+    pub function: u8,
+    pub device: u8,
+    pub segment: u16,
+    pub primary_bus: u8,
+    pub secondary_bus: u8,
+    /// The physical slot number is held in bits `3..16`.
+    pub slot: u16,
+    _reserved: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PcieErrorSection {
+    pub validation_bits: u64,
+    pub port_type: u32,
+    pub version: u32,
+    pub command: u16,
+    pub status: u16,
+    _reserved: u32,
+    pub device_id: PcieDeviceId,
+    pub device_serial_number: u64,
+    pub bridge_secondary_status: u16,
+    pub bridge_control: u16,
+    /// The PCIe Capability Structure of the device.
+    pub capability_structure: [u8; 60],
+    /// The PCIe Advanced Error Reporting Extended Capability Structure of the device.
+    pub aer_info: [u8; 96],
+}
+
+impl PcieErrorSection {
+    pub fn port_type(&self) -> Option<u32> {
+        if { self.validation_bits }.get_bit(0) { Some(self.port_type) } else { None }
+    }
+
+    pub fn pci_address(&self) -> Option<PciAddress> {
+        if !{ self.validation_bits }.get_bit(3) {
+            return None;
+        }
+        let device_id = self.device_id;
+        if device_id.device > 31 || device_id.function > 7 {
+            return None;
+        }
+        Some(PciAddress::new(device_id.segment, device_id.primary_bus, device_id.device, device_id.function))
+    }
+
+    pub fn device_serial_number(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(4) { Some(self.device_serial_number) } else { None }
+    }
+
+    pub fn aer_info(&self) -> Option<&[u8; 96]> {
+        if { self.validation_bits }.get_bit(7) { Some(&self.aer_info) } else { None }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ArmProcessorErrorHeader {
+    pub validation_bits: u32,
+    pub error_info_count: u16,
+    pub context_info_count: u16,
+    pub section_length: u32,
+    pub error_affinity_level: u8,
+    _reserved: [u8; 3],
+    pub mpidr_el1: u64,
+    pub midr_el1: u64,
+    pub running_state: u32,
+    pub psci_state: u32,
+}
+
+/// An Arm processor error section, which describes one or more errors on a single processor,
+/// optionally along with the processor's register context.
+#[derive(Clone, Copy, Debug)]
+pub struct ArmProcessorSection<'a> {
+    pub header: ArmProcessorErrorHeader,
+    pub error_info: &'a [ArmErrorInfo],
+    /// The register context structures, followed by any vendor-specific data.
+    context: &'a [u8],
+}
+
+impl<'a> ArmProcessorSection<'a> {
+    fn parse(data: &'a [u8]) -> Option<ArmProcessorSection<'a>> {
+        let header = read_section::<ArmProcessorErrorHeader>(data)?;
+        let length = (header.section_length as usize).min(data.len());
+        let error_info_end = mem::size_of::<ArmProcessorErrorHeader>()
+            + header.error_info_count as usize * mem::size_of::<ArmErrorInfo>();
+        if error_info_end > length {
+            return None;
+        }
+
+        let error_info = unsafe {
+            slice::from_raw_parts(
+                data.as_ptr().add(mem::size_of::<ArmProcessorErrorHeader>()) as *const ArmErrorInfo,
+                header.error_info_count as usize,
+            )
+        };
+        Some(ArmProcessorSection { header, error_info, context: &data[error_info_end..length] })
+    }
+
+    pub fn mpidr(&self) -> Option<u64> {
+        if { self.header.validation_bits }.get_bit(0) { Some(self.header.mpidr_el1) } else { None }
+    }
+
+    pub fn midr(&self) -> u64 {
+        self.header.midr_el1
+    }
+
+    /// Whether the processor was running when the error occurred, if this is reported. If it was
+    /// not, `header.psci_state` holds the PSCI state of the processor.
+    pub fn running_state(&self) -> Option<bool> {
+        if { self.header.validation_bits }.get_bit(2) {
+            Some({ self.header.running_state }.get_bit(0))
+        } else {
+            None
+        }
+    }
+
+    pub fn contexts(&self) -> ArmContextIter<'a> {
+        ArmContextIter { bytes: self.context, remaining: self.header.context_info_count }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArmErrorType {
+    Cache,
+    Tlb,
+    Bus,
+    MicroArchitectural,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ArmErrorInfo {
+    pub version: u8,
+    pub length: u8,
+    pub validation_bits: u16,
+    pub typ: u8,
+    pub multiple_error: u16,
+    pub flags: u8,
+    pub error_information: u64,
+    pub virtual_fault_address: u64,
+    pub physical_fault_address: u64,
+}
+
+impl ArmErrorInfo {
+    pub fn error_type(&self) -> ArmErrorType {
+        match self.typ {
+            0 => ArmErrorType::Cache,
+            1 => ArmErrorType::Tlb,
+            2 => ArmErrorType::Bus,
+            3 => ArmErrorType::MicroArchitectural,
+            other => ArmErrorType::Reserved(other),
+        }
+    }
+
+    pub fn virtual_fault_address(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(3) { Some(self.virtual_fault_address) } else { None }
+    }
+
+    pub fn physical_fault_address(&self) -> Option<u64> {
+        if { self.validation_bits }.get_bit(4) { Some(self.physical_fault_address) } else { None }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ArmContextHeader {
+    pub version: u16,
+    pub register_context_type: u16,
+    pub register_array_size: u32,
+}
+
+/// A register context of an [`ArmProcessorSection`]. The layout of `registers` is determined by
+/// the context's `register_context_type`.
+#[derive(Clone, Copy, Debug)]
+pub struct ArmContext<'a> {
+    pub header: ArmContextHeader,
+    pub registers: &'a [u8],
+}
+
+#[derive(Clone, Debug)]
+pub struct ArmContextIter<'a> {
+    bytes: &'a [u8],
+    remaining: u16,
+}
+
+impl<'a> Iterator for ArmContextIter<'a> {
+    type Item = ArmContext<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let header = read_section::<ArmContextHeader>(self.bytes)?;
+        let end = mem::size_of::<ArmContextHeader>() + header.register_array_size as usize;
+        if end > self.bytes.len() {
+            warn!("Arm processor context extends past the end of its section. Ignoring");
+            return None;
+        }
+
+        let registers = &self.bytes[mem::size_of::<ArmContextHeader>()..end];
+        self.bytes = &self.bytes[end..];
+        self.remaining -= 1;
+        Some(ArmContext { header, registers })
+    }
+}
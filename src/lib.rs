@@ -29,7 +29,7 @@
 //! * Use [`AcpiTables::from_rsdt`] if you have the physical address of the RSDT/XSDT
 //!
 //! Once you have an [`AcpiTables`], you can search for relevant tables, or use the higher-level
-//! interfaces, such as [`PowerProfile`], or [`HpetInfo`].
+//! interfaces, such as [`PowerProfile`], [`HpetInfo`], or [`GtdtInfo`].
 //!
 //! If you have the `aml` feature enabled then you can construct an
 //! [AML interpreter](`crate::aml::Interpreter`) by first constructing an
@@ -55,6 +55,8 @@ extern crate alloc;
 pub mod address;
 #[cfg(feature = "aml")]
 pub mod aml;
+pub mod console;
+pub mod cper;
 #[cfg(feature = "alloc")]
 pub mod platform;
 pub mod registers;
@@ -62,7 +64,7 @@ pub mod rsdp;
 pub mod sdt;
 
 pub use pci_types::PciAddress;
-pub use sdt::{fadt::PowerProfile, hpet::HpetInfo, madt::MadtError};
+pub use sdt::{fadt::PowerProfile, gtdt::GtdtInfo, hpet::HpetInfo, madt::MadtError};
 
 use crate::sdt::{SdtHeader, Signature};
 use core::{
@@ -275,6 +277,22 @@ pub enum AcpiError {
     InvalidDsdtAddress,
     InvalidMadt(MadtError),
     InvalidGenericAddress,
+    /// The given General-Purpose Event does not exist in the GPE block it was used with.
+    InvalidGpe(usize),
+    /// The platform does not support the given sleep state, as it does not provide an `\_Sx` object
+    /// for it.
+    SleepStateUnsupported(u8),
+    /// The given waking vector can't be used on this platform, either because it is out of range for
+    /// its mode, or because the FACS does not support the mode.
+    UnsupportedWakingVector(sdt::facs::WakingVector),
+    /// The platform does not support resetting the system via the FADT reset register.
+    ResetUnsupported,
+    /// The given HPET comparator does not exist, or does not support the requested configuration.
+    HpetComparatorUnsupported(u8),
+    /// An operation on the platform's error record store did not complete successfully.
+    ErstCommandFailed(sdt::erst::CommandStatus),
+    /// The platform's watchdog does not provide any instructions for the given action.
+    WatchdogActionUnsupported(sdt::wdat::WatchdogAction),
 
     Timeout,
 
@@ -455,6 +473,19 @@ pub trait Handler: Clone {
     /// time supported, and should relinquish the processor.
     fn sleep(&self, milliseconds: u64);
 
+    /// Read from a register in the `FunctionalFixedHardware` address space. The meaning of these
+    /// registers is processor-specific (e.g. on x86, `address` is usually an MSR), so the whole GAS
+    /// is passed, and the host is responsible for any masking.
+    fn read_ffh(&self, _gas: address::GenericAddress) -> Result<u64, AcpiError> {
+        Err(AcpiError::HostUnimplemented)
+    }
+
+    /// Write to a register in the `FunctionalFixedHardware` address space. See
+    /// [`Handler::read_ffh`].
+    fn write_ffh(&self, _gas: address::GenericAddress, _value: u64) -> Result<(), AcpiError> {
+        Err(AcpiError::HostUnimplemented)
+    }
+
     #[cfg(feature = "aml")]
     fn create_mutex(&self) -> Handle;
 
@@ -0,0 +1,287 @@
+use crate::{
+    AcpiError,
+    AcpiTables,
+    Handler,
+    PhysicalMapping,
+    address::{GasMapper, MappedGas},
+    sdt::{
+        Signature,
+        erst::{CommandStatus, Erst, Instruction, InstructionEntry, SerializationAction},
+    },
+};
+use alloc::{
+    alloc::{Allocator, Global},
+    vec::Vec,
+};
+use core::{ptr, slice};
+use log::warn;
+
+/// Provides access to the platform's persistent error record store, as described by the ERST.
+/// Records are UEFI CPER records, and are identified by the record ID in their header. Operations
+/// are performed by interpreting the serialization instructions provided by the firmware.
+///
+/// Writing, reading, and clearing records take several actions, and so need exclusive access to
+/// the store. Users sharing a store between processors must provide their own locking.
+pub struct ErrorRecordStore<H: Handler, A: Allocator = Global> {
+    handler: H,
+    instructions: Vec<(InstructionEntry, Option<MappedGas<H>>), A>,
+    error_log: Option<PhysicalMapping<H, u8>>,
+    error_log_attributes: u64,
+}
+
+/// The state of the ERST interpreter while executing an action.
+struct Context {
+    value: u64,
+    var1: u64,
+    var2: u64,
+    src_base: u64,
+    dst_base: u64,
+}
+
+impl<H> ErrorRecordStore<H, Global>
+where
+    H: Handler,
+{
+    pub fn new(tables: &AcpiTables<H>) -> Result<ErrorRecordStore<H, Global>, AcpiError> {
+        Self::new_in(tables, Global)
+    }
+}
+
+impl<H, A> ErrorRecordStore<H, A>
+where
+    H: Handler,
+    A: Allocator,
+{
+    /// How long to wait for the firmware to complete an operation, in microseconds.
+    const TIMEOUT: u64 = 1_000_000;
+    /// The most instructions that will be executed for a single action, so that a loop of `GOTO`
+    /// instructions can't hang the interpreter.
+    const MAX_INSTRUCTIONS: usize = 0x10000;
+
+    pub fn new_in(tables: &AcpiTables<H>, allocator: A) -> Result<ErrorRecordStore<H, A>, AcpiError> {
+        let Some(erst) = tables.find_table::<Erst>() else { Err(AcpiError::TableNotFound(Signature::ERST))? };
+
+        let mut mapper = GasMapper::new(tables.handler.clone());
+        let mut instructions = Vec::new_in(allocator);
+        for entry in erst.get().entries() {
+            let register = if entry.register_region.is_empty() {
+                None
+            } else {
+                Some(unsafe { mapper.map_gas(entry.register()?)? })
+            };
+            instructions.push((*entry, register));
+        }
+
+        let mut store = ErrorRecordStore {
+            handler: tables.handler.clone(),
+            instructions,
+            error_log: None,
+            error_log_attributes: 0,
+        };
+
+        let error_log_address = store.execute(SerializationAction::GetErrorLogAddressRange, 0)?;
+        let error_log_length = store.execute(SerializationAction::GetErrorLogAddressRangeLength, 0)?;
+        store.error_log_attributes = store.execute(SerializationAction::GetErrorLogAddressRangeAttributes, 0)?;
+        if error_log_address != 0 && error_log_length != 0 {
+            store.error_log = Some(unsafe {
+                store.handler.map_physical_region(error_log_address as usize, error_log_length as usize)
+            });
+        }
+
+        Ok(store)
+    }
+
+    /// Whether records are stored in non-volatile memory, and so persist across reboots. This is
+    /// reported by bit `1` of the error log attributes - bit `0` is reserved.
+    pub fn is_non_volatile(&self) -> bool {
+        self.error_log_attributes & (1 << 1) != 0
+    }
+
+    /// The number of records in the store.
+    pub fn record_count(&self) -> Result<u64, AcpiError> {
+        self.execute(SerializationAction::GetRecordCount, 0)
+    }
+
+    /// The ID of a record in the store, or `None` if the store is empty. After a record has been
+    /// read, this is the ID of the next record, and so can be used to iterate over the store.
+    pub fn next_record_id(&self) -> Result<Option<u64>, AcpiError> {
+        let id = self.execute(SerializationAction::GetRecordIdentifier, 0)?;
+        Ok(if id == u64::MAX { None } else { Some(id) })
+    }
+
+    /// Write a CPER record to the store. If a record with the same ID already exists, it is
+    /// replaced.
+    pub fn write_record(&mut self, record: &[u8]) -> Result<(), AcpiError> {
+        let error_log = match &self.error_log {
+            Some(mapping) if record.len() <= mapping.region_length => mapping,
+            _ => return Err(AcpiError::ErstCommandFailed(CommandStatus::NotEnoughSpace)),
+        };
+        unsafe { ptr::copy_nonoverlapping(record.as_ptr(), error_log.virtual_start.as_ptr(), record.len()) };
+
+        self.perform_operation(
+            SerializationAction::BeginWriteOperation,
+            &[(SerializationAction::SetRecordOffset, 0)],
+        )
+    }
+
+    /// Read the record with the given ID into `buffer`. A record ID of `0` reads the first record
+    /// in the store. Returns the length of the record, which may be larger than `buffer`, in which
+    /// case the record is truncated.
+    pub fn read_record(&mut self, record_id: u64, buffer: &mut [u8]) -> Result<usize, AcpiError> {
+        self.perform_operation(
+            SerializationAction::BeginReadOperation,
+            &[(SerializationAction::SetRecordOffset, 0), (SerializationAction::SetRecordIdentifier, record_id)],
+        )?;
+
+        /*
+         * The length of the record is found in the CPER record header.
+         */
+        let error_log = self.error_log_bytes();
+        let length = match error_log.get(20..24) {
+            Some(length) => (u32::from_le_bytes(length.try_into().unwrap()) as usize).min(error_log.len()),
+            None => 0,
+        };
+        let copied = length.min(buffer.len());
+        buffer[0..copied].copy_from_slice(&error_log[0..copied]);
+        Ok(length)
+    }
+
+    /// Remove the record with the given ID from the store.
+    pub fn clear_record(&mut self, record_id: u64) -> Result<(), AcpiError> {
+        self.perform_operation(
+            SerializationAction::BeginClearOperation,
+            &[(SerializationAction::SetRecordIdentifier, record_id)],
+        )
+    }
+
+    /// Execute the instructions of the given action. `value` is the input of the action (e.g. the
+    /// record ID for `SET_RECORD_IDENTIFIER`), and the output of the action (e.g. the record count
+    /// for `GET_RECORD_COUNT`) is returned.
+    fn execute(&self, action: SerializationAction, value: u64) -> Result<u64, AcpiError> {
+        let mut context = Context { value, var1: 0, var2: 0, src_base: 0, dst_base: 0 };
+        let mut stalled = 0;
+        let mut ip = 0;
+        let mut executed = 0;
+
+        while let Some((entry, register)) =
+            self.instructions.iter().filter(|(entry, _)| SerializationAction::from(entry.action) == action).nth(ip)
+        {
+            if executed >= Self::MAX_INSTRUCTIONS {
+                return Err(AcpiError::Timeout);
+            }
+            executed += 1;
+            ip += 1;
+            let register = || register.as_ref().ok_or(AcpiError::InvalidGenericAddress);
+
+            match entry.instruction() {
+                Instruction::ReadRegister => context.value = entry.read_register(register()?)?,
+                Instruction::ReadRegisterValue => {
+                    context.value = (entry.read_register(register()?)? == entry.value) as u64;
+                }
+                Instruction::WriteRegister => entry.write_register(register()?, context.value)?,
+                Instruction::WriteRegisterValue => entry.write_register(register()?, entry.value)?,
+                Instruction::Noop => (),
+                Instruction::LoadVar1 => context.var1 = entry.read_register(register()?)?,
+                Instruction::LoadVar2 => context.var2 = entry.read_register(register()?)?,
+                Instruction::StoreVar1 => entry.write_register(register()?, context.var1)?,
+                Instruction::Add => context.var1 = context.var1.wrapping_add(context.var2),
+                Instruction::Subtract => context.var1 = context.var1.wrapping_sub(context.var2),
+                Instruction::AddValue => {
+                    let register = register()?;
+                    let value = entry.read_register(register)?.wrapping_add(entry.value);
+                    entry.write_register(register, value)?;
+                }
+                Instruction::SubtractValue => {
+                    let register = register()?;
+                    let value = entry.read_register(register)?.wrapping_sub(entry.value);
+                    entry.write_register(register, value)?;
+                }
+                Instruction::Stall => self.handler.stall(entry.value),
+                Instruction::StallWhileTrue => {
+                    let register = register()?;
+                    while entry.read_register(register)? == entry.value {
+                        if stalled >= Self::TIMEOUT {
+                            return Err(AcpiError::Timeout);
+                        }
+                        self.handler.stall(context.var1);
+                        stalled += context.var1.max(1);
+                    }
+                }
+                Instruction::SkipNextInstructionIfTrue => {
+                    if entry.read_register(register()?)? == entry.value {
+                        ip += 1;
+                    }
+                }
+                Instruction::Goto => ip = entry.value as usize,
+                Instruction::SetSrcAddressBase => context.src_base = entry.read_register(register()?)?,
+                Instruction::SetDstAddressBase => context.dst_base = entry.read_register(register()?)?,
+                Instruction::MoveData => {
+                    let offset = entry.read_register(register()?)?;
+                    let src = context.src_base.checked_add(offset).ok_or(AcpiError::InvalidGenericAddress)?;
+                    let dst = context.dst_base.checked_add(offset).ok_or(AcpiError::InvalidGenericAddress)?;
+                    self.move_data(src, dst, context.var2 as usize);
+                }
+                Instruction::Reserved(other) => {
+                    warn!("Unrecognised instruction in ERST of type {}", other);
+                    return Err(AcpiError::LibUnimplemented);
+                }
+            }
+        }
+
+        Ok(context.value)
+    }
+
+    /// Begin an operation, execute the given actions to set it up, and then execute it. The
+    /// operation is always ended, even if one of the steps fails, so that the firmware is not left
+    /// mid-operation.
+    fn perform_operation(
+        &self,
+        begin: SerializationAction,
+        setup: &[(SerializationAction, u64)],
+    ) -> Result<(), AcpiError> {
+        let result = self
+            .execute(begin, 0)
+            .and_then(|_| setup.iter().try_for_each(|&(action, value)| self.execute(action, value).map(|_| ())))
+            .and_then(|()| self.execute_operation());
+        let end = self.execute(SerializationAction::EndOperation, 0);
+        result.and(end.map(|_| ()))
+    }
+
+    /// Execute the current operation, and wait for the firmware to complete it.
+    fn execute_operation(&self) -> Result<(), AcpiError> {
+        self.execute(SerializationAction::ExecuteOperation, 0)?;
+
+        let mut spinning = Self::TIMEOUT;
+        while self.execute(SerializationAction::CheckBusyStatus, 0)? != 0 {
+            if spinning == 0 {
+                return Err(AcpiError::Timeout);
+            }
+            spinning = spinning.saturating_sub(100);
+            self.handler.stall(100);
+        }
+
+        match CommandStatus::from(self.execute(SerializationAction::GetCommandStatus, 0)? as u8) {
+            CommandStatus::Success => Ok(()),
+            other => Err(AcpiError::ErstCommandFailed(other)),
+        }
+    }
+
+    fn move_data(&self, src: u64, dst: u64, length: usize) {
+        if length == 0 {
+            return;
+        }
+
+        let src = unsafe { self.handler.map_physical_region::<u8>(src as usize, length) };
+        let dst = unsafe { self.handler.map_physical_region::<u8>(dst as usize, length) };
+        unsafe { ptr::copy(src.virtual_start.as_ptr(), dst.virtual_start.as_ptr(), length) };
+    }
+
+    fn error_log_bytes(&self) -> &[u8] {
+        match &self.error_log {
+            Some(mapping) => unsafe {
+                slice::from_raw_parts(mapping.virtual_start.as_ptr(), mapping.region_length)
+            },
+            None => &[],
+        }
+    }
+}
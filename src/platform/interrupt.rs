@@ -6,12 +6,13 @@ use crate::{
     MadtError,
     sdt::{
         Signature,
-        madt::{Madt, MadtEntry, parse_mps_inti_flags},
+        madt::{GiccEntry, Madt, MadtEntry, parse_mps_inti_flags},
     },
 };
 use alloc::{alloc::Global, vec::Vec};
 use bit_field::BitField;
 use core::{alloc::Allocator, pin::Pin};
+use log::warn;
 
 pub use crate::sdt::madt::{Polarity, TriggerMode};
 
@@ -26,6 +27,16 @@ pub enum InterruptModel<A: Allocator = Global> {
     /// XAPIC, or X2APIC). These are likely to be found on x86 and x86_64 systems and are made up of a Local APIC
     /// for each core and one or more I/O APICs to handle external interrupts.
     Apic(Apic<A>),
+
+    /// Describes an interrupt controller based around the Arm Generic Interrupt Controller (GIC). These are
+    /// found on Arm systems, and are made up of a CPU interface for each core, a distributor, and, from GICv3,
+    /// redistributors and optionally Interrupt Translation Services (ITSes) to handle MSIs.
+    Gic(Gic<A>),
+
+    /// Describes the interrupt controllers of a RISC-V system. Each hart has a local interrupt controller, and
+    /// optionally an Incoming MSI Controller (IMSIC). External interrupts are routed to harts by Advanced
+    /// Platform-Level Interrupt Controllers (APLICs) or Platform-Level Interrupt Controllers (PLICs).
+    Riscv(Riscv<A>),
 }
 
 impl InterruptModel<Global> {
@@ -64,7 +75,13 @@ impl<A: Allocator + Clone> InterruptModel<A> {
                 | MadtEntry::Gicd(_)
                 | MadtEntry::GicMsiFrame(_)
                 | MadtEntry::GicRedistributor(_)
-                | MadtEntry::GicInterruptTranslationService(_) => {}
+                | MadtEntry::GicInterruptTranslationService(_) => {
+                    return Self::from_gic_model_in(madt.get(), allocator);
+                }
+
+                MadtEntry::Rintc(_) | MadtEntry::Imsic(_) | MadtEntry::Aplic(_) | MadtEntry::Plic(_) => {
+                    return Self::from_riscv_model_in(madt.get(), allocator);
+                }
 
                 MadtEntry::NmiSource(_) => (),
                 MadtEntry::MultiprocessorWakeup(_) => (),
@@ -127,6 +144,8 @@ impl<A: Allocator + Clone> InterruptModel<A> {
                         local_apic_id: entry.apic_id as u32,
                         state,
                         is_ap,
+                        gicc: None,
+                        rintc: None,
                     };
 
                     if is_ap {
@@ -151,6 +170,8 @@ impl<A: Allocator + Clone> InterruptModel<A> {
                         local_apic_id: entry.x2apic_id,
                         state,
                         is_ap,
+                        gicc: None,
+                        rintc: None,
                     };
 
                     if is_ap {
@@ -247,6 +268,236 @@ impl<A: Allocator + Clone> InterruptModel<A> {
             Some(ProcessorInfo::new_in(boot_processor.unwrap(), application_processors)),
         ))
     }
+
+    fn from_gic_model_in(
+        madt: Pin<&Madt>,
+        allocator: A,
+    ) -> Result<(InterruptModel<A>, Option<ProcessorInfo<A>>), AcpiError> {
+        let mut redistributor_count = 0;
+        let mut its_count = 0;
+        let mut msi_frame_count = 0;
+        let mut processor_count = 0usize;
+
+        // Do a pass over the entries so we know how much space we should reserve in the vectors
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::GicRedistributor(_) => redistributor_count += 1,
+                MadtEntry::GicInterruptTranslationService(_) => its_count += 1,
+                MadtEntry::GicMsiFrame(_) => msi_frame_count += 1,
+                MadtEntry::Gicc(_) => processor_count += 1,
+                _ => (),
+            }
+        }
+
+        let mut distributor = None;
+        let mut redistributors = Vec::with_capacity_in(redistributor_count, allocator.clone());
+        let mut its = Vec::with_capacity_in(its_count, allocator.clone());
+        let mut msi_frames = Vec::with_capacity_in(msi_frame_count, allocator.clone());
+        let mut application_processors = Vec::with_capacity_in(processor_count.saturating_sub(1), allocator);
+        let mut boot_processor = None;
+
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::Gicc(entry) => {
+                    /*
+                     * The MADT doesn't identify the boot processor on Arm platforms. As with the
+                     * APIC, we treat the first enabled processor as the boot processor - the host
+                     * should check this against its own `MPIDR` if it matters.
+                     */
+                    let flags = entry.flags;
+                    let is_enabled = flags.get_bit(0);
+                    let is_ap = boot_processor.is_some() || !is_enabled;
+
+                    let state = match (is_ap, is_enabled) {
+                        (_, false) => ProcessorState::Disabled,
+                        (true, true) => ProcessorState::Parked,
+                        (false, true) => ProcessorState::Running,
+                    };
+
+                    let processor = Processor {
+                        processor_uid: entry.processor_uid,
+                        local_apic_id: entry.cpu_interface_number,
+                        state,
+                        is_ap,
+                        gicc: Some(GiccInfo::from_entry(entry)),
+                        rintc: None,
+                    };
+
+                    if is_ap {
+                        application_processors.push(processor);
+                    } else {
+                        boot_processor = Some(processor);
+                    }
+                }
+
+                MadtEntry::Gicd(entry) => {
+                    if distributor.is_some() {
+                        warn!("MADT contains more than one GIC Distributor entry. Ignoring");
+                        continue;
+                    }
+                    distributor = Some(GicDistributor {
+                        id: entry.gic_id,
+                        address: entry.physical_base_address,
+                        system_vector_base: entry.system_vector_base,
+                        version: match entry.gic_version {
+                            0 => GicVersion::Unspecified,
+                            1 => GicVersion::V1,
+                            2 => GicVersion::V2,
+                            3 => GicVersion::V3,
+                            4 => GicVersion::V4,
+                            other => GicVersion::Reserved(other),
+                        },
+                    });
+                }
+
+                MadtEntry::GicRedistributor(entry) => {
+                    redistributors.push(GicRedistributor {
+                        discovery_range_base_address: entry.discovery_range_base_address,
+                        discovery_range_length: entry.discovery_range_length,
+                    });
+                }
+
+                MadtEntry::GicInterruptTranslationService(entry) => {
+                    its.push(GicIts { id: entry.id, address: entry.physical_base_address });
+                }
+
+                MadtEntry::GicMsiFrame(entry) => {
+                    let overrides_spis = { entry.flags }.get_bit(0);
+                    msi_frames.push(GicMsiFrame {
+                        id: entry.frame_id,
+                        address: entry.physical_base_address,
+                        spis: if overrides_spis { Some((entry.spi_base, entry.spi_count)) } else { None },
+                    });
+                }
+
+                MadtEntry::NmiSource(_) | MadtEntry::MultiprocessorWakeup(_) => {}
+
+                _ => {
+                    return Err(AcpiError::InvalidMadt(MadtError::UnexpectedEntry));
+                }
+            }
+        }
+
+        let Some(distributor) = distributor else {
+            return Err(AcpiError::InvalidMadt(MadtError::GicdNotFound));
+        };
+
+        Ok((
+            InterruptModel::Gic(Gic { distributor, redistributors, its, msi_frames }),
+            boot_processor.map(|boot_processor| ProcessorInfo::new_in(boot_processor, application_processors)),
+        ))
+    }
+
+    fn from_riscv_model_in(
+        madt: Pin<&Madt>,
+        allocator: A,
+    ) -> Result<(InterruptModel<A>, Option<ProcessorInfo<A>>), AcpiError> {
+        let mut aplic_count = 0;
+        let mut plic_count = 0;
+        let mut processor_count = 0usize;
+
+        // Do a pass over the entries so we know how much space we should reserve in the vectors
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::Aplic(_) => aplic_count += 1,
+                MadtEntry::Plic(_) => plic_count += 1,
+                MadtEntry::Rintc(_) => processor_count += 1,
+                _ => (),
+            }
+        }
+
+        let mut imsic = None;
+        let mut aplics = Vec::with_capacity_in(aplic_count, allocator.clone());
+        let mut plics = Vec::with_capacity_in(plic_count, allocator.clone());
+        let mut application_processors = Vec::with_capacity_in(processor_count.saturating_sub(1), allocator);
+        let mut boot_processor = None;
+
+        for entry in madt.entries() {
+            match entry {
+                MadtEntry::Rintc(entry) => {
+                    /*
+                     * As on Arm, the MADT doesn't identify the boot hart, so we treat the first
+                     * enabled hart as the boot hart. The host should check this against the hart
+                     * ID it was booted on.
+                     */
+                    let flags = entry.flags;
+                    let is_enabled = flags.get_bit(0);
+                    let is_ap = boot_processor.is_some() || !is_enabled;
+
+                    let state = match (is_ap, is_enabled) {
+                        (_, false) => ProcessorState::Disabled,
+                        (true, true) => ProcessorState::Parked,
+                        (false, true) => ProcessorState::Running,
+                    };
+
+                    let processor = Processor {
+                        processor_uid: entry.acpi_processor_uid,
+                        local_apic_id: 0,
+                        state,
+                        is_ap,
+                        gicc: None,
+                        rintc: Some(RintcInfo {
+                            hart_id: entry.hart_id,
+                            external_interrupt_controller_id: entry.external_interrupt_controller_id,
+                            imsic_base_address: entry.imsic_base_address,
+                            imsic_size: entry.imsic_size,
+                            online_capable: flags.get_bit(1),
+                        }),
+                    };
+
+                    if is_ap {
+                        application_processors.push(processor);
+                    } else {
+                        boot_processor = Some(processor);
+                    }
+                }
+
+                MadtEntry::Imsic(entry) => {
+                    imsic = Some(Imsic {
+                        num_ids: entry.num_ids,
+                        num_guest_ids: entry.num_guest_ids,
+                        guest_index_bits: entry.guest_index_bits,
+                        hart_index_bits: entry.hart_index_bits,
+                        group_index_bits: entry.group_index_bits,
+                        group_index_shift: entry.group_index_shift,
+                    });
+                }
+
+                MadtEntry::Aplic(entry) => {
+                    aplics.push(Aplic {
+                        id: entry.aplic_id,
+                        hardware_id: entry.hardware_id,
+                        num_idcs: entry.num_idcs,
+                        num_sources: entry.num_sources,
+                        global_system_interrupt_base: entry.global_system_interrupt_base,
+                        address: entry.aplic_address,
+                        size: entry.aplic_size,
+                    });
+                }
+
+                MadtEntry::Plic(entry) => {
+                    plics.push(Plic {
+                        id: entry.plic_id,
+                        hardware_id: entry.hardware_id,
+                        num_irqs: entry.num_irqs,
+                        max_priority: entry.max_priority,
+                        global_system_interrupt_base: entry.global_system_interrupt_base,
+                        address: entry.plic_address,
+                        size: entry.plic_size,
+                    });
+                }
+
+                _ => {
+                    return Err(AcpiError::InvalidMadt(MadtError::UnexpectedEntry));
+                }
+            }
+        }
+
+        Ok((
+            InterruptModel::Riscv(Riscv { imsic, aplics, plics }),
+            boot_processor.map(|boot_processor| ProcessorInfo::new_in(boot_processor, application_processors)),
+        ))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -332,3 +583,178 @@ impl<A: Allocator> Apic<A> {
         }
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct Gic<A: Allocator = Global> {
+    pub distributor: GicDistributor,
+    /// The discovery ranges of the GICv3+ redistributors. If this is empty on a GICv3+ system, the
+    /// redistributor of each processor is instead given by [`GiccInfo::gicr_base_address`].
+    pub redistributors: Vec<GicRedistributor, A>,
+    pub its: Vec<GicIts, A>,
+    /// The MSI frames of a GICv2m, if present.
+    pub msi_frames: Vec<GicMsiFrame, A>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GicVersion {
+    /// The firmware does not report the version of the GIC, and it must be discovered from the
+    /// hardware.
+    Unspecified,
+    V1,
+    V2,
+    V3,
+    V4,
+    Reserved(u8),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GicDistributor {
+    pub id: u32,
+    /// The physical address of the distributor's register block.
+    pub address: u64,
+    /// The global system interrupt number where this distributor's interrupt inputs start. This
+    /// should always be `0`.
+    pub system_vector_base: u32,
+    pub version: GicVersion,
+}
+
+/// A range of memory containing one or more contiguous GICv3+ redistributors.
+#[derive(Debug, Clone, Copy)]
+pub struct GicRedistributor {
+    pub discovery_range_base_address: u64,
+    pub discovery_range_length: u32,
+}
+
+/// A GICv3+ Interrupt Translation Service.
+#[derive(Debug, Clone, Copy)]
+pub struct GicIts {
+    /// The ID of the ITS. This is used to refer to the ITS from other tables, such as the IORT
+    /// and SRAT.
+    pub id: u32,
+    pub address: u64,
+}
+
+/// A GICv2m MSI frame.
+#[derive(Debug, Clone, Copy)]
+pub struct GicMsiFrame {
+    pub id: u32,
+    pub address: u64,
+    /// The base and count of the SPIs this frame generates, if the firmware overrides the values
+    /// reported by the frame's `MSI_TYPER` register.
+    pub spis: Option<(u16, u16)>,
+}
+
+/// Information about a processor on a GIC platform, from its GICC entry in the MADT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GiccInfo {
+    /// The affinity fields of the processor's `MPIDR_EL1` register. This is how the processor is
+    /// identified to PSCI, and how it is targeted by GICv3+ SGIs.
+    pub mpidr: u64,
+    /// The version of the Arm parking protocol the processor implements, or `0` if it doesn't.
+    pub parking_protocol_version: u32,
+    /// The physical address of the processor's mailbox for the parking protocol.
+    pub parked_address: u64,
+    pub performance_interrupt_gsiv: u32,
+    pub performance_interrupt_trigger_mode: TriggerMode,
+    /// The physical address of the GICv2 CPU interface, or of the GICv3+ memory-mapped CPU
+    /// interface if it's implemented.
+    pub gic_registers_address: u64,
+    pub gic_virtual_registers_address: u64,
+    pub gic_hypervisor_registers_address: u64,
+    pub vgic_maintenance_interrupt: u32,
+    pub vgic_maintenance_interrupt_trigger_mode: TriggerMode,
+    /// The physical address of the processor's GICv3+ redistributor, if redistributors are not
+    /// described by [`Gic::redistributors`].
+    pub gicr_base_address: u64,
+    pub power_efficiency_class: u8,
+    /// The interrupt used by the Statistical Profiling Extension, or `0` if it is not supported.
+    pub spe_overflow_interrupt: u16,
+    /// Whether the processor can be brought online later, even though it is currently disabled.
+    pub online_capable: bool,
+}
+
+impl GiccInfo {
+    fn from_entry(entry: &GiccEntry) -> GiccInfo {
+        let flags = entry.flags;
+        let trigger_mode = |edge: bool| if edge { TriggerMode::Edge } else { TriggerMode::Level };
+
+        GiccInfo {
+            mpidr: entry.mpidr,
+            parking_protocol_version: entry.parking_protocol_version,
+            parked_address: entry.parked_address,
+            performance_interrupt_gsiv: entry.performance_interrupt_gsiv,
+            performance_interrupt_trigger_mode: trigger_mode(flags.get_bit(1)),
+            gic_registers_address: entry.gic_registers_address,
+            gic_virtual_registers_address: entry.gic_virtual_registers_address,
+            gic_hypervisor_registers_address: entry.gic_hypervisor_registers_address,
+            vgic_maintenance_interrupt: entry.vgic_maintenance_interrupt,
+            vgic_maintenance_interrupt_trigger_mode: trigger_mode(flags.get_bit(2)),
+            gicr_base_address: entry.gicr_base_address,
+            power_efficiency_class: entry.processor_power_efficiency_class,
+            spe_overflow_interrupt: entry.spe_overflow_interrupt,
+            online_capable: flags.get_bit(3),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Riscv<A: Allocator = Global> {
+    /// The properties shared by the IMSICs of every hart, if the harts have IMSICs. The address of
+    /// each hart's interrupt files is given by [`RintcInfo::imsic_base_address`].
+    pub imsic: Option<Imsic>,
+    pub aplics: Vec<Aplic, A>,
+    pub plics: Vec<Plic, A>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Imsic {
+    /// The number of interrupt identities supported by each hart's supervisor-level interrupt file.
+    pub num_ids: u16,
+    /// The number of interrupt identities supported by each guest interrupt file.
+    pub num_guest_ids: u16,
+    pub guest_index_bits: u8,
+    pub hart_index_bits: u8,
+    pub group_index_bits: u8,
+    pub group_index_shift: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aplic {
+    pub id: u8,
+    pub hardware_id: [u8; 8],
+    /// The number of interrupt delivery controls, or `0` if the APLIC forwards interrupts to the
+    /// harts' IMSICs as MSIs.
+    pub num_idcs: u16,
+    pub num_sources: u16,
+    /// The global system interrupt number where this APLIC's interrupt sources start.
+    pub global_system_interrupt_base: u32,
+    pub address: u64,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Plic {
+    pub id: u8,
+    pub hardware_id: [u8; 8],
+    pub num_irqs: u16,
+    pub max_priority: u16,
+    /// The global system interrupt number where this PLIC's interrupt sources start.
+    pub global_system_interrupt_base: u32,
+    pub address: u64,
+    pub size: u32,
+}
+
+/// Information about a hart on a RISC-V platform, from its RINTC entry in the MADT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RintcInfo {
+    pub hart_id: u64,
+    /// The ID of the APLIC or PLIC that this hart's external interrupts are routed from, if it
+    /// doesn't have an IMSIC.
+    pub external_interrupt_controller_id: u32,
+    /// The physical address of the hart's IMSIC interrupt files, or `0` if it doesn't have an
+    /// IMSIC.
+    pub imsic_base_address: u64,
+    pub imsic_size: u32,
+    /// Whether the hart can be brought online later, even though it is currently disabled.
+    pub online_capable: bool,
+}
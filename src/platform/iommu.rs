@@ -0,0 +1,205 @@
+use crate::{
+    AcpiError,
+    AcpiTables,
+    Handler,
+    sdt::{
+        Signature,
+        dmar::{
+            AtsrFlags,
+            DeviceScope,
+            DeviceScopePathEntry,
+            DeviceScopeType,
+            Dmar,
+            DmarEntry,
+            DmarFlags,
+            DrhdFlags,
+            SatcFlags,
+        },
+    },
+};
+use alloc::{
+    alloc::{Allocator, Global},
+    vec::Vec,
+};
+use log::warn;
+use pci_types::PciAddress;
+
+/// Information about the Intel VT-d DMA remapping hardware in the system, as described by the
+/// DMAR. Device scopes are resolved to the PCI addresses of the devices they describe.
+pub struct DmarInfo<A: Allocator = Global> {
+    /// The maximum DMA physical address width supported by the platform, in bits.
+    pub address_width: u8,
+    pub supports_interrupt_remapping: bool,
+    pub x2apic_opt_out: bool,
+    pub dma_control_opt_in: bool,
+    pub remapping_units: Vec<RemappingUnit<A>, A>,
+    pub reserved_memory_regions: Vec<ReservedMemoryRegion<A>, A>,
+    pub ats_root_ports: Vec<AtsRootPorts<A>, A>,
+    pub soc_translation_caches: Vec<SocTranslationCache<A>, A>,
+}
+
+/// A DMA remapping hardware unit.
+pub struct RemappingUnit<A: Allocator = Global> {
+    pub register_base_address: u64,
+    /// The size of the unit's register set, in bytes.
+    pub register_size: usize,
+    pub segment: u16,
+    /// If `true`, this unit is responsible for all devices in its segment that are not under
+    /// another unit, and `devices` only contains I/O APICs and HPETs.
+    pub include_pci_all: bool,
+    pub proximity_domain: Option<u32>,
+    pub devices: Vec<ScopedDevice, A>,
+}
+
+/// A region of memory that must be identity-mapped for DMA from the given devices.
+pub struct ReservedMemoryRegion<A: Allocator = Global> {
+    pub base_address: u64,
+    /// The last address of the region (inclusive).
+    pub limit_address: u64,
+    pub segment: u16,
+    pub devices: Vec<ScopedDevice, A>,
+}
+
+/// The PCIe root ports in a segment that support Address Translation Services.
+pub struct AtsRootPorts<A: Allocator = Global> {
+    pub segment: u16,
+    /// If `true`, all root ports in the segment support ATS, and `devices` is empty.
+    pub all_ports: bool,
+    pub devices: Vec<ScopedDevice, A>,
+}
+
+/// SoC-integrated devices that have an Address Translation Cache.
+pub struct SocTranslationCache<A: Allocator = Global> {
+    pub segment: u16,
+    pub atc_required: bool,
+    pub devices: Vec<ScopedDevice, A>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ScopedDevice {
+    pub typ: DeviceScopeType,
+    /// The PCI address of the device, found by following the device scope's path from its start
+    /// bus.
+    pub address: PciAddress,
+    /// For I/O APICs, the I/O APIC ID. For HPETs, the HPET number. For namespace devices, the
+    /// device number declared by an `Andd` entry.
+    pub enumeration_id: u8,
+}
+
+impl DmarInfo<Global> {
+    pub fn new<H>(tables: &AcpiTables<H>) -> Result<DmarInfo<Global>, AcpiError>
+    where
+        H: Handler,
+    {
+        Self::new_in(tables, Global)
+    }
+}
+
+impl<A: Allocator + Clone> DmarInfo<A> {
+    pub fn new_in<H>(tables: &AcpiTables<H>, allocator: A) -> Result<DmarInfo<A>, AcpiError>
+    where
+        H: Handler,
+    {
+        let Some(dmar) = tables.find_table::<Dmar>() else { Err(AcpiError::TableNotFound(Signature::DMAR))? };
+        let flags = dmar.get().flags;
+
+        let mut remapping_units = Vec::new_in(allocator.clone());
+        let mut reserved_memory_regions = Vec::new_in(allocator.clone());
+        let mut ats_root_ports = Vec::new_in(allocator.clone());
+        let mut soc_translation_caches = Vec::new_in(allocator.clone());
+
+        let resolve_scopes = |segment: u16, scopes: &mut dyn Iterator<Item = DeviceScope>| {
+            let mut devices = Vec::new_in(allocator.clone());
+            for scope in scopes {
+                let Some(address) = resolve_device_scope(&tables.handler, segment, &scope) else {
+                    warn!("Invalid device scope path in DMAR. Ignoring");
+                    continue;
+                };
+                devices.push(ScopedDevice {
+                    typ: scope.typ(),
+                    address,
+                    enumeration_id: scope.header.enumeration_id,
+                });
+            }
+            devices
+        };
+
+        for entry in dmar.get().entries() {
+            match entry {
+                DmarEntry::Drhd(drhd) => remapping_units.push(RemappingUnit {
+                    register_base_address: drhd.register_base_address,
+                    register_size: 0x1000 << (drhd.size & 0xf),
+                    segment: drhd.segment,
+                    include_pci_all: { drhd.flags }.contains(DrhdFlags::INCLUDE_PCI_ALL),
+                    proximity_domain: None,
+                    devices: resolve_scopes(drhd.segment, &mut drhd.device_scopes()),
+                }),
+                DmarEntry::Rmrr(rmrr) => reserved_memory_regions.push(ReservedMemoryRegion {
+                    base_address: rmrr.base_address,
+                    limit_address: rmrr.limit_address,
+                    segment: rmrr.segment,
+                    devices: resolve_scopes(rmrr.segment, &mut rmrr.device_scopes()),
+                }),
+                DmarEntry::Atsr(atsr) => ats_root_ports.push(AtsRootPorts {
+                    segment: atsr.segment,
+                    all_ports: { atsr.flags }.contains(AtsrFlags::ALL_PORTS),
+                    devices: resolve_scopes(atsr.segment, &mut atsr.device_scopes()),
+                }),
+                DmarEntry::Satc(satc) => soc_translation_caches.push(SocTranslationCache {
+                    segment: satc.segment,
+                    atc_required: { satc.flags }.contains(SatcFlags::ATC_REQUIRED),
+                    devices: resolve_scopes(satc.segment, &mut satc.device_scopes()),
+                }),
+                DmarEntry::Rhsa(_) | DmarEntry::Andd(_) => (),
+            }
+        }
+
+        /*
+         * RHSA entries can appear anywhere in the table, so match them up with their units once
+         * they've all been found.
+         */
+        for entry in dmar.get().entries() {
+            if let DmarEntry::Rhsa(rhsa) = entry
+                && let Some(unit) = remapping_units
+                    .iter_mut()
+                    .find(|unit| unit.register_base_address == rhsa.register_base_address)
+            {
+                unit.proximity_domain = Some(rhsa.proximity_domain);
+            }
+        }
+
+        Ok(DmarInfo {
+            address_width: dmar.get().host_address_width + 1,
+            supports_interrupt_remapping: flags.contains(DmarFlags::INTR_REMAP),
+            x2apic_opt_out: flags.contains(DmarFlags::X2APIC_OPT_OUT),
+            dma_control_opt_in: flags.contains(DmarFlags::DMA_CTRL_PLATFORM_OPT_IN),
+            remapping_units,
+            reserved_memory_regions,
+            ats_root_ports,
+            soc_translation_caches,
+        })
+    }
+}
+
+/// Find the PCI address of the device described by a device scope. Each element of the scope's
+/// path, other than the last, describes a PCI-PCI bridge - we read each bridge's secondary bus
+/// number to find the bus the next element is on. Returns `None` if the path is empty or contains
+/// an invalid device or function number.
+pub fn resolve_device_scope<H: Handler>(handler: &H, segment: u16, scope: &DeviceScope) -> Option<PciAddress> {
+    const SECONDARY_BUS_NUMBER_OFFSET: u16 = 0x19;
+
+    let mut bus = scope.header.start_bus_number;
+    let (last, bridges) = scope.path.split_last()?;
+    let address = |bus: u8, element: &DeviceScopePathEntry| {
+        if element.device > 31 || element.function > 7 {
+            return None;
+        }
+        Some(PciAddress::new(segment, bus, element.device, element.function))
+    };
+
+    for bridge in bridges {
+        bus = handler.read_pci_u8(address(bus, bridge)?, SECONDARY_BUS_NUMBER_OFFSET);
+    }
+
+    address(bus, last)
+}
@@ -1,6 +1,10 @@
+pub mod erst;
 pub mod interrupt;
+pub mod iommu;
 pub mod numa;
 pub mod pci;
+pub mod topology;
+pub mod wdat;
 
 pub use interrupt::InterruptModel;
 pub use pci::PciConfigRegions;
@@ -10,7 +14,7 @@ use crate::{
     AcpiTables,
     Handler,
     PowerProfile,
-    address::GenericAddress,
+    address::{AddressSpace, GenericAddress, MappedGas},
     registers::{FixedRegisters, Pm1ControlBit, Pm1Event},
     sdt::{
         Signature,
@@ -20,6 +24,7 @@ use crate::{
 };
 use alloc::{alloc::Global, sync::Arc, vec::Vec};
 use core::{alloc::Allocator, mem, ptr};
+use log::warn;
 
 /// `AcpiPlatform` is a higher-level view of the ACPI tables that makes it easier to perform common
 /// tasks with ACPI. It requires allocator support.
@@ -92,11 +97,53 @@ impl<H: Handler, A: Allocator + Clone> AcpiPlatform<H, A> {
         self.registers.pm1_event_registers.set_event_enabled(Pm1Event::PciEWake, false)?;
         self.registers.pm1_event_registers.set_event_enabled(Pm1Event::Wake, false)?;
 
-        // TODO: deal with GPEs
+        /*
+         * Disable all General-Purpose Events, and clear any that are pending. GPEs are enabled
+         * individually once their handlers have been discovered in the namespace.
+         */
+        if let Some(ref gpe0) = self.registers.gpe0 {
+            gpe0.disable_all();
+        }
+        if let Some(ref gpe1) = self.registers.gpe1 {
+            gpe1.disable_all();
+        }
 
         Ok(())
     }
 
+    /// Reset the system by writing the FADT's reset value to its reset register. Returns
+    /// [`AcpiError::ResetUnsupported`] if the platform does not support this, in which case the
+    /// host should fall back to another method (e.g. the keyboard controller on x86).
+    ///
+    /// The reset may not take effect immediately, so if this returns `Ok`, the host should wait for
+    /// a short time before falling back to another method.
+    pub fn reset(&self) -> Result<(), AcpiError> {
+        let Some(fadt) = self.tables.find_table::<Fadt>() else { Err(AcpiError::TableNotFound(Signature::FADT))? };
+        if !{ fadt.flags }.supports_system_reset_via_fadt() {
+            return Err(AcpiError::ResetUnsupported);
+        }
+
+        /*
+         * The reset register must be a single byte in system memory, system I/O, or the
+         * configuration space of a PCI device on bus `0`.
+         */
+        let reset_register = fadt.reset_register()?;
+        if reset_register.address == 0 {
+            return Err(AcpiError::ResetUnsupported);
+        }
+        if !matches!(
+            reset_register.address_space,
+            AddressSpace::SystemMemory | AddressSpace::SystemIo | AddressSpace::PciConfigSpace
+        ) {
+            warn!("FADT reset register is in unsupported address space {:?}", reset_register.address_space);
+            return Err(AcpiError::ResetUnsupported);
+        }
+
+        let reset_value = fadt.reset_value;
+        let reset_register = unsafe { MappedGas::map_gas(reset_register, &self.handler)? };
+        reset_register.write(reset_value as u64)
+    }
+
     pub fn read_mode(&self) -> Result<AcpiMode, AcpiError> {
         if self.registers.pm1_control_registers.read_bit(Pm1ControlBit::SciEnable)? {
             Ok(AcpiMode::Acpi)
@@ -206,6 +253,10 @@ pub enum ProcessorState {
 
     /// A Running processor is currently brought up and running code.
     Running,
+
+    /// A processor that is not currently active, but may be brought up through a firmware interface
+    /// (e.g. PSCI or the parking protocol on Arm, or SBI HSM on RISC-V), rather than with a SIPI.
+    Parked,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -214,7 +265,8 @@ pub struct Processor {
     /// object, in AML.
     pub processor_uid: u32,
     /// The ID of the local APIC of the processor. Will be less than `256` if the APIC is being used, but can be
-    /// greater than this if the X2APIC is being used.
+    /// greater than this if the X2APIC is being used. On GIC platforms, this is the processor's GIC CPU interface
+    /// number, and on RISC-V platforms it is `0` - use the hart ID in [`Processor::rintc`] instead.
     pub local_apic_id: u32,
 
     /// The state of this processor. Check that the processor is not `Disabled` before attempting to bring it up!
@@ -224,6 +276,11 @@ pub struct Processor {
     /// When the bootloader is entered, the BSP is the only processor running code. To run code on
     /// more than one processor, you need to "bring up" the APs.
     pub is_ap: bool,
+
+    /// On GIC platforms, information about the processor from its GICC entry in the MADT.
+    pub gicc: Option<interrupt::GiccInfo>,
+    /// On RISC-V platforms, information about the hart from its RINTC entry in the MADT.
+    pub rintc: Option<interrupt::RintcInfo>,
 }
 
 #[derive(Debug, Clone)]
@@ -249,12 +306,89 @@ pub struct PmTimer {
 }
 
 impl PmTimer {
+    /// The frequency that the PM timer counts at, in Hz.
+    pub const FREQUENCY: u64 = 3_579_545;
+
     pub fn new(fadt: &Fadt) -> Result<Option<PmTimer>, AcpiError> {
         match fadt.pm_timer_block()? {
             Some(base) => Ok(Some(PmTimer { base, supports_32bit: { fadt.flags }.pm_timer_is_32_bit() })),
             None => Ok(None),
         }
     }
+
+    /// Map the timer's counter register, so that it can be read.
+    ///
+    /// ### Safety
+    /// `base` must be the address of the platform's PM timer counter register, as reported by the
+    /// FADT, and so be valid to read.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> Result<MappedPmTimer<H>, AcpiError> {
+        let counter = unsafe { MappedGas::map_gas(self.base, handler)? };
+        Ok(MappedPmTimer { timer: self.clone(), counter })
+    }
+
+    /// The mask of the bits of the counter that are implemented.
+    pub fn counter_mask(&self) -> u32 {
+        if self.supports_32bit { u32::MAX } else { 0x00ff_ffff }
+    }
+
+    /// Calculate the number of ticks between two reads of the counter. This is correct as long as
+    /// the counter has wrapped at most once between the reads - this takes ~4.7 seconds for a
+    /// 24-bit timer, and ~20 minutes for a 32-bit timer.
+    pub fn ticks_between(&self, start: u32, end: u32) -> u32 {
+        end.wrapping_sub(start) & self.counter_mask()
+    }
+
+    pub fn ticks_to_nanos(ticks: u64) -> u64 {
+        (ticks as u128 * 1_000_000_000 / Self::FREQUENCY as u128) as u64
+    }
+
+    /// Convert a number of nanoseconds to the number of ticks that will take at least that long.
+    pub fn nanos_to_ticks(nanos: u64) -> u64 {
+        (nanos as u128 * Self::FREQUENCY as u128).div_ceil(1_000_000_000) as u64
+    }
+}
+
+/// A [`PmTimer`] with its counter register mapped, so that it can be used as a time source.
+pub struct MappedPmTimer<H: Handler> {
+    pub timer: PmTimer,
+    counter: MappedGas<H>,
+}
+
+impl<H> MappedPmTimer<H>
+where
+    H: Handler,
+{
+    /// Read the current value of the counter.
+    pub fn read_ticks(&self) -> Result<u32, AcpiError> {
+        Ok(self.counter.read()? as u32 & self.timer.counter_mask())
+    }
+
+    /// Get the number of ticks that have elapsed since `start` was read from the counter. See
+    /// [`PmTimer::ticks_between`] for the limits on how long this can measure.
+    pub fn ticks_since(&self, start: u32) -> Result<u32, AcpiError> {
+        Ok(self.timer.ticks_between(start, self.read_ticks()?))
+    }
+
+    pub fn nanos_since(&self, start: u32) -> Result<u64, AcpiError> {
+        Ok(PmTimer::ticks_to_nanos(self.ticks_since(start)? as u64))
+    }
+
+    /// Busy-wait for at least the given number of nanoseconds. This can wait for longer than the
+    /// counter takes to wrap, as the counter is polled continuously.
+    pub fn delay_nanos(&self, nanos: u64) -> Result<(), AcpiError> {
+        let target = PmTimer::nanos_to_ticks(nanos);
+        let mut elapsed = 0u64;
+        let mut last = self.read_ticks()?;
+
+        while elapsed < target {
+            let now = self.read_ticks()?;
+            elapsed += self.timer.ticks_between(last, now) as u64;
+            last = now;
+            core::hint::spin_loop();
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -262,3 +396,39 @@ pub enum AcpiMode {
     Legacy,
     Acpi,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::AddressSpace;
+
+    fn pm_timer(supports_32bit: bool) -> PmTimer {
+        let base = GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width: 32,
+            bit_offset: 0,
+            access_size: 0,
+            address: 0x408,
+        };
+        PmTimer { base, supports_32bit }
+    }
+
+    #[test]
+    fn test_pm_timer_wraparound() {
+        let timer = pm_timer(false);
+        assert_eq!(timer.ticks_between(100, 250), 150);
+        assert_eq!(timer.ticks_between(0x00ff_fff0, 0x10), 0x20);
+
+        let timer = pm_timer(true);
+        assert_eq!(timer.ticks_between(0xffff_fff0, 0x10), 0x20);
+        assert_eq!(timer.ticks_between(0x00ff_fff0, 0x0100_0010), 0x20);
+    }
+
+    #[test]
+    fn test_pm_timer_conversions() {
+        assert_eq!(PmTimer::ticks_to_nanos(PmTimer::FREQUENCY), 1_000_000_000);
+        assert_eq!(PmTimer::ticks_to_nanos(3580), 1_000_127);
+        assert_eq!(PmTimer::nanos_to_ticks(1_000_000), 3580);
+        assert_eq!(PmTimer::nanos_to_ticks(1_000_000_000), PmTimer::FREQUENCY);
+    }
+}
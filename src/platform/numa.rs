@@ -2,6 +2,7 @@ use crate::{
     AcpiTables,
     Handler,
     sdt::{
+        hmat::{CacheAssociativity, CacheWritePolicy, Hmat, HmatDataType, HmatEntry, MemoryHierarchy},
         slit::{DistanceMatrix, Slit},
         srat::{LocalApicAffinityFlags, MemoryAffinityFlags, Srat, SratEntry},
     },
@@ -16,6 +17,13 @@ pub struct NumaInfo<A: Allocator = Global> {
     pub memory_affinity: Vec<MemoryAffinity, A>,
     pub num_proximity_domains: usize,
     pub distance_matrix: Vec<u8, A>,
+    /// The initiator proximity domain that the memory of each proximity domain is attached to,
+    /// from the HMAT.
+    pub memory_domain_attributes: Vec<MemoryDomainAttributes, A>,
+    /// The latency and bandwidth of the memory of each target proximity domain, when accessed from
+    /// each initiator proximity domain, from the HMAT.
+    pub latency_bandwidth: Vec<LatencyBandwidth, A>,
+    pub memory_side_caches: Vec<MemorySideCache, A>,
 }
 
 impl NumaInfo<Global> {
@@ -61,12 +69,79 @@ impl<A: Allocator + Clone> NumaInfo<A> {
             (0, Vec::new_in(allocator.clone()))
         };
 
-        NumaInfo { processor_affinity, memory_affinity, num_proximity_domains, distance_matrix }
+        let mut memory_domain_attributes = Vec::new_in(allocator.clone());
+        let mut latency_bandwidth = Vec::new_in(allocator.clone());
+        let mut memory_side_caches = Vec::new_in(allocator.clone());
+        if let Some(hmat) = tables.find_table::<Hmat>() {
+            let revision = hmat.get().header.revision;
+            for entry in hmat.get().entries() {
+                match entry {
+                    HmatEntry::MemoryProximityDomainAttributes(entry) => {
+                        if let Some(memory_proximity_domain) = entry.memory_proximity_domain(revision) {
+                            memory_domain_attributes.push(MemoryDomainAttributes {
+                                memory_proximity_domain,
+                                initiator_proximity_domain: entry.initiator_proximity_domain(),
+                            })
+                        }
+                    }
+                    HmatEntry::LatencyBandwidthInfo(entry) => {
+                        for (i, initiator_proximity_domain) in entry.initiator_proximity_domains().enumerate() {
+                            for (t, target_proximity_domain) in entry.target_proximity_domains().enumerate() {
+                                if let Some(value) = entry.value(revision, i, t) {
+                                    latency_bandwidth.push(LatencyBandwidth {
+                                        initiator_proximity_domain,
+                                        target_proximity_domain,
+                                        memory_hierarchy: entry.memory_hierarchy(),
+                                        data_type: entry.data_type(),
+                                        value,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                    HmatEntry::MemorySideCacheInfo(entry) => memory_side_caches.push(MemorySideCache {
+                        memory_proximity_domain: entry.memory_proximity_domain,
+                        size: entry.memory_side_cache_size,
+                        level: entry.cache_level(),
+                        total_levels: entry.total_cache_levels(),
+                        associativity: entry.associativity(),
+                        write_policy: entry.write_policy(),
+                        line_size: entry.line_size(),
+                    }),
+                }
+            }
+        }
+
+        NumaInfo {
+            processor_affinity,
+            memory_affinity,
+            num_proximity_domains,
+            distance_matrix,
+            memory_domain_attributes,
+            latency_bandwidth,
+            memory_side_caches,
+        }
     }
 
     pub fn distance_matrix(&self) -> DistanceMatrix<'_> {
         DistanceMatrix { num_proximity_domains: self.num_proximity_domains as u64, matrix: &self.distance_matrix }
     }
+
+    /// Get an attribute of the memory of the `target` proximity domain, when accessed from the
+    /// `initiator` proximity domain. Latencies are in picoseconds, and bandwidths are in MB/s.
+    pub fn memory_attribute(&self, initiator: u32, target: u32, data_type: HmatDataType) -> Option<u64> {
+        self.latency_bandwidth.iter().find_map(|entry| {
+            if entry.initiator_proximity_domain == initiator
+                && entry.target_proximity_domain == target
+                && entry.memory_hierarchy == MemoryHierarchy::Memory
+                && entry.data_type == data_type
+            {
+                Some(entry.value)
+            } else {
+                None
+            }
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -85,3 +160,35 @@ pub struct MemoryAffinity {
     pub is_hot_pluggable: bool,
     pub is_non_volatile: bool,
 }
+
+#[derive(Clone, Debug)]
+pub struct MemoryDomainAttributes {
+    pub memory_proximity_domain: u32,
+    /// The initiator proximity domain the memory is attached to, if the firmware describes one.
+    pub initiator_proximity_domain: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LatencyBandwidth {
+    pub initiator_proximity_domain: u32,
+    pub target_proximity_domain: u32,
+    /// Whether this describes the memory itself, or a memory-side cache in front of it.
+    pub memory_hierarchy: MemoryHierarchy,
+    pub data_type: HmatDataType,
+    /// The latency, in picoseconds, or the bandwidth, in MB/s, depending on `data_type`.
+    pub value: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct MemorySideCache {
+    pub memory_proximity_domain: u32,
+    /// The size of the cache, in bytes.
+    pub size: u64,
+    /// The level of the cache, where the cache closest to the memory is level `1`.
+    pub level: u8,
+    pub total_levels: u8,
+    pub associativity: CacheAssociativity,
+    pub write_policy: CacheWritePolicy,
+    /// The size of a cache line, in bytes.
+    pub line_size: u16,
+}
@@ -0,0 +1,292 @@
+use crate::{
+    AcpiError,
+    AcpiTables,
+    Handler,
+    sdt::{
+        Signature,
+        pptt::{CacheAllocationType, CacheType, CacheWritePolicy, Pptt, PpttEntry, ProcessorHierarchyFlags},
+    },
+};
+use alloc::{alloc::Global, vec::Vec};
+use core::alloc::Allocator;
+use log::warn;
+
+/// The topology of the processors in the system, as described by the PPTT. This is a tree of
+/// [`TopologyNode`]s, with a leaf node for each processor, which can be found from the processor's
+/// ACPI processor UID in the MADT (see [`super::Processor::processor_uid`]). Nodes and
+/// caches refer to each other by their index into [`ProcessorTopology::nodes`] and
+/// [`ProcessorTopology::caches`].
+#[derive(Clone, Debug)]
+pub struct ProcessorTopology<A: Allocator = Global> {
+    pub nodes: Vec<TopologyNode<A>, A>,
+    pub caches: Vec<CacheInfo, A>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TopologyLevel {
+    Package,
+    /// A group of cores within a package, such as those sharing a cache.
+    Cluster,
+    Core,
+    /// A hardware thread of a core.
+    Thread,
+}
+
+#[derive(Clone, Debug)]
+pub struct TopologyNode<A: Allocator = Global> {
+    pub level: TopologyLevel,
+    /// For processors, the ACPI processor UID of the processor in the MADT. For other nodes, the
+    /// `_UID` of the matching processor container in the namespace.
+    pub acpi_processor_id: Option<u32>,
+    pub parent: Option<usize>,
+    /// The caches private to this node.
+    pub caches: Vec<usize, A>,
+    pub is_leaf: bool,
+    /// Whether all of the node's children are implemented identically.
+    pub identical_implementation: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheInfo {
+    /// The level of the cache, where the caches closest to a processor are level `1`.
+    pub level: u8,
+    pub cache_type: Option<CacheType>,
+    /// The size of the cache, in bytes.
+    pub size: Option<u32>,
+    pub number_of_sets: Option<u32>,
+    /// The number of ways of the cache.
+    pub associativity: Option<u8>,
+    /// The size of a cache line, in bytes.
+    pub line_size: Option<u16>,
+    pub allocation_type: Option<CacheAllocationType>,
+    pub write_policy: Option<CacheWritePolicy>,
+    pub cache_id: Option<u32>,
+    /// The next level of cache, further from the processor.
+    pub next_level: Option<usize>,
+}
+
+impl ProcessorTopology<Global> {
+    pub fn new(tables: &AcpiTables<impl Handler>) -> Result<ProcessorTopology<Global>, AcpiError> {
+        Self::new_in(tables, Global)
+    }
+}
+
+impl<A: Allocator + Clone> ProcessorTopology<A> {
+    pub fn new_in(tables: &AcpiTables<impl Handler>, allocator: A) -> Result<ProcessorTopology<A>, AcpiError> {
+        let Some(pptt) = tables.find_table::<Pptt>() else { Err(AcpiError::TableNotFound(Signature::PPTT))? };
+        let pptt = pptt.get();
+
+        /*
+         * Entries refer to each other by offset, so first collect the offsets of the nodes and
+         * caches, so that the references can be converted into indices.
+         */
+        let mut node_offsets = Vec::new_in(allocator.clone());
+        let mut cache_offsets = Vec::new_in(allocator.clone());
+        for (offset, entry) in pptt.entries() {
+            match entry {
+                PpttEntry::ProcessorHierarchy(_) => node_offsets.push(offset),
+                PpttEntry::Cache(_) => cache_offsets.push(offset),
+                PpttEntry::Id(_) => (),
+            }
+        }
+        let node_index = |offset: u32| node_offsets.iter().position(|&o| o == offset);
+        let cache_index = |offset: u32| cache_offsets.iter().position(|&o| o == offset);
+
+        let mut nodes = Vec::with_capacity_in(node_offsets.len(), allocator.clone());
+        let mut caches = Vec::with_capacity_in(cache_offsets.len(), allocator.clone());
+        for (_, entry) in pptt.entries() {
+            match entry {
+                PpttEntry::ProcessorHierarchy(node) => {
+                    let flags = node.flags();
+                    let parent = node.parent().and_then(|parent| {
+                        let index = node_index(parent);
+                        if index.is_none() {
+                            warn!("PPTT processor hierarchy node has invalid parent. Ignoring");
+                        }
+                        index
+                    });
+
+                    let mut node_caches = Vec::new_in(allocator.clone());
+                    for resource in node.private_resources() {
+                        match cache_index(resource) {
+                            Some(index) => node_caches.push(index),
+                            None => {
+                                warn!("Unsupported private resource of PPTT processor hierarchy node. Ignoring")
+                            }
+                        }
+                    }
+
+                    nodes.push(TopologyNode {
+                        /*
+                         * The level is fixed up once the shape of the tree is known.
+                         */
+                        level: if flags.contains(ProcessorHierarchyFlags::PHYSICAL_PACKAGE) {
+                            TopologyLevel::Package
+                        } else if flags.contains(ProcessorHierarchyFlags::PROCESSOR_IS_A_THREAD) {
+                            TopologyLevel::Thread
+                        } else {
+                            TopologyLevel::Core
+                        },
+                        acpi_processor_id: if flags.contains(ProcessorHierarchyFlags::ACPI_PROCESSOR_ID_VALID) {
+                            Some(node.acpi_processor_id)
+                        } else {
+                            None
+                        },
+                        parent,
+                        caches: node_caches,
+                        is_leaf: true,
+                        identical_implementation: flags
+                            .contains(ProcessorHierarchyFlags::IDENTICAL_IMPLEMENTATION),
+                    });
+                }
+                PpttEntry::Cache(cache) => caches.push(CacheInfo {
+                    level: 0,
+                    cache_type: cache.cache_type(),
+                    size: cache.size(),
+                    number_of_sets: cache.number_of_sets(),
+                    associativity: cache.associativity(),
+                    line_size: cache.line_size(),
+                    allocation_type: cache.allocation_type(),
+                    write_policy: cache.write_policy(),
+                    cache_id: cache.cache_id(),
+                    next_level: cache.next_level_of_cache().and_then(cache_index),
+                }),
+                PpttEntry::Id(_) => (),
+            }
+        }
+
+        /*
+         * Older tables do not mark leaf nodes, so find them from the shape of the tree. A node that
+         * is not a package is a core if its children are threads, and a cluster otherwise.
+         */
+        for i in 0..nodes.len() {
+            if let Some(parent) = nodes[i].parent {
+                nodes[parent].is_leaf = false;
+            }
+        }
+        for i in 0..nodes.len() {
+            if nodes[i].is_leaf || nodes[i].level == TopologyLevel::Package {
+                continue;
+            }
+            let has_threads = nodes
+                .iter()
+                .any(|node| node.parent == Some(i) && node.is_leaf && node.level == TopologyLevel::Thread);
+            nodes[i].level = if has_threads { TopologyLevel::Core } else { TopologyLevel::Cluster };
+        }
+
+        let mut topology = ProcessorTopology { nodes, caches };
+        topology.assign_cache_levels();
+        Ok(topology)
+    }
+
+    /// The index of the leaf node of the processor with the given ACPI processor UID.
+    pub fn processor(&self, acpi_processor_uid: u32) -> Option<usize> {
+        self.nodes.iter().position(|node| node.is_leaf && node.acpi_processor_id == Some(acpi_processor_uid))
+    }
+
+    /// Iterate over the ancestors of the given node, from its parent up to the root of the tree.
+    pub fn ancestors(&self, node: usize) -> impl Iterator<Item = &TopologyNode<A>> {
+        let mut next = self.nodes.get(node).and_then(|node| node.parent);
+        /*
+         * Guard against loops in the tree - no node can have more ancestors than there are nodes.
+         */
+        let mut remaining = self.nodes.len();
+        core::iter::from_fn(move || {
+            remaining = remaining.checked_sub(1)?;
+            let node = &self.nodes[next?];
+            next = node.parent;
+            Some(node)
+        })
+    }
+
+    /// Find the ancestor of the given node at the given level, e.g. the package that contains a
+    /// processor.
+    pub fn ancestor_at(&self, node: usize, level: TopologyLevel) -> Option<&TopologyNode<A>> {
+        self.ancestors(node).find(|ancestor| ancestor.level == level)
+    }
+
+    /// Iterate over the caches used by the processor with the given ACPI processor UID - those
+    /// private to the processor and its ancestors, and the further levels of cache they lead to.
+    /// Each cache is only produced once, even if it can be reached in several ways.
+    pub fn processor_caches(&self, acpi_processor_uid: u32) -> impl Iterator<Item = &CacheInfo> {
+        let mut caches = Vec::new_in(self.caches.allocator().clone());
+        if let Some(node) = self.processor(acpi_processor_uid) {
+            for node in core::iter::once(&self.nodes[node]).chain(self.ancestors(node)) {
+                for &first in &node.caches {
+                    for cache in self.cache_chain(first) {
+                        if !caches.contains(&cache) {
+                            caches.push(cache);
+                        }
+                    }
+                }
+            }
+        }
+        caches.into_iter().map(|cache| &self.caches[cache])
+    }
+
+    /// Iterate over the indices of the given cache and the further levels of cache it leads to.
+    fn cache_chain(&self, cache: usize) -> impl Iterator<Item = usize> {
+        let mut next = Some(cache);
+        /*
+         * Guard against loops in the cache chain.
+         */
+        let mut remaining = self.caches.len();
+        core::iter::from_fn(move || {
+            remaining = remaining.checked_sub(1)?;
+            let cache = next?;
+            next = self.caches[cache].next_level;
+            Some(cache)
+        })
+    }
+
+    /// Work out the level of each cache by walking from each processor up the tree. The caches
+    /// private to a node, and the caches they lead to, are at levels above those of the node's
+    /// descendants.
+    fn assign_cache_levels(&mut self) {
+        for leaf in 0..self.nodes.len() {
+            if !self.nodes[leaf].is_leaf {
+                continue;
+            }
+
+            let mut starting_level: u8 = 0;
+            let mut next = Some(leaf);
+            let mut walked_nodes = 0;
+            while let Some(node) = next {
+                /*
+                 * Guard against loops in the processor hierarchy.
+                 */
+                walked_nodes += 1;
+                if walked_nodes > self.nodes.len() {
+                    warn!("Loop in PPTT processor hierarchy. Ignoring");
+                    break;
+                }
+
+                let mut max_level = starting_level;
+                for i in 0..self.nodes[node].caches.len() {
+                    let mut level = starting_level;
+                    let mut cache = Some(self.nodes[node].caches[i]);
+                    let mut walked = 0;
+                    while let Some(index) = cache {
+                        /*
+                         * Guard against loops in the cache chain.
+                         */
+                        walked += 1;
+                        if walked > self.caches.len() {
+                            warn!("Loop in PPTT cache hierarchy. Ignoring");
+                            break;
+                        }
+
+                        if self.caches[index].level == 0 {
+                            self.caches[index].level = level.saturating_add(1);
+                        }
+                        level = self.caches[index].level;
+                        max_level = max_level.max(level);
+                        cache = self.caches[index].next_level;
+                    }
+                }
+                starting_level = max_level;
+                next = self.nodes[node].parent;
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    AcpiError,
+    AcpiTables,
+    Handler,
+    address::{GasMapper, MappedGas},
+    sdt::{
+        Signature,
+        wdat::{WatchdogAction, WatchdogInstruction, WatchdogInstructionEntry, Wdat},
+    },
+};
+use alloc::{
+    alloc::{Allocator, Global},
+    vec::Vec,
+};
+use log::warn;
+use pci_types::PciAddress;
+
+/// The platform's hardware watchdog, as described by the WDAT. The watchdog is controlled by
+/// interpreting the instructions provided by the firmware for each [`WatchdogAction`].
+pub struct Watchdog<H: Handler, A: Allocator = Global> {
+    instructions: Vec<(WatchdogInstructionEntry, MappedGas<H>), A>,
+    /// Whether the watchdog is enabled. If it is not, it should not be used.
+    pub enabled: bool,
+    /// The length of one count of the watchdog's countdown, in milliseconds.
+    pub timer_period: u32,
+    pub min_count: u32,
+    pub max_count: u32,
+    pub pci_address: Option<PciAddress>,
+    /// Whether the watchdog is stopped while the system is in a sleeping state.
+    pub stopped_in_sleep_state: bool,
+}
+
+impl<H> Watchdog<H, Global>
+where
+    H: Handler,
+{
+    pub fn new(tables: &AcpiTables<H>) -> Result<Watchdog<H, Global>, AcpiError> {
+        Self::new_in(tables, Global)
+    }
+}
+
+impl<H, A> Watchdog<H, A>
+where
+    H: Handler,
+    A: Allocator,
+{
+    pub fn new_in(tables: &AcpiTables<H>, allocator: A) -> Result<Watchdog<H, A>, AcpiError> {
+        let Some(wdat) = tables.find_table::<Wdat>() else { Err(AcpiError::TableNotFound(Signature::WDAT))? };
+        let wdat = wdat.get();
+
+        let mut mapper = GasMapper::new(tables.handler.clone());
+        let mut instructions = Vec::new_in(allocator);
+        for entry in wdat.entries() {
+            let register = unsafe { mapper.map_gas(entry.register()?)? };
+            instructions.push((*entry, register));
+        }
+
+        Ok(Watchdog {
+            instructions,
+            enabled: wdat.enabled(),
+            timer_period: wdat.timer_period,
+            min_count: wdat.min_count,
+            max_count: wdat.max_count,
+            pci_address: wdat.pci_address(),
+            stopped_in_sleep_state: wdat.stopped_in_sleep_state(),
+        })
+    }
+
+    /// Reset the watchdog's countdown, to stop it from expiring.
+    pub fn ping(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::Reset, 0).map(|_| ())
+    }
+
+    pub fn start(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::SetRunningState, 0).map(|_| ())
+    }
+
+    pub fn stop(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::SetStoppedState, 0).map(|_| ())
+    }
+
+    pub fn is_running(&self) -> Result<bool, AcpiError> {
+        Ok(self.execute(WatchdogAction::QueryRunningState, 0)? != 0)
+    }
+
+    /// The period after which the watchdog expires if it is not pinged, in milliseconds.
+    pub fn timeout(&self) -> Result<u64, AcpiError> {
+        Ok(self.execute(WatchdogAction::QueryCountdownPeriod, 0)? * self.timer_period as u64)
+    }
+
+    /// Set the period after which the watchdog expires if it is not pinged, in milliseconds. The
+    /// period is rounded down to a whole number of counts, and clamped to the range supported by
+    /// the watchdog. Returns the period that was actually set.
+    pub fn set_timeout(&self, timeout_ms: u64) -> Result<u64, AcpiError> {
+        let count =
+            (timeout_ms / self.timer_period.max(1) as u64).max(self.min_count as u64).min(self.max_count as u64);
+        self.execute(WatchdogAction::SetCountdownPeriod, count)?;
+        Ok(count * self.timer_period as u64)
+    }
+
+    /// The time remaining before the watchdog expires, in milliseconds.
+    pub fn time_left(&self) -> Result<u64, AcpiError> {
+        Ok(self.execute(WatchdogAction::QueryCurrentCountdownPeriod, 0)? * self.timer_period as u64)
+    }
+
+    /// Configure the watchdog to reboot the system when it expires.
+    pub fn set_reboot(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::SetReboot, 0).map(|_| ())
+    }
+
+    /// Configure the watchdog to shut down the system when it expires.
+    pub fn set_shutdown(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::SetShutdown, 0).map(|_| ())
+    }
+
+    /// Whether the current boot was caused by the watchdog expiring.
+    pub fn caused_last_boot(&self) -> Result<bool, AcpiError> {
+        Ok(self.execute(WatchdogAction::QueryWatchdogStatus, 0)? != 0)
+    }
+
+    /// Reset the watchdog's boot status, reported by [`Watchdog::caused_last_boot`].
+    pub fn clear_boot_status(&self) -> Result<(), AcpiError> {
+        self.execute(WatchdogAction::SetWatchdogStatus, 0).map(|_| ())
+    }
+
+    /// Execute the instructions of the given action. `parameter` is written by `WRITE_COUNTDOWN`
+    /// instructions, and the result of the last `READ_VALUE` or `READ_COUNTDOWN` instruction is
+    /// returned.
+    pub fn execute(&self, action: WatchdogAction, parameter: u64) -> Result<u64, AcpiError> {
+        let mut instructions =
+            self.instructions.iter().filter(|(entry, _)| WatchdogAction::from(entry.action) == action).peekable();
+        if instructions.peek().is_none() {
+            return Err(AcpiError::WatchdogActionUnsupported(action));
+        }
+
+        let mut result = 0;
+        for (entry, register) in instructions {
+            match entry.instruction() {
+                WatchdogInstruction::ReadValue => {
+                    result = (entry.read_register(register)? == entry.value as u64) as u64;
+                }
+                WatchdogInstruction::ReadCountdown => result = entry.read_register(register)?,
+                WatchdogInstruction::WriteValue => entry.write_register(register, entry.value as u64)?,
+                WatchdogInstruction::WriteCountdown => entry.write_register(register, parameter)?,
+                WatchdogInstruction::Reserved(other) => {
+                    warn!("Unrecognised instruction in WDAT of type {}", other);
+                    return Err(AcpiError::LibUnimplemented);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
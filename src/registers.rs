@@ -1,9 +1,28 @@
-use crate::{AcpiError, Handler, address::MappedGas, sdt::fadt::Fadt};
+use crate::{
+    AcpiError,
+    Handler,
+    PhysicalMapping,
+    address::{AddressSpace, GenericAddress, MappedGas},
+    sdt::fadt::Fadt,
+};
 use bit_field::BitField;
+use log::warn;
 
 pub struct FixedRegisters<H: Handler> {
     pub pm1_event_registers: Pm1EventRegisterBlock<H>,
     pub pm1_control_registers: Pm1ControlRegisterBlock<H>,
+    /// The `GPE0` register block described by the FADT, if present.
+    pub gpe0: Option<GpeRegisterBlock<H>>,
+    /// The `GPE1` register block described by the FADT, if present. The events of this block are
+    /// numbered from `gpe1_base`.
+    pub gpe1: Option<GpeRegisterBlock<H>>,
+    pub gpe1_base: u8,
+    /// The sleep control register, used instead of the PM1 control registers to enter sleep states
+    /// on hardware-reduced platforms.
+    pub sleep_control: Option<MappedGas<H>>,
+    /// The sleep status register, used instead of the PM1 event registers to detect wake on
+    /// hardware-reduced platforms.
+    pub sleep_status: Option<MappedGas<H>>,
 }
 
 impl<H> FixedRegisters<H>
@@ -27,11 +46,83 @@ where
             };
             Pm1ControlRegisterBlock { pm1a, pm1b }
         };
+        let gpe0 = match fadt.gpe0_block()? {
+            Some(gas) => {
+                Some(unsafe { GpeRegisterBlock::new(gas, fadt.gpe0_block_length as usize, handler.clone())? })
+            }
+            None => None,
+        };
+        let gpe1 = match fadt.gpe1_block()? {
+            Some(gas) => {
+                Some(unsafe { GpeRegisterBlock::new(gas, fadt.gpe1_block_length as usize, handler.clone())? })
+            }
+            None => None,
+        };
 
-        Ok(FixedRegisters { pm1_event_registers, pm1_control_registers })
+        let sleep_control = match fadt.sleep_control_register()? {
+            Some(gas) if gas.address != 0 => Some(unsafe { MappedGas::map_gas(gas, &handler)? }),
+            _ => None,
+        };
+        let sleep_status = match fadt.sleep_status_register()? {
+            Some(gas) if gas.address != 0 => Some(unsafe { MappedGas::map_gas(gas, &handler)? }),
+            _ => None,
+        };
+
+        Ok(FixedRegisters {
+            pm1_event_registers,
+            pm1_control_registers,
+            gpe0,
+            gpe1,
+            gpe1_base: fadt.gpe1_base,
+            sleep_control,
+            sleep_status,
+        })
+    }
+
+    /// Put the platform into the sleep state described by the given `SLP_TYP` values, which are
+    /// found in the `\_Sx` object for the state. On hardware-reduced platforms, this uses the
+    /// sleep control register (and only `slp_typa`). Otherwise, it uses the PM1 control registers.
+    ///
+    /// This first clears the wake status, so [`FixedRegisters::is_waking`] can be used to detect
+    /// when the platform has woken. OSPM must have flushed the processor caches before calling
+    /// this.
+    pub fn enter_sleep(&self, slp_typa: u8, slp_typb: u8) -> Result<(), AcpiError> {
+        if let Some(ref sleep_control) = self.sleep_control {
+            if let Some(ref sleep_status) = self.sleep_status {
+                sleep_status.write(1 << SLEEP_STATUS_WAK_STS)?;
+            }
+
+            let mut value = 0;
+            value.set_bits(SLEEP_CONTROL_SLP_TYP, slp_typa as u64);
+            value.set_bit(SLEEP_CONTROL_SLP_EN, true);
+            sleep_control.write(value)
+        } else {
+            self.pm1_event_registers.clear_status(Pm1Event::Wake)?;
+            self.pm1_control_registers.enter_sleep(slp_typa, slp_typb)
+        }
+    }
+
+    /// Returns `true` if the platform has woken from a sleep state entered with
+    /// [`FixedRegisters::enter_sleep`].
+    pub fn is_waking(&self) -> Result<bool, AcpiError> {
+        if self.sleep_control.is_some() {
+            match self.sleep_status {
+                Some(ref sleep_status) => Ok(sleep_status.read()?.get_bit(SLEEP_STATUS_WAK_STS)),
+                None => Ok(false),
+            }
+        } else {
+            self.pm1_event_registers.is_event_set(Pm1Event::Wake)
+        }
     }
 }
 
+/// The bits of the hardware-reduced sleep control register that hold `SLP_TYP`.
+const SLEEP_CONTROL_SLP_TYP: core::ops::Range<usize> = 2..5;
+/// The bit of the hardware-reduced sleep control register that triggers entry to the sleep state.
+const SLEEP_CONTROL_SLP_EN: usize = 5;
+/// The bit of the hardware-reduced sleep status register that is set when the platform wakes.
+const SLEEP_STATUS_WAK_STS: usize = 7;
+
 /// The PM1 register grouping contains two register blocks that control fixed events. It is split
 /// into two to allow its functionality to be split between two hardware components. `PM1a` and
 /// `PM1b` are effectively mirrors of each other - reads are made from both of them and logically
@@ -63,24 +154,53 @@ where
     H: Handler,
 {
     pub fn set_event_enabled(&self, event: Pm1Event, enabled: bool) -> Result<(), AcpiError> {
-        let enable_offset = self.pm1_event_length * 8 / 2;
-        let event_bit = match event {
-            Pm1Event::Timer => 0,
-            Pm1Event::GlobalLock => 5,
-            Pm1Event::PowerButton => 8,
-            Pm1Event::SleepButton => 9,
-            Pm1Event::Rtc => 10,
-            Pm1Event::PciEWake => 14,
-            Pm1Event::Wake => 15,
-        };
+        let enable_bit = self.status_length() + event as usize;
 
+        /*
+         * The status bits share a register with the enable bits, and are cleared by writing a `1`
+         * to them. Make sure we write back zeros to them so pending events aren't lost.
+         */
         let mut pm1a = self.pm1a.read()?;
-        pm1a.set_bit(enable_offset + event_bit, enabled);
+        pm1a.set_bits(0..self.status_length(), 0);
+        pm1a.set_bit(enable_bit, enabled);
         self.pm1a.write(pm1a)?;
 
         if let Some(pm1b) = &self.pm1b {
             let mut value = pm1b.read()?;
-            value.set_bit(enable_offset + event_bit, enabled);
+            value.set_bits(0..self.status_length(), 0);
+            value.set_bit(enable_bit, enabled);
+            pm1b.write(value)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_event_enabled(&self, event: Pm1Event) -> Result<bool, AcpiError> {
+        Ok(self.read()?.get_bit(self.status_length() + event as usize))
+    }
+
+    /// Read the `PM1_STS` status register. Each bit is set if the corresponding [`Pm1Event`] has
+    /// fired, regardless of whether it is enabled.
+    pub fn read_status(&self) -> Result<u64, AcpiError> {
+        Ok(self.read()?.get_bits(0..self.status_length()))
+    }
+
+    pub fn is_event_set(&self, event: Pm1Event) -> Result<bool, AcpiError> {
+        Ok(self.read_status()?.get_bit(event as usize))
+    }
+
+    /// Clear the status bit of the given event, acknowledging it. This preserves the enable bits,
+    /// and does not affect the status of other events.
+    pub fn clear_status(&self, event: Pm1Event) -> Result<(), AcpiError> {
+        let mut pm1a = self.pm1a.read()?;
+        pm1a.set_bits(0..self.status_length(), 0);
+        pm1a.set_bit(event as usize, true);
+        self.pm1a.write(pm1a)?;
+
+        if let Some(pm1b) = &self.pm1b {
+            let mut value = pm1b.read()?;
+            value.set_bits(0..self.status_length(), 0);
+            value.set_bit(event as usize, true);
             pm1b.write(value)?;
         }
 
@@ -95,6 +215,11 @@ where
 
         Ok(pm1a | pm1b)
     }
+
+    /// The length of the status register, in bits. The enable register immediately follows it.
+    fn status_length(&self) -> usize {
+        self.pm1_event_length * 8 / 2
+    }
 }
 
 pub struct Pm1ControlRegisterBlock<H: Handler> {
@@ -159,6 +284,55 @@ where
         Ok(())
     }
 
+    /// Enter a sleep state by writing `SLP_TYP` to each control register, and then writing it again
+    /// along with `SLP_EN`. Unlike [`Pm1ControlRegisterBlock::set_sleep_typ`], this allows the
+    /// `PM1a` and `PM1b` registers to be given different values, as required by the `\_Sx`
+    /// objects.
+    pub fn enter_sleep(&self, slp_typa: u8, slp_typb: u8) -> Result<(), AcpiError> {
+        let mut pm1a = self.pm1a.read()?;
+        pm1a.set_bits(10..13, slp_typa as u64);
+        self.pm1a.write(pm1a)?;
+
+        let pm1b = if let Some(pm1b) = &self.pm1b {
+            let mut value = pm1b.read()?;
+            value.set_bits(10..13, slp_typb as u64);
+            pm1b.write(value)?;
+            Some(value)
+        } else {
+            None
+        };
+
+        /*
+         * The platform may not return from these writes, so the `PM1a` and `PM1b` values have been
+         * prepared beforehand.
+         */
+        pm1a.set_bit(13, true);
+        self.pm1a.write(pm1a)?;
+        if let (Some(pm1b), Some(mut value)) = (&self.pm1b, pm1b) {
+            value.set_bit(13, true);
+            pm1b.write(value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write `SLP_TYP` to each control register, without setting `SLP_EN`. Like
+    /// [`Pm1ControlRegisterBlock::enter_sleep`], the `PM1a` and `PM1b` registers can be given
+    /// different values.
+    pub fn set_sleep_types(&self, slp_typa: u8, slp_typb: u8) -> Result<(), AcpiError> {
+        let mut pm1a = self.pm1a.read()?;
+        pm1a.set_bits(10..13, slp_typa as u64);
+        self.pm1a.write(pm1a)?;
+
+        if let Some(pm1b) = &self.pm1b {
+            let mut value = pm1b.read()?;
+            value.set_bits(10..13, slp_typb as u64);
+            pm1b.write(value)?;
+        }
+
+        Ok(())
+    }
+
     pub fn set_sleep_typ(&self, value: u8) -> Result<(), AcpiError> {
         let mut pm1a = self.pm1a.read()?;
         pm1a.set_bits(10..13, value as u64);
@@ -173,3 +347,144 @@ where
         Ok(())
     }
 }
+
+/// A General-Purpose Event (GPE) register block. GPE blocks can be described by the FADT (`GPE0`
+/// and `GPE1`), or by `ACPI0006` GPE Block Devices in the namespace.
+///
+/// A block is split into two halves of equal length. The first half contains byte-wide `STS`
+/// status registers, and the second half contains the corresponding `EN` enable registers. Each
+/// bit of these registers corresponds to a single event, so event `n` of a block is controlled by
+/// bit `n % 8` of the `n / 8`th register of each half.
+pub struct GpeRegisterBlock<H: Handler> {
+    /// The address of the first status register of the block.
+    pub base: GenericAddress,
+    /// The length of the entire block (both the status and enable halves), in bytes.
+    pub block_length: usize,
+    handler: H,
+    mapping: Option<PhysicalMapping<H, u8>>,
+}
+
+impl<H> GpeRegisterBlock<H>
+where
+    H: Handler,
+{
+    /// Construct a `GpeRegisterBlock` from the address of its first register and its total
+    /// length, in bytes.
+    ///
+    /// Returns [`AcpiError::InvalidGenericAddress`] if the block is in I/O space and extends past
+    /// port `0xffff`.
+    ///
+    /// ### Safety
+    /// The supplied address must describe a valid GPE block of `block_length` bytes.
+    pub unsafe fn new(
+        base: GenericAddress,
+        block_length: usize,
+        handler: H,
+    ) -> Result<GpeRegisterBlock<H>, AcpiError> {
+        if !block_length.is_multiple_of(2) {
+            warn!("GPE block at {:#x} has odd length {}. Ignoring last register.", base.address, block_length);
+        }
+
+        let mapping = match base.address_space {
+            AddressSpace::SystemMemory => {
+                Some(unsafe { handler.map_physical_region(base.address as usize, block_length) })
+            }
+            AddressSpace::SystemIo => {
+                /*
+                 * Registers are accessed by adding their offset to the base port, so the whole
+                 * block must fit in the 16-bit I/O port space.
+                 */
+                if base.address.checked_add(block_length as u64).is_none_or(|end| end > 0x1_0000) {
+                    warn!("GPE block at {:#x} extends past the end of I/O port space", base.address);
+                    return Err(AcpiError::InvalidGenericAddress);
+                }
+                None
+            }
+            other => {
+                warn!("Tried to map GPE block of unsupported type {:?}", other);
+                return Err(AcpiError::LibUnimplemented);
+            }
+        };
+
+        Ok(GpeRegisterBlock { base, block_length, handler, mapping })
+    }
+
+    /// The number of status (or enable) registers in this block.
+    pub fn num_registers(&self) -> usize {
+        self.block_length / 2
+    }
+
+    /// The number of events that can be signalled by this block.
+    pub fn num_events(&self) -> usize {
+        self.num_registers() * 8
+    }
+
+    pub fn read_status(&self, event: usize) -> Result<bool, AcpiError> {
+        let register = self.read_register(self.status_register_offset(event)?);
+        Ok(register.get_bit(event % 8))
+    }
+
+    /// Clear the status bit of the given event. Status bits are cleared by writing a `1` to them,
+    /// so this does not affect the other events sharing the register.
+    pub fn clear_status(&self, event: usize) -> Result<(), AcpiError> {
+        let offset = self.status_register_offset(event)?;
+        self.write_register(offset, 1 << (event % 8));
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, event: usize) -> Result<bool, AcpiError> {
+        let register = self.read_register(self.enable_register_offset(event)?);
+        Ok(register.get_bit(event % 8))
+    }
+
+    pub fn set_event_enabled(&self, event: usize, enabled: bool) -> Result<(), AcpiError> {
+        let offset = self.enable_register_offset(event)?;
+        let mut value = self.read_register(offset);
+        value.set_bit(event % 8, enabled);
+        self.write_register(offset, value);
+        Ok(())
+    }
+
+    /// Disable every event in the block, and clear any pending statuses.
+    pub fn disable_all(&self) {
+        for i in 0..self.num_registers() {
+            self.write_register(self.num_registers() + i, 0x00);
+            self.write_register(i, 0xff);
+        }
+    }
+
+    /// Iterate over the events that are both enabled and have their status bit set.
+    pub fn pending_events(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.num_registers()).flat_map(move |i| {
+            let pending = self.read_register(i) & self.read_register(self.num_registers() + i);
+            (0..8).filter(move |bit| pending.get_bit(*bit)).map(move |bit| i * 8 + bit)
+        })
+    }
+
+    fn status_register_offset(&self, event: usize) -> Result<usize, AcpiError> {
+        if event >= self.num_events() {
+            return Err(AcpiError::InvalidGpe(event));
+        }
+        Ok(event / 8)
+    }
+
+    fn enable_register_offset(&self, event: usize) -> Result<usize, AcpiError> {
+        Ok(self.num_registers() + self.status_register_offset(event)?)
+    }
+
+    fn read_register(&self, offset: usize) -> u8 {
+        match self.mapping {
+            Some(ref mapping) => unsafe { core::ptr::read_volatile(mapping.virtual_start.as_ptr().add(offset)) },
+            None => self.handler.read_io_u8(self.base.address as u16 + offset as u16),
+        }
+    }
+
+    fn write_register(&self, offset: usize, value: u8) {
+        match self.mapping {
+            Some(ref mapping) => unsafe {
+                core::ptr::write_volatile(mapping.virtual_start.as_ptr().add(offset), value)
+            },
+            None => self.handler.write_io_u8(self.base.address as u16 + offset as u16, value),
+        }
+    }
+}
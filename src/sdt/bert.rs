@@ -0,0 +1,71 @@
+use crate::{
+    AcpiTable,
+    Handler,
+    PhysicalMapping,
+    sdt::{SdtHeader, Signature, hest::GenericErrorStatusBlock},
+};
+use core::slice;
+
+/// Represents the BERT (Boot Error Record Table), which points to the Boot Error Region. Firmware
+/// uses this region to report errors that occurred during the previous boot, or before the OS
+/// took control of the system, as Generic Error Status Blocks.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+pub struct Bert {
+    pub header: SdtHeader,
+    pub boot_error_region_length: u32,
+    /// The physical address of the Boot Error Region.
+    pub boot_error_region: u64,
+}
+
+unsafe impl AcpiTable for Bert {
+    const SIGNATURE: Signature = Signature::BERT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Bert {
+    /// Map the Boot Error Region, so that the errors it reports can be read. Returns `None` if the
+    /// table does not describe a region.
+    ///
+    /// ### Safety
+    /// `boot_error_region` and `boot_error_region_length` must describe memory that is valid to
+    /// read, as the firmware reports them in the BERT.
+    pub unsafe fn map_boot_error_region<H: Handler>(&self, handler: &H) -> Option<BootErrorRegion<H>> {
+        if self.boot_error_region == 0 || self.boot_error_region_length == 0 {
+            return None;
+        }
+
+        let mapping = unsafe {
+            handler.map_physical_region(self.boot_error_region as usize, self.boot_error_region_length as usize)
+        };
+        Some(BootErrorRegion { mapping })
+    }
+}
+
+/// A mapped Boot Error Region.
+pub struct BootErrorRegion<H: Handler> {
+    mapping: PhysicalMapping<H, u8>,
+}
+
+impl<H> BootErrorRegion<H>
+where
+    H: Handler,
+{
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.mapping.virtual_start.as_ptr(), self.mapping.region_length) }
+    }
+
+    /// Iterate over the Generic Error Status Blocks in the region. Iteration stops at the first
+    /// block that does not report any errors.
+    pub fn blocks(&self) -> impl Iterator<Item = GenericErrorStatusBlock<'_>> {
+        let mut bytes = self.bytes();
+        core::iter::from_fn(move || {
+            let block = GenericErrorStatusBlock::parse(bytes)?;
+            bytes = &bytes[block.length()..];
+            Some(block)
+        })
+    }
+}
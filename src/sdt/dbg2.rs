@@ -0,0 +1,203 @@
+use crate::{
+    AcpiTable,
+    address::RawGenericAddress,
+    sdt::{SdtHeader, Signature, spcr::SpcrInterfaceType},
+};
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+    str::{self, Utf8Error},
+};
+use log::warn;
+
+/// Represents the DBG2 (Debug Port Table 2), which describes the debug ports available in the
+/// system. Unlike the SPCR, a single table can describe multiple ports, each of which may be
+/// described by multiple registers.
+///
+/// For more information, see [the official documentation](https://learn.microsoft.com/en-us/windows-hardware/drivers/bringup/acpi-debug-port-table).
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Dbg2 {
+    pub header: SdtHeader,
+    /// The offset of the device information structures from the start of the table.
+    pub device_info_offset: u32,
+    pub device_info_count: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Dbg2 {
+    const SIGNATURE: Signature = Signature::DBG2;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Dbg2 {
+    pub fn devices(self: Pin<&Self>) -> Dbg2DeviceIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Dbg2 as *const u8 };
+        let offset = self.device_info_offset.min(self.header.length);
+        Dbg2DeviceIter {
+            pointer: unsafe { ptr.add(offset as usize) },
+            remaining_length: self.header.length - offset,
+            remaining_devices: self.device_info_count,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Dbg2DeviceIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    remaining_devices: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for Dbg2DeviceIter<'a> {
+    type Item = &'a DeviceInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_devices == 0 || (self.remaining_length as usize) < mem::size_of::<DeviceInfo>() {
+            return None;
+        }
+
+        let device = unsafe { &*(self.pointer as *const DeviceInfo) };
+        if device.length as u32 > self.remaining_length || (device.length as usize) < mem::size_of::<DeviceInfo>()
+        {
+            warn!("Invalid device information structure in DBG2 - extending past length of table. Ignoring");
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(device.length as usize) };
+        self.remaining_length -= device.length as u32;
+        self.remaining_devices -= 1;
+        Some(device)
+    }
+}
+
+/// Describes a single debug port. All offsets are from the start of this structure.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DeviceInfo {
+    pub revision: u8,
+    pub length: u16,
+    pub num_registers: u8,
+    pub namespace_string_length: u16,
+    pub namespace_string_offset: u16,
+    pub oem_data_length: u16,
+    pub oem_data_offset: u16,
+    pub port_type: u16,
+    pub port_subtype: u16,
+    _reserved: u16,
+    pub base_address_register_offset: u16,
+    pub address_size_offset: u16,
+}
+
+impl DeviceInfo {
+    pub fn port_type(&self) -> Dbg2PortType {
+        Dbg2PortType::new(self.port_type, self.port_subtype)
+    }
+
+    /// The registers used to access the port. The size of each register block is given by the
+    /// corresponding entry of [`DeviceInfo::address_sizes`].
+    pub fn base_address_registers(&self) -> &[RawGenericAddress] {
+        match self.field_bytes(
+            self.base_address_register_offset,
+            self.num_registers as usize * mem::size_of::<RawGenericAddress>(),
+        ) {
+            Some(bytes) => unsafe {
+                slice::from_raw_parts(bytes.as_ptr() as *const RawGenericAddress, self.num_registers as usize)
+            },
+            None => {
+                warn!("DBG2 device base address registers extend past the end of the structure. Ignoring");
+                &[]
+            }
+        }
+    }
+
+    /// The sizes, in bytes, of the register blocks described by
+    /// [`DeviceInfo::base_address_registers`].
+    pub fn address_sizes(&self) -> impl Iterator<Item = u32> + '_ {
+        let bytes =
+            self.field_bytes(self.address_size_offset, self.num_registers as usize * mem::size_of::<u32>());
+        if bytes.is_none() {
+            warn!("DBG2 device address sizes extend past the end of the structure. Ignoring");
+        }
+        bytes
+            .unwrap_or(&[])
+            .chunks_exact(mem::size_of::<u32>())
+            .map(|size| u32::from_le_bytes(size.try_into().unwrap()))
+    }
+
+    /// A fully qualified reference to the object that represents this device in the ACPI
+    /// namespace, or `"."` if there is no such object.
+    pub fn namespace_string(&self) -> Result<&str, Utf8Error> {
+        let bytes =
+            self.field_bytes(self.namespace_string_offset, self.namespace_string_length as usize).unwrap_or(&[]);
+        let length = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        str::from_utf8(&bytes[0..length])
+    }
+
+    pub fn oem_data(&self) -> &[u8] {
+        if self.oem_data_offset == 0 {
+            return &[];
+        }
+        self.field_bytes(self.oem_data_offset, self.oem_data_length as usize).unwrap_or(&[])
+    }
+
+    /// Get the bytes of a field at the given offset from the start of this structure, if it lies
+    /// within the structure.
+    fn field_bytes(&self, offset: u16, length: usize) -> Option<&[u8]> {
+        if offset as usize + length > self.length as usize {
+            return None;
+        }
+        Some(unsafe {
+            slice::from_raw_parts((self as *const DeviceInfo as *const u8).add(offset as usize), length)
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dbg2UsbSubtype {
+    Xhci,
+    Ehci,
+    Reserved(u16),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dbg2PortType {
+    /// A serial port. The subtypes are shared with the interface types of the SPCR.
+    Serial(SpcrInterfaceType),
+    /// An IEEE 1394 (FireWire) host controller.
+    Ieee1394,
+    Usb(Dbg2UsbSubtype),
+    /// A network adapter. The subtype is the PCI vendor ID of the adapter.
+    Net {
+        vendor_id: u16,
+    },
+    Reserved {
+        port_type: u16,
+        port_subtype: u16,
+    },
+}
+
+impl Dbg2PortType {
+    pub fn new(port_type: u16, port_subtype: u16) -> Dbg2PortType {
+        match port_type {
+            0x8000 if port_subtype <= 0xff => Dbg2PortType::Serial(SpcrInterfaceType::from(port_subtype as u8)),
+            0x8001 => Dbg2PortType::Ieee1394,
+            0x8002 => Dbg2PortType::Usb(match port_subtype {
+                0x0000 => Dbg2UsbSubtype::Xhci,
+                0x0001 => Dbg2UsbSubtype::Ehci,
+                other => Dbg2UsbSubtype::Reserved(other),
+            }),
+            0x8003 => Dbg2PortType::Net { vendor_id: port_subtype },
+            _ => Dbg2PortType::Reserved { port_type, port_subtype },
+        }
+    }
+}
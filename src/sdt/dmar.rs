@@ -0,0 +1,356 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+    str,
+};
+use log::warn;
+
+/// Represents the DMAR (DMA Remapping Reporting) table, which describes the Intel VT-d DMA
+/// remapping hardware units present in the system, the devices under each of them, and memory
+/// regions that must be kept identity-mapped for DMA.
+///
+/// Like the MADT, this is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Dmar {
+    pub header: SdtHeader,
+    /// The maximum DMA physical addressability supported by the platform is `host_address_width + 1`
+    /// bits.
+    pub host_address_width: u8,
+    pub flags: DmarFlags,
+    _reserved: [u8; 10],
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Dmar {
+    const SIGNATURE: Signature = Signature::DMAR;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct DmarFlags: u8 {
+        /// The platform supports interrupt remapping.
+        const INTR_REMAP = 1;
+        /// The firmware requests that OSPM does not enable x2APIC mode, if interrupt remapping is
+        /// enabled with x2APIC support.
+        const X2APIC_OPT_OUT = 1 << 1;
+        /// The firmware has set up DMA protection, and OSPM should keep DMA remapping enabled for
+        /// devices that are not explicitly trusted.
+        const DMA_CTRL_PLATFORM_OPT_IN = 1 << 2;
+    }
+}
+
+impl Dmar {
+    pub fn entries(self: Pin<&Self>) -> DmarEntryIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Dmar as *const u8 };
+        DmarEntryIter {
+            pointer: unsafe { ptr.add(mem::size_of::<Dmar>()) },
+            remaining_length: self.header.length - mem::size_of::<Dmar>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DmarEntryIter<'a> {
+    pointer: *const u8,
+    /*
+     * The iterator can only have at most `u32::MAX` remaining bytes, because the length of the
+     * whole SDT can only be at most `u32::MAX`.
+     */
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum DmarEntry<'a> {
+    Drhd(&'a Drhd),
+    Rmrr(&'a Rmrr),
+    Atsr(&'a Atsr),
+    Rhsa(&'a Rhsa),
+    Andd(&'a Andd),
+    Satc(&'a Satc),
+}
+
+impl<'a> Iterator for DmarEntryIter<'a> {
+    type Item = DmarEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length > 0 {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length as u32 > self.remaining_length
+                || (header.length as usize) < mem::size_of::<EntryHeader>()
+            {
+                warn!("Invalid entry of type {} in DMAR - extending past length of table. Ignoring", {
+                    header.typ
+                });
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.byte_offset(header.length as isize) };
+            self.remaining_length = self.remaining_length.saturating_sub(header.length as u32);
+
+            match header.typ {
+                0 => return Some(DmarEntry::Drhd(unsafe { &*(entry_pointer as *const Drhd) })),
+                1 => return Some(DmarEntry::Rmrr(unsafe { &*(entry_pointer as *const Rmrr) })),
+                2 => return Some(DmarEntry::Atsr(unsafe { &*(entry_pointer as *const Atsr) })),
+                3 => return Some(DmarEntry::Rhsa(unsafe { &*(entry_pointer as *const Rhsa) })),
+                4 => return Some(DmarEntry::Andd(unsafe { &*(entry_pointer as *const Andd) })),
+                5 => return Some(DmarEntry::Satc(unsafe { &*(entry_pointer as *const Satc) })),
+                other => warn!("Unrecognised entry in DMAR of type {}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub typ: u16,
+    pub length: u16,
+}
+
+/// Iterate over the device scopes that follow a fixed-size DMAR entry of type `T`.
+///
+/// ### Safety
+/// `entry` must be a reference into a DMAR, whose header's length covers its device scopes.
+unsafe fn device_scopes_after<T>(entry: &T, header: EntryHeader) -> DeviceScopeIter<'_> {
+    DeviceScopeIter {
+        pointer: unsafe { (entry as *const T as *const u8).add(mem::size_of::<T>()) },
+        remaining_length: (header.length as usize).saturating_sub(mem::size_of::<T>()),
+        _phantom: PhantomData,
+    }
+}
+
+/// DMA Remapping Hardware Unit Definition. Describes a single remapping hardware unit, and the
+/// devices it is responsible for.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Drhd {
+    pub header: EntryHeader,
+    pub flags: DrhdFlags,
+    /// The size of the unit's register set is `2^size` 4KiB pages.
+    pub size: u8,
+    pub segment: u16,
+    pub register_base_address: u64,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct DrhdFlags: u8 {
+        /// This unit is responsible for all PCI devices in its segment that are not reported
+        /// under another unit. Its device scopes only contain I/O APICs and HPETs.
+        const INCLUDE_PCI_ALL = 1;
+    }
+}
+
+impl Drhd {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        unsafe { device_scopes_after(self, self.header) }
+    }
+}
+
+/// Reserved Memory Region Reporting. Describes a region of memory that the firmware may use for
+/// DMA from the given devices, and that so must be identity-mapped for them.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Rmrr {
+    pub header: EntryHeader,
+    _reserved: u16,
+    pub segment: u16,
+    pub base_address: u64,
+    /// The last address of the region (inclusive).
+    pub limit_address: u64,
+}
+
+impl Rmrr {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        unsafe { device_scopes_after(self, self.header) }
+    }
+}
+
+/// Root Port ATS Capability Reporting. Describes the PCIe root ports that support Address
+/// Translation Services.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Atsr {
+    pub header: EntryHeader,
+    pub flags: AtsrFlags,
+    _reserved: u8,
+    pub segment: u16,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct AtsrFlags: u8 {
+        /// All root ports in the segment support ATS. The device scopes are empty.
+        const ALL_PORTS = 1;
+    }
+}
+
+impl Atsr {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        unsafe { device_scopes_after(self, self.header) }
+    }
+}
+
+/// Remapping Hardware Static Affinity. Associates a remapping hardware unit with a proximity
+/// domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Rhsa {
+    pub header: EntryHeader,
+    _reserved: u32,
+    pub register_base_address: u64,
+    pub proximity_domain: u32,
+}
+
+/// ACPI Namespace Device Declaration. Associates an ACPI namespace device with the number used
+/// to refer to it in device scopes.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Andd {
+    pub header: EntryHeader,
+    _reserved: [u8; 3],
+    pub acpi_device_number: u8,
+}
+
+impl Andd {
+    /// The fully-qualified path of the device in the namespace.
+    pub fn object_name(&self) -> Option<&str> {
+        let length = (self.header.length as usize).saturating_sub(mem::size_of::<Andd>());
+        let bytes = unsafe {
+            slice::from_raw_parts((self as *const Andd as *const u8).add(mem::size_of::<Andd>()), length)
+        };
+        let length = bytes.iter().position(|&b| b == 0).unwrap_or(length);
+        str::from_utf8(&bytes[0..length]).ok()
+    }
+}
+
+/// SoC Integrated Address Translation Cache. Describes devices integrated into the SoC that have
+/// an address translation cache.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Satc {
+    pub header: EntryHeader,
+    pub flags: SatcFlags,
+    _reserved: u8,
+    pub segment: u16,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct SatcFlags: u8 {
+        /// The devices must have ATS enabled when DMA remapping is enabled.
+        const ATC_REQUIRED = 1;
+    }
+}
+
+impl Satc {
+    pub fn device_scopes(&self) -> DeviceScopeIter<'_> {
+        unsafe { device_scopes_after(self, self.header) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceScopeType {
+    PciEndpoint,
+    /// A PCI-PCI bridge, and all devices below it.
+    PciSubHierarchy,
+    /// An I/O APIC. The enumeration ID is its I/O APIC ID.
+    IoApic,
+    /// An MSI-capable HPET. The enumeration ID is its HPET number.
+    Hpet,
+    /// An ACPI namespace device. The enumeration ID is the device number from an [`Andd`].
+    NamespaceDevice,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DeviceScopeHeader {
+    pub typ: u8,
+    pub length: u8,
+    pub flags: u8,
+    _reserved: u8,
+    pub enumeration_id: u8,
+    pub start_bus_number: u8,
+}
+
+/// A single hop in a device scope's path. Each element but the last describes a PCI-PCI bridge.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct DeviceScopePathEntry {
+    pub device: u8,
+    pub function: u8,
+}
+
+/// Identifies a device, by the path of PCI-PCI bridges taken from a bus to reach it.
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceScope<'a> {
+    pub header: &'a DeviceScopeHeader,
+    pub path: &'a [DeviceScopePathEntry],
+}
+
+impl DeviceScope<'_> {
+    pub fn typ(&self) -> DeviceScopeType {
+        match self.header.typ {
+            1 => DeviceScopeType::PciEndpoint,
+            2 => DeviceScopeType::PciSubHierarchy,
+            3 => DeviceScopeType::IoApic,
+            4 => DeviceScopeType::Hpet,
+            5 => DeviceScopeType::NamespaceDevice,
+            other => DeviceScopeType::Reserved(other),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeviceScopeIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for DeviceScopeIter<'a> {
+    type Item = DeviceScope<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_length < mem::size_of::<DeviceScopeHeader>() {
+            return None;
+        }
+
+        let header = unsafe { &*(self.pointer as *const DeviceScopeHeader) };
+        let length = header.length as usize;
+        if length > self.remaining_length || length < mem::size_of::<DeviceScopeHeader>() {
+            warn!("Invalid device scope in DMAR - extending past length of entry. Ignoring");
+            return None;
+        }
+
+        let path = unsafe {
+            slice::from_raw_parts(
+                self.pointer.add(mem::size_of::<DeviceScopeHeader>()) as *const DeviceScopePathEntry,
+                (length - mem::size_of::<DeviceScopeHeader>()) / mem::size_of::<DeviceScopePathEntry>(),
+            )
+        };
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length;
+
+        Some(DeviceScope { header, path })
+    }
+}
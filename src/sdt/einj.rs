@@ -0,0 +1,106 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature, erst::InstructionEntry},
+};
+use core::{marker::PhantomPinned, mem, pin::Pin, slice};
+
+/// Represents the EINJ (Error Injection Table), which describes how the OS can inject hardware
+/// errors, to test its error handling. Each step of an injection is performed by executing the
+/// instructions associated with an [`InjectionAction`]. The instructions use the same format as
+/// those of the ERST, but only the `READ_REGISTER`, `READ_REGISTER_VALUE`, `WRITE_REGISTER`,
+/// `WRITE_REGISTER_VALUE` and `NOOP` instructions are permitted.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Einj {
+    pub header: SdtHeader,
+    pub injection_header_size: u32,
+    pub injection_flags: u8,
+    _reserved: [u8; 3],
+    pub injection_entry_count: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Einj {
+    const SIGNATURE: Signature = Signature::EINJ;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Einj {
+    pub fn entries(self: Pin<&Self>) -> &[InstructionEntry] {
+        let count = (self.injection_entry_count as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<Einj>())
+                / mem::size_of::<InstructionEntry>(),
+        );
+        unsafe {
+            slice::from_raw_parts(
+                (Pin::into_inner_unchecked(self) as *const Einj as *const u8).add(mem::size_of::<Einj>())
+                    as *const InstructionEntry,
+                count,
+            )
+        }
+    }
+
+    /// Get the instructions that make up the given action, in the order they should be executed.
+    pub fn instructions(self: Pin<&Self>, action: InjectionAction) -> impl Iterator<Item = &InstructionEntry> {
+        self.entries().iter().filter(move |entry| InjectionAction::from(entry.action) == action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InjectionAction {
+    BeginInjectionOperation,
+    GetTriggerErrorActionTable,
+    SetErrorType,
+    GetErrorType,
+    EndOperation,
+    ExecuteOperation,
+    CheckBusyStatus,
+    GetCommandStatus,
+    SetErrorTypeWithAddress,
+    GetExecuteOperationTimings,
+    Reserved(u8),
+}
+
+impl From<u8> for InjectionAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x0 => InjectionAction::BeginInjectionOperation,
+            0x1 => InjectionAction::GetTriggerErrorActionTable,
+            0x2 => InjectionAction::SetErrorType,
+            0x3 => InjectionAction::GetErrorType,
+            0x4 => InjectionAction::EndOperation,
+            0x5 => InjectionAction::ExecuteOperation,
+            0x6 => InjectionAction::CheckBusyStatus,
+            0x7 => InjectionAction::GetCommandStatus,
+            0x8 => InjectionAction::SetErrorTypeWithAddress,
+            0x9 => InjectionAction::GetExecuteOperationTimings,
+            other => InjectionAction::Reserved(other),
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// The types of error that can be injected, as reported by the `GET_ERROR_TYPE` action and
+    /// written by the `SET_ERROR_TYPE` action.
+    #[derive(Clone, Copy, Debug)]
+    pub struct ErrorTypes: u32 {
+        const PROCESSOR_CORRECTABLE = 1 << 0;
+        const PROCESSOR_UNCORRECTABLE_NON_FATAL = 1 << 1;
+        const PROCESSOR_UNCORRECTABLE_FATAL = 1 << 2;
+        const MEMORY_CORRECTABLE = 1 << 3;
+        const MEMORY_UNCORRECTABLE_NON_FATAL = 1 << 4;
+        const MEMORY_UNCORRECTABLE_FATAL = 1 << 5;
+        const PCIE_CORRECTABLE = 1 << 6;
+        const PCIE_UNCORRECTABLE_NON_FATAL = 1 << 7;
+        const PCIE_UNCORRECTABLE_FATAL = 1 << 8;
+        const PLATFORM_CORRECTABLE = 1 << 9;
+        const PLATFORM_UNCORRECTABLE_NON_FATAL = 1 << 10;
+        const PLATFORM_UNCORRECTABLE_FATAL = 1 << 11;
+        const VENDOR_DEFINED = 1 << 31;
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    AcpiError,
+    AcpiTable,
+    Handler,
+    address::{GenericAddress, MappedGas, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+};
+use core::{marker::PhantomPinned, mem, pin::Pin, slice};
+
+/// Represents the ERST (Error Record Serialization Table), which describes how the OS can store
+/// error records in, and retrieve them from, persistent storage managed by the platform. Each
+/// operation is performed by executing the instructions associated with a series of
+/// [`SerializationAction`]s.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Erst {
+    pub header: SdtHeader,
+    pub serialization_header_size: u32,
+    _reserved: u32,
+    pub instruction_entry_count: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Erst {
+    const SIGNATURE: Signature = Signature::ERST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Erst {
+    pub fn entries(self: Pin<&Self>) -> &[InstructionEntry] {
+        let count = (self.instruction_entry_count as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<Erst>())
+                / mem::size_of::<InstructionEntry>(),
+        );
+        unsafe {
+            slice::from_raw_parts(
+                (Pin::into_inner_unchecked(self) as *const Erst as *const u8).add(mem::size_of::<Erst>())
+                    as *const InstructionEntry,
+                count,
+            )
+        }
+    }
+
+    /// Get the instructions that make up the given action, in the order they should be executed.
+    pub fn instructions(self: Pin<&Self>, action: SerializationAction) -> impl Iterator<Item = &InstructionEntry> {
+        self.entries().iter().filter(move |entry| SerializationAction::from(entry.action) == action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SerializationAction {
+    BeginWriteOperation,
+    BeginReadOperation,
+    BeginClearOperation,
+    EndOperation,
+    SetRecordOffset,
+    ExecuteOperation,
+    CheckBusyStatus,
+    GetCommandStatus,
+    GetRecordIdentifier,
+    SetRecordIdentifier,
+    GetRecordCount,
+    BeginDummyWriteOperation,
+    GetErrorLogAddressRange,
+    GetErrorLogAddressRangeLength,
+    GetErrorLogAddressRangeAttributes,
+    GetExecuteOperationTimings,
+    Reserved(u8),
+}
+
+impl From<u8> for SerializationAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x00 => SerializationAction::BeginWriteOperation,
+            0x01 => SerializationAction::BeginReadOperation,
+            0x02 => SerializationAction::BeginClearOperation,
+            0x03 => SerializationAction::EndOperation,
+            0x04 => SerializationAction::SetRecordOffset,
+            0x05 => SerializationAction::ExecuteOperation,
+            0x06 => SerializationAction::CheckBusyStatus,
+            0x07 => SerializationAction::GetCommandStatus,
+            0x08 => SerializationAction::GetRecordIdentifier,
+            0x09 => SerializationAction::SetRecordIdentifier,
+            0x0a => SerializationAction::GetRecordCount,
+            0x0b => SerializationAction::BeginDummyWriteOperation,
+            0x0d => SerializationAction::GetErrorLogAddressRange,
+            0x0e => SerializationAction::GetErrorLogAddressRangeLength,
+            0x0f => SerializationAction::GetErrorLogAddressRangeAttributes,
+            0x10 => SerializationAction::GetExecuteOperationTimings,
+            other => SerializationAction::Reserved(other),
+        }
+    }
+}
+
+/// The status of an ERST operation, as returned by the `GET_COMMAND_STATUS` action.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CommandStatus {
+    Success,
+    NotEnoughSpace,
+    HardwareNotAvailable,
+    Failed,
+    RecordStoreEmpty,
+    RecordNotFound,
+    Reserved(u8),
+}
+
+impl From<u8> for CommandStatus {
+    fn from(status: u8) -> Self {
+        match status {
+            0x0 => CommandStatus::Success,
+            0x1 => CommandStatus::NotEnoughSpace,
+            0x2 => CommandStatus::HardwareNotAvailable,
+            0x3 => CommandStatus::Failed,
+            0x4 => CommandStatus::RecordStoreEmpty,
+            0x5 => CommandStatus::RecordNotFound,
+            other => CommandStatus::Reserved(other),
+        }
+    }
+}
+
+/// A single instruction of an APEI action. This format is shared by the ERST and the EINJ, where
+/// `action` is a [`SerializationAction`] or an
+/// [`InjectionAction`](crate::sdt::einj::InjectionAction) respectively.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct InstructionEntry {
+    pub action: u8,
+    pub instruction: u8,
+    pub flags: u8,
+    _reserved: u8,
+    pub register_region: RawGenericAddress,
+    pub value: u64,
+    pub mask: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Instruction {
+    ReadRegister,
+    ReadRegisterValue,
+    WriteRegister,
+    WriteRegisterValue,
+    Noop,
+    LoadVar1,
+    LoadVar2,
+    StoreVar1,
+    Add,
+    Subtract,
+    AddValue,
+    SubtractValue,
+    Stall,
+    StallWhileTrue,
+    SkipNextInstructionIfTrue,
+    Goto,
+    SetSrcAddressBase,
+    SetDstAddressBase,
+    MoveData,
+    Reserved(u8),
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct InstructionFlags: u8 {
+        /// Bits of the register outside of the instruction's mask must be preserved when writing
+        /// to it.
+        const PRESERVE_REGISTER = 1 << 0;
+    }
+}
+
+impl InstructionEntry {
+    pub fn instruction(&self) -> Instruction {
+        match self.instruction {
+            0x00 => Instruction::ReadRegister,
+            0x01 => Instruction::ReadRegisterValue,
+            0x02 => Instruction::WriteRegister,
+            0x03 => Instruction::WriteRegisterValue,
+            0x04 => Instruction::Noop,
+            0x05 => Instruction::LoadVar1,
+            0x06 => Instruction::LoadVar2,
+            0x07 => Instruction::StoreVar1,
+            0x08 => Instruction::Add,
+            0x09 => Instruction::Subtract,
+            0x0a => Instruction::AddValue,
+            0x0b => Instruction::SubtractValue,
+            0x0c => Instruction::Stall,
+            0x0d => Instruction::StallWhileTrue,
+            0x0e => Instruction::SkipNextInstructionIfTrue,
+            0x0f => Instruction::Goto,
+            0x10 => Instruction::SetSrcAddressBase,
+            0x11 => Instruction::SetDstAddressBase,
+            0x12 => Instruction::MoveData,
+            other => Instruction::Reserved(other),
+        }
+    }
+
+    pub fn flags(&self) -> InstructionFlags {
+        InstructionFlags::from_bits_truncate(self.flags)
+    }
+
+    pub fn register(&self) -> Result<GenericAddress, AcpiError> {
+        GenericAddress::from_raw(self.register_region)
+    }
+
+    /// Map the register this instruction operates on. Use a [`GasMapper`](crate::address::GasMapper)
+    /// to map the registers of many instructions at once.
+    ///
+    /// ### Safety
+    /// The register must be valid to read from and write to.
+    pub unsafe fn map_register<H: Handler>(&self, handler: &H) -> Result<MappedGas<H>, AcpiError> {
+        unsafe { MappedGas::map_gas(self.register()?, handler) }
+    }
+
+    /// Read the instruction's register, masked by the instruction's mask.
+    pub fn read_register<H: Handler>(&self, register: &MappedGas<H>) -> Result<u64, AcpiError> {
+        Ok(register.read()? & self.mask)
+    }
+
+    /// Write `value`, masked by the instruction's mask, to the instruction's register. If the
+    /// instruction has the [`InstructionFlags::PRESERVE_REGISTER`] flag, bits outside of the mask
+    /// are preserved.
+    pub fn write_register<H: Handler>(&self, register: &MappedGas<H>, value: u64) -> Result<(), AcpiError> {
+        let value = value & self.mask;
+        if self.flags().contains(InstructionFlags::PRESERVE_REGISTER) {
+            let current = register.read()?;
+            register.write((current & !self.mask) | value)
+        } else {
+            register.write(value)
+        }
+    }
+}
@@ -1,17 +1,131 @@
 use crate::sdt::Signature;
-use core::sync::atomic::AtomicU32;
+use core::{cell::UnsafeCell, ptr, sync::atomic::AtomicU32};
 
+/// The Firmware ACPI Control Structure (FACS) is a structure in read/write memory that the
+/// firmware reserves for communication with OSPM. It contains the global lock, and the waking
+/// vectors used to resume execution after waking from a sleep state. Unlike most ACPI structures,
+/// it does not have a standard header or checksum. It is pointed to by the FADT.
 #[repr(C)]
 pub struct Facs {
     pub signature: Signature,
     pub length: u32,
+    /// A value calculated by the firmware from the hardware configuration of the system. If this
+    /// changes across a sleep state transition, the system cannot resume from `S4`.
     pub hardware_signature: u32,
-    pub firmware_waking_vector: u32,
+    /// The 32-bit physical address of the OSPM's waking vector. This is written by OSPM, and so is
+    /// only accessed through [`Facs::firmware_waking_vector`], [`Facs::waking_vector`], and
+    /// [`Facs::set_waking_vector`].
+    firmware_waking_vector: UnsafeCell<u32>,
     pub global_lock: AtomicU32,
-    pub flags: u32,
-    pub x_firmware_waking_vector: u64,
+    pub flags: FacsFlags,
+    /// The 64-bit physical address of the OSPM's waking vector. If this is non-zero, it is used
+    /// instead of `firmware_waking_vector`, in either 32-bit protected mode or 64-bit mode,
+    /// depending on `ospm_flags`.
+    x_firmware_waking_vector: UnsafeCell<u64>,
     pub version: u8,
     _reserved0: [u8; 3],
-    pub ospm_flags: u32,
+    ospm_flags: UnsafeCell<OspmFlags>,
     reserved1: [u8; 24],
 }
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct FacsFlags: u32 {
+        /// The platform supports the `S4BIOS_REQ` command, which makes the firmware save and
+        /// restore memory for `S4`.
+        const S4BIOS = 1;
+        /// The platform supports waking in 64-bit mode via `x_firmware_waking_vector`.
+        const WAKE_64BIT_SUPPORTED = 1 << 1;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct OspmFlags: u32 {
+        /// Set by OSPM to request that the firmware jumps to `x_firmware_waking_vector` in 64-bit
+        /// mode, instead of 32-bit protected mode.
+        const WAKE_64BIT = 1;
+    }
+}
+
+impl Facs {
+    pub fn is_valid(&self) -> bool {
+        self.signature == Signature::FACS
+    }
+
+    /// Returns the waking vector that the firmware will use to resume execution after waking,
+    /// if one has been set.
+    pub fn waking_vector(&self) -> Option<WakingVector> {
+        let firmware_waking_vector = self.firmware_waking_vector();
+        let x_firmware_waking_vector = self.x_firmware_waking_vector();
+
+        if self.version >= 1 && x_firmware_waking_vector != 0 {
+            if self.ospm_flags().contains(OspmFlags::WAKE_64BIT) {
+                Some(WakingVector::LongMode(x_firmware_waking_vector))
+            } else {
+                Some(WakingVector::ProtectedMode(x_firmware_waking_vector as u32))
+            }
+        } else if firmware_waking_vector != 0 {
+            Some(WakingVector::RealMode(firmware_waking_vector))
+        } else {
+            None
+        }
+    }
+
+    /// The raw value of the 32-bit `Firmware Waking Vector` field.
+    pub fn firmware_waking_vector(&self) -> u32 {
+        unsafe { ptr::read_volatile(self.firmware_waking_vector.get()) }
+    }
+
+    /// The raw value of the 64-bit `X Firmware Waking Vector` field. This is only present from
+    /// version `1` of the FACS.
+    pub fn x_firmware_waking_vector(&self) -> u64 {
+        unsafe { ptr::read_volatile(self.x_firmware_waking_vector.get()) }
+    }
+
+    pub fn ospm_flags(&self) -> OspmFlags {
+        unsafe { ptr::read_volatile(self.ospm_flags.get()) }
+    }
+
+    /// Write the waking vector fields. The caller is responsible for checking that the vector is
+    /// supported by the platform.
+    pub(crate) fn set_waking_vector(
+        &self,
+        firmware_waking_vector: u32,
+        x_firmware_waking_vector: u64,
+        wake_64bit: bool,
+    ) {
+        /*
+         * The FACS lives in memory that's shared with the firmware, so these fields are only ever
+         * accessed through volatile reads and writes.
+         */
+        unsafe {
+            ptr::write_volatile(self.firmware_waking_vector.get(), firmware_waking_vector);
+            if self.version >= 1 {
+                ptr::write_volatile(self.x_firmware_waking_vector.get(), x_firmware_waking_vector);
+
+                let mut ospm_flags = ptr::read_volatile(self.ospm_flags.get());
+                ospm_flags.set(OspmFlags::WAKE_64BIT, wake_64bit);
+                ptr::write_volatile(self.ospm_flags.get(), ospm_flags);
+            }
+        }
+    }
+
+    pub fn supports_64bit_wake(&self) -> bool {
+        self.version >= 1 && self.flags.contains(FacsFlags::WAKE_64BIT_SUPPORTED)
+    }
+}
+
+/// Describes where, and in what processor mode, the firmware should transfer control to OSPM when
+/// waking from a sleep state in which processor context is lost (e.g. `S3`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WakingVector {
+    /// Jump to the given physical address in real mode. The address must be below 1MiB; the
+    /// firmware will jump to `(address >> 4):(address & 0xf)`.
+    RealMode(u32),
+    /// Jump to the given physical address in 32-bit protected mode, with paging disabled.
+    ProtectedMode(u32),
+    /// Jump to the given physical address in 64-bit mode. Only supported if
+    /// [`Facs::supports_64bit_wake`] returns `true`.
+    LongMode(u64),
+}
@@ -0,0 +1,322 @@
+use crate::{
+    AcpiTable,
+    Handler,
+    PhysicalMapping,
+    sdt::{SdtHeader, Signature},
+};
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+};
+use log::warn;
+
+/// Represents the FPDT (Firmware Performance Data Table), which points to tables that record how
+/// long the firmware took to perform various parts of the boot and S3 resume paths. Those tables
+/// live in memory that is updated by the firmware, and so are mapped separately, with
+/// [`Fpdt::map_fbpt`] and [`Fpdt::map_s3pt`]. All timestamps are in nanoseconds.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Fpdt {
+    pub header: SdtHeader,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Fpdt {
+    const SIGNATURE: Signature = Signature::FPDT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Fpdt {
+    pub fn records(self: Pin<&Self>) -> FpdtRecordIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Fpdt as *const u8 };
+        FpdtRecordIter {
+            pointer: unsafe { ptr.add(mem::size_of::<Fpdt>()) },
+            remaining_length: (self.header.length as usize).saturating_sub(mem::size_of::<Fpdt>()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The physical address of the Firmware Basic Boot Performance Table, if the table has one.
+    pub fn fbpt_address(self: Pin<&Self>) -> Option<u64> {
+        self.records().find_map(|record| match record {
+            FpdtRecord::FirmwareBasicBootPointer(address) => Some(address),
+            _ => None,
+        })
+    }
+
+    /// The physical address of the S3 Performance Table, if the table has one.
+    pub fn s3pt_address(self: Pin<&Self>) -> Option<u64> {
+        self.records().find_map(|record| match record {
+            FpdtRecord::S3PerformancePointer(address) => Some(address),
+            _ => None,
+        })
+    }
+
+    /// Map the Firmware Basic Boot Performance Table. Returns `None` if the table does not point
+    /// to one, or it is not valid.
+    pub fn map_fbpt<H: Handler>(self: Pin<&Self>, handler: &H) -> Option<Fbpt<H>> {
+        let mapping = unsafe { map_performance_table(self.fbpt_address()?, *b"FBPT", handler)? };
+        Some(Fbpt { mapping })
+    }
+
+    /// Map the S3 Performance Table. Returns `None` if the table does not point to one, or it is
+    /// not valid.
+    pub fn map_s3pt<H: Handler>(self: Pin<&Self>, handler: &H) -> Option<S3pt<H>> {
+        let mapping = unsafe { map_performance_table(self.s3pt_address()?, *b"S3PT", handler)? };
+        Some(S3pt { mapping })
+    }
+}
+
+/// The header shared by the performance records of the FPDT and the tables it points to.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PerformanceRecordHeader {
+    pub record_type: u16,
+    pub length: u8,
+    pub revision: u8,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PerformanceTablePointerRecord {
+    pub header: PerformanceRecordHeader,
+    _reserved: u32,
+    pub address: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FpdtRecord {
+    /// The physical address of the Firmware Basic Boot Performance Table.
+    FirmwareBasicBootPointer(u64),
+    /// The physical address of the S3 Performance Table.
+    S3PerformancePointer(u64),
+    Unknown(PerformanceRecordHeader),
+}
+
+#[derive(Debug)]
+pub struct FpdtRecordIter<'a> {
+    pointer: *const u8,
+    remaining_length: usize,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl Iterator for FpdtRecordIter<'_> {
+    type Item = FpdtRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (header, bytes) = unsafe { next_record(&mut self.pointer, &mut self.remaining_length, "FPDT")? };
+
+        let pointer = || {
+            if bytes.len() < mem::size_of::<PerformanceTablePointerRecord>() {
+                return None;
+            }
+            let record = unsafe { (bytes.as_ptr() as *const PerformanceTablePointerRecord).read_unaligned() };
+            Some(record.address)
+        };
+
+        Some(match header.record_type {
+            0x0000 => pointer().map(FpdtRecord::FirmwareBasicBootPointer).unwrap_or(FpdtRecord::Unknown(header)),
+            0x0001 => pointer().map(FpdtRecord::S3PerformancePointer).unwrap_or(FpdtRecord::Unknown(header)),
+            _ => FpdtRecord::Unknown(header),
+        })
+    }
+}
+
+/// A mapped Firmware Basic Boot Performance Table.
+pub struct Fbpt<H: Handler> {
+    mapping: PhysicalMapping<H, u8>,
+}
+
+impl<H> Fbpt<H>
+where
+    H: Handler,
+{
+    pub fn records(&self) -> impl Iterator<Item = FbptRecord> + '_ {
+        let (mut pointer, mut remaining_length) = table_records(&self.mapping);
+        core::iter::from_fn(move || {
+            let (header, bytes) = unsafe { next_record(&mut pointer, &mut remaining_length, "FBPT")? };
+            Some(match header.record_type {
+                0x0002 if bytes.len() >= mem::size_of::<FirmwareBasicBootRecord>() => {
+                    FbptRecord::BasicBoot(unsafe {
+                        (bytes.as_ptr() as *const FirmwareBasicBootRecord).read_unaligned()
+                    })
+                }
+                _ => FbptRecord::Unknown(header),
+            })
+        })
+    }
+
+    /// The Firmware Basic Boot Performance Record, which contains the timestamps of the main
+    /// stages of the boot path.
+    pub fn basic_boot(&self) -> Option<FirmwareBasicBootRecord> {
+        self.records().find_map(|record| match record {
+            FbptRecord::BasicBoot(record) => Some(record),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FbptRecord {
+    BasicBoot(FirmwareBasicBootRecord),
+    Unknown(PerformanceRecordHeader),
+}
+
+/// The timestamps of the main stages of the boot path. A timestamp of `0` means that the stage
+/// was not recorded.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct FirmwareBasicBootRecord {
+    pub header: PerformanceRecordHeader,
+    _reserved: u32,
+    /// The time at which the processor came out of reset, relative to the start of the timer.
+    pub reset_end: u64,
+    /// The time at which the OS loader was loaded by the firmware.
+    pub os_loader_load_image_start: u64,
+    /// The time at which the firmware started executing the OS loader.
+    pub os_loader_start_image_start: u64,
+    /// The time at which the OS loader called `ExitBootServices`.
+    pub exit_boot_services_entry: u64,
+    /// The time at which `ExitBootServices` returned to the OS loader.
+    pub exit_boot_services_exit: u64,
+}
+
+/// A mapped S3 Performance Table.
+pub struct S3pt<H: Handler> {
+    mapping: PhysicalMapping<H, u8>,
+}
+
+impl<H> S3pt<H>
+where
+    H: Handler,
+{
+    pub fn records(&self) -> impl Iterator<Item = S3ptRecord> + '_ {
+        let (mut pointer, mut remaining_length) = table_records(&self.mapping);
+        core::iter::from_fn(move || {
+            let (header, bytes) = unsafe { next_record(&mut pointer, &mut remaining_length, "S3PT")? };
+            Some(match header.record_type {
+                0x0000 if bytes.len() >= mem::size_of::<S3ResumeRecord>() => {
+                    S3ptRecord::Resume(unsafe { (bytes.as_ptr() as *const S3ResumeRecord).read_unaligned() })
+                }
+                0x0001 if bytes.len() >= mem::size_of::<S3SuspendRecord>() => {
+                    S3ptRecord::Suspend(unsafe { (bytes.as_ptr() as *const S3SuspendRecord).read_unaligned() })
+                }
+                _ => S3ptRecord::Unknown(header),
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum S3ptRecord {
+    Resume(S3ResumeRecord),
+    Suspend(S3SuspendRecord),
+    Unknown(PerformanceRecordHeader),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct S3ResumeRecord {
+    pub header: PerformanceRecordHeader,
+    /// The number of times the system has resumed from S3 since the last full boot.
+    pub resume_count: u32,
+    /// The duration of the last resume from S3, from the processor coming out of reset to the
+    /// firmware handing control to the OS waking vector.
+    pub full_resume: u64,
+    /// The average duration of all resumes from S3 since the last full boot.
+    pub average_resume: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct S3SuspendRecord {
+    pub header: PerformanceRecordHeader,
+    /// The time at which the OS wrote `SLP_TYP` to start the last suspend to S3.
+    pub suspend_start: u64,
+    /// The time at which the OS wrote `SLP_EN` to enter S3.
+    pub suspend_end: u64,
+}
+
+/// The header of the tables pointed to by the FPDT.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+struct PerformanceTableHeader {
+    signature: [u8; 4],
+    length: u32,
+}
+
+/// Map a table pointed to by the FPDT, checking its signature.
+///
+/// ### Safety
+/// `address` must point to a performance table.
+unsafe fn map_performance_table<H: Handler>(
+    address: u64,
+    signature: [u8; 4],
+    handler: &H,
+) -> Option<PhysicalMapping<H, u8>> {
+    if address == 0 {
+        return None;
+    }
+
+    let header = unsafe {
+        handler.map_physical_region::<PerformanceTableHeader>(
+            address as usize,
+            mem::size_of::<PerformanceTableHeader>(),
+        )
+    };
+    let length = header.length as usize;
+    if header.signature != signature || length < mem::size_of::<PerformanceTableHeader>() {
+        warn!("Performance table pointed to by FPDT does not have the expected signature or length. Ignoring");
+        return None;
+    }
+    drop(header);
+
+    Some(unsafe { handler.map_physical_region(address as usize, length) })
+}
+
+/// Get a pointer to the records of a mapped performance table, and their length.
+fn table_records<H: Handler>(mapping: &PhysicalMapping<H, u8>) -> (*const u8, usize) {
+    let header_length = mem::size_of::<PerformanceTableHeader>();
+    (
+        unsafe { mapping.virtual_start.as_ptr().add(header_length) },
+        mapping.region_length.saturating_sub(header_length),
+    )
+}
+
+/// Read the next performance record, advancing past it. Returns the record's header and its
+/// bytes, including the header.
+///
+/// ### Safety
+/// `pointer` must be valid for reads of `remaining_length` bytes.
+unsafe fn next_record<'a>(
+    pointer: &mut *const u8,
+    remaining_length: &mut usize,
+    table: &str,
+) -> Option<(PerformanceRecordHeader, &'a [u8])> {
+    if *remaining_length < mem::size_of::<PerformanceRecordHeader>() {
+        return None;
+    }
+
+    let header = unsafe { (*pointer as *const PerformanceRecordHeader).read_unaligned() };
+    let length = header.length as usize;
+    if length < mem::size_of::<PerformanceRecordHeader>() || length > *remaining_length {
+        let record_type = header.record_type;
+        warn!(
+            "Invalid performance record in {} of type {} - extending past length of table. Ignoring",
+            table, record_type
+        );
+        return None;
+    }
+
+    let bytes = unsafe { slice::from_raw_parts(*pointer, length) };
+    *pointer = unsafe { pointer.add(length) };
+    *remaining_length -= length;
+    Some((header, bytes))
+}
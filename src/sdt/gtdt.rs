@@ -0,0 +1,336 @@
+use crate::{
+    AcpiError,
+    AcpiTable,
+    AcpiTables,
+    Handler,
+    sdt::{
+        ExtendedField,
+        SdtHeader,
+        Signature,
+        madt::{Polarity, TriggerMode},
+    },
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+};
+use log::warn;
+
+/// Represents the GTDT (Generic Timer Description Table), which describes the interrupts of the
+/// Arm architected timers, and any memory-mapped platform timers (GT blocks and SBSA generic
+/// watchdogs) present in the system.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Gtdt {
+    pub header: SdtHeader,
+    /// The physical address of the `CNTControlBase` frame, or `0xffff_ffff_ffff_ffff` if it is not
+    /// provided.
+    pub cnt_control_base: u64,
+    _reserved: u32,
+    pub secure_el1_timer_gsiv: u32,
+    pub secure_el1_timer_flags: u32,
+    pub non_secure_el1_timer_gsiv: u32,
+    pub non_secure_el1_timer_flags: u32,
+    pub virtual_el1_timer_gsiv: u32,
+    pub virtual_el1_timer_flags: u32,
+    pub el2_timer_gsiv: u32,
+    pub el2_timer_flags: u32,
+    /// The physical address of the `CNTReadBase` frame, or `0xffff_ffff_ffff_ffff` if it is not
+    /// provided.
+    pub cnt_read_base: u64,
+    pub platform_timer_count: u32,
+    /// The offset of the platform timer structures from the start of the table.
+    pub platform_timer_offset: u32,
+    pub virtual_el2_timer_gsiv: ExtendedField<u32, 3>,
+    pub virtual_el2_timer_flags: ExtendedField<u32, 3>,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Gtdt {
+    const SIGNATURE: Signature = Signature::GTDT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Gtdt {
+    pub fn platform_timers(self: Pin<&Self>) -> PlatformTimerIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Gtdt as *const u8 };
+        let offset = self.platform_timer_offset.min(self.header.length);
+        PlatformTimerIter {
+            pointer: unsafe { ptr.add(offset as usize) },
+            remaining_length: self.header.length - offset,
+            remaining_timers: self.platform_timer_count,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn gt_blocks(self: Pin<&Self>) -> impl Iterator<Item = &GtBlock> {
+        self.platform_timers().filter_map(|timer| match timer {
+            PlatformTimer::GtBlock(block) => Some(block),
+            _ => None,
+        })
+    }
+
+    pub fn watchdogs(self: Pin<&Self>) -> impl Iterator<Item = &SbsaWatchdog> {
+        self.platform_timers().filter_map(|timer| match timer {
+            PlatformTimer::SbsaWatchdog(watchdog) => Some(watchdog),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PlatformTimerIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    remaining_timers: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum PlatformTimer<'a> {
+    GtBlock(&'a GtBlock),
+    SbsaWatchdog(&'a SbsaWatchdog),
+}
+
+impl<'a> Iterator for PlatformTimerIter<'a> {
+    type Item = PlatformTimer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_timers > 0 && self.remaining_length > 0 {
+            if (self.remaining_length as usize) < mem::size_of::<PlatformTimerHeader>() {
+                warn!("GTDT has {} trailing bytes after its last platform timer. Ignoring", self.remaining_length);
+                return None;
+            }
+
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const PlatformTimerHeader) };
+
+            if header.length as u32 > self.remaining_length
+                || (header.length as usize) < mem::size_of::<PlatformTimerHeader>()
+            {
+                warn!("Invalid platform timer of type {} in GTDT - extending past length of table. Ignoring", {
+                    header.typ
+                });
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.byte_offset(header.length as isize) };
+            self.remaining_length = self.remaining_length.saturating_sub(header.length as u32);
+            self.remaining_timers -= 1;
+
+            let minimum_length = match header.typ {
+                0 => mem::size_of::<GtBlock>(),
+                1 => mem::size_of::<SbsaWatchdog>(),
+                _ => 0,
+            };
+            if (header.length as usize) < minimum_length {
+                warn!("Platform timer of type {} in GTDT is too short. Ignoring", header.typ);
+                continue;
+            }
+
+            match header.typ {
+                0 => return Some(PlatformTimer::GtBlock(unsafe { &*(entry_pointer as *const GtBlock) })),
+                1 => {
+                    return Some(PlatformTimer::SbsaWatchdog(unsafe { &*(entry_pointer as *const SbsaWatchdog) }));
+                }
+                other => warn!("Unrecognised platform timer in GTDT of type {}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PlatformTimerHeader {
+    pub typ: u8,
+    pub length: u16,
+    _reserved: u8,
+}
+
+/// A memory-mapped generic timer block, containing up to 8 timer frames.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GtBlock {
+    pub header: PlatformTimerHeader,
+    /// The physical address of the block's `CNTCTLBase` frame.
+    pub cnt_ctl_base: u64,
+    pub timer_count: u32,
+    /// The offset of the timer frame array from the start of this structure.
+    pub timer_offset: u32,
+}
+
+impl GtBlock {
+    pub fn timers(&self) -> &[GtBlockTimer] {
+        let offset = self.timer_offset as usize;
+        let count = self.timer_count as usize;
+        if offset + count * mem::size_of::<GtBlockTimer>() > self.header.length as usize {
+            warn!("GT block timers extend past the end of the structure. Ignoring");
+            return &[];
+        }
+        unsafe {
+            slice::from_raw_parts((self as *const GtBlock as *const u8).add(offset) as *const GtBlockTimer, count)
+        }
+    }
+}
+
+/// A single timer frame within a [`GtBlock`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GtBlockTimer {
+    pub frame_number: u8,
+    _reserved: [u8; 3],
+    /// The physical address of the frame's `CNTBaseN` frame.
+    pub cnt_base: u64,
+    /// The physical address of the frame's `CNTEL0BaseN` frame, or `0xffff_ffff_ffff_ffff` if it
+    /// is not implemented.
+    pub cnt_el0_base: u64,
+    pub physical_timer_gsiv: u32,
+    pub physical_timer_flags: u32,
+    /// The GSIV of the virtual timer, or `0` if it is not implemented.
+    pub virtual_timer_gsiv: u32,
+    pub virtual_timer_flags: u32,
+    pub common_flags: u32,
+}
+
+impl GtBlockTimer {
+    pub fn physical_timer(&self) -> TimerInterrupt {
+        TimerInterrupt::from_flags(self.physical_timer_gsiv, self.physical_timer_flags)
+    }
+
+    pub fn virtual_timer(&self) -> Option<TimerInterrupt> {
+        if self.virtual_timer_gsiv == 0 {
+            return None;
+        }
+        Some(TimerInterrupt::from_flags(self.virtual_timer_gsiv, self.virtual_timer_flags))
+    }
+
+    pub fn is_secure(&self) -> bool {
+        { self.common_flags }.get_bit(0)
+    }
+
+    pub fn always_on(&self) -> bool {
+        { self.common_flags }.get_bit(1)
+    }
+}
+
+/// An SBSA generic watchdog.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SbsaWatchdog {
+    pub header: PlatformTimerHeader,
+    pub refresh_frame_address: u64,
+    pub control_frame_address: u64,
+    pub timer_gsiv: u32,
+    pub timer_flags: u32,
+}
+
+impl SbsaWatchdog {
+    pub fn interrupt(&self) -> TimerInterrupt {
+        TimerInterrupt::from_flags(self.timer_gsiv, self.timer_flags)
+    }
+
+    pub fn is_secure(&self) -> bool {
+        { self.timer_flags }.get_bit(2)
+    }
+}
+
+/// The interrupt used by a timer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimerInterrupt {
+    pub gsiv: u32,
+    pub trigger_mode: TriggerMode,
+    pub polarity: Polarity,
+    /// Whether the timer can wake the processor from low-power states. This is only reported for
+    /// the architected timers, and is `false` otherwise.
+    pub always_on: bool,
+}
+
+impl TimerInterrupt {
+    fn from_flags(gsiv: u32, flags: u32) -> TimerInterrupt {
+        TimerInterrupt {
+            gsiv,
+            trigger_mode: if flags.get_bit(0) { TriggerMode::Edge } else { TriggerMode::Level },
+            polarity: if flags.get_bit(1) { Polarity::ActiveLow } else { Polarity::ActiveHigh },
+            always_on: false,
+        }
+    }
+
+    /// The architected timer interrupts are optional, and report a GSIV of `0` if they are not
+    /// provided.
+    fn architected(gsiv: u32, flags: u32) -> Option<TimerInterrupt> {
+        if gsiv == 0 {
+            return None;
+        }
+        Some(TimerInterrupt { always_on: flags.get_bit(2), ..TimerInterrupt::from_flags(gsiv, flags) })
+    }
+}
+
+/// Information about the Arm generic timers, as described by the GTDT.
+#[derive(Debug)]
+pub struct GtdtInfo {
+    /// The physical address of the `CNTControlBase` frame, if the platform provides it.
+    pub cnt_control_base: Option<u64>,
+    /// The physical address of the `CNTReadBase` frame, if the platform provides it.
+    pub cnt_read_base: Option<u64>,
+    pub secure_el1_timer: Option<TimerInterrupt>,
+    pub non_secure_el1_timer: Option<TimerInterrupt>,
+    pub virtual_el1_timer: Option<TimerInterrupt>,
+    pub el2_timer: Option<TimerInterrupt>,
+    /// The interrupt of the EL2 virtual timer. Only reported by revision 3 of the GTDT onwards.
+    pub virtual_el2_timer: Option<TimerInterrupt>,
+    pub num_gt_blocks: usize,
+    pub num_watchdogs: usize,
+}
+
+impl GtdtInfo {
+    pub fn new<H>(tables: &AcpiTables<H>) -> Result<GtdtInfo, AcpiError>
+    where
+        H: Handler,
+    {
+        let Some(gtdt) = tables.find_table::<Gtdt>() else { Err(AcpiError::TableNotFound(Signature::GTDT))? };
+        let table = gtdt.get();
+
+        let optional_address = |address: u64| if address == u64::MAX { None } else { Some(address) };
+        let revision = table.header.revision;
+        let virtual_el2_timer = unsafe {
+            match (
+                { table.virtual_el2_timer_gsiv }.access(revision),
+                { table.virtual_el2_timer_flags }.access(revision),
+            ) {
+                (Some(gsiv), Some(flags)) => TimerInterrupt::architected(gsiv, flags),
+                _ => None,
+            }
+        };
+
+        Ok(GtdtInfo {
+            cnt_control_base: optional_address(table.cnt_control_base),
+            cnt_read_base: optional_address(table.cnt_read_base),
+            secure_el1_timer: TimerInterrupt::architected(
+                table.secure_el1_timer_gsiv,
+                table.secure_el1_timer_flags,
+            ),
+            non_secure_el1_timer: TimerInterrupt::architected(
+                table.non_secure_el1_timer_gsiv,
+                table.non_secure_el1_timer_flags,
+            ),
+            virtual_el1_timer: TimerInterrupt::architected(
+                table.virtual_el1_timer_gsiv,
+                table.virtual_el1_timer_flags,
+            ),
+            el2_timer: TimerInterrupt::architected(table.el2_timer_gsiv, table.el2_timer_flags),
+            virtual_el2_timer,
+            num_gt_blocks: table.gt_blocks().count(),
+            num_watchdogs: table.watchdogs().count(),
+        })
+    }
+}
@@ -0,0 +1,804 @@
+use crate::{
+    AcpiError,
+    AcpiTable,
+    Handler,
+    PhysicalMapping,
+    address::{GenericAddress, MappedGas, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+    str,
+};
+use log::warn;
+use pci_types::PciAddress;
+
+/// Represents the HEST (Hardware Error Source Table), which describes the sources of hardware
+/// errors in the system, and how firmware reports them to the OS. Unlike most tables, the error
+/// source structures do not have a length field, and so their size is determined by their type -
+/// iteration stops at the first error source of an unrecognised type.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Hest {
+    pub header: SdtHeader,
+    pub error_source_count: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Hest {
+    const SIGNATURE: Signature = Signature::HEST;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Hest {
+    pub fn error_sources(self: Pin<&Self>) -> ErrorSourceIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Hest as *const u8 };
+        ErrorSourceIter {
+            pointer: unsafe { ptr.add(mem::size_of::<Hest>()) },
+            remaining_length: self.header.length.saturating_sub(mem::size_of::<Hest>() as u32),
+            remaining_sources: self.error_source_count,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Find the error source with the given source ID.
+    pub fn error_source(self: Pin<&Self>, source_id: u16) -> Option<ErrorSource<'_>> {
+        self.error_sources().find(|source| source.source_id() == source_id)
+    }
+}
+
+#[derive(Debug)]
+pub struct ErrorSourceIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    remaining_sources: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ErrorSource<'a> {
+    Ia32MachineCheck(&'a Ia32MachineCheckSource),
+    Ia32CorrectedMachineCheck(&'a Ia32CorrectedMachineCheckSource),
+    Ia32Nmi(&'a Ia32NmiSource),
+    AerRootPort(&'a AerRootPortSource),
+    AerEndpoint(&'a AerEndpointSource),
+    AerBridge(&'a AerBridgeSource),
+    Ghes(&'a GhesSource),
+    GhesV2(&'a GhesV2Source),
+    Ia32DeferredMachineCheck(&'a Ia32DeferredMachineCheckSource),
+}
+
+impl ErrorSource<'_> {
+    pub fn source_id(&self) -> u16 {
+        match self {
+            ErrorSource::Ia32MachineCheck(source) => source.source_id,
+            ErrorSource::Ia32CorrectedMachineCheck(source) => source.source_id,
+            ErrorSource::Ia32Nmi(source) => source.source_id,
+            ErrorSource::AerRootPort(source) => source.source_id,
+            ErrorSource::AerEndpoint(source) => source.source_id,
+            ErrorSource::AerBridge(source) => source.source_id,
+            ErrorSource::Ghes(source) => source.source_id,
+            ErrorSource::GhesV2(source) => source.ghes.source_id,
+            ErrorSource::Ia32DeferredMachineCheck(source) => source.source_id,
+        }
+    }
+}
+
+impl<'a> Iterator for ErrorSourceIter<'a> {
+    type Item = ErrorSource<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining_sources == 0 || (self.remaining_length as usize) < mem::size_of::<u16>() {
+            return None;
+        }
+
+        let entry_pointer = self.pointer;
+        let typ = unsafe { (entry_pointer as *const u16).read_unaligned() };
+
+        /*
+         * Error sources don't have a length field, so we first need to check that the fixed part of
+         * the structure fits, and then (for the machine check sources) that its banks do too.
+         */
+        let fixed_length = match typ {
+            0 => mem::size_of::<Ia32MachineCheckSource>(),
+            1 => mem::size_of::<Ia32CorrectedMachineCheckSource>(),
+            2 => mem::size_of::<Ia32NmiSource>(),
+            6 => mem::size_of::<AerRootPortSource>(),
+            7 => mem::size_of::<AerEndpointSource>(),
+            8 => mem::size_of::<AerBridgeSource>(),
+            9 => mem::size_of::<GhesSource>(),
+            10 => mem::size_of::<GhesV2Source>(),
+            11 => mem::size_of::<Ia32DeferredMachineCheckSource>(),
+            other => {
+                warn!("Unrecognised error source in HEST of type {}. Ignoring remaining error sources", other);
+                return None;
+            }
+        };
+        if fixed_length > self.remaining_length as usize {
+            warn!("Invalid error source of type {} in HEST - extending past length of table. Ignoring", typ);
+            return None;
+        }
+
+        let (source, length) = unsafe {
+            match typ {
+                0 => {
+                    let source = &*(entry_pointer as *const Ia32MachineCheckSource);
+                    (ErrorSource::Ia32MachineCheck(source), fixed_length + bank_length(source.num_hardware_banks))
+                }
+                1 => {
+                    let source = &*(entry_pointer as *const Ia32CorrectedMachineCheckSource);
+                    let length = fixed_length + bank_length(source.num_hardware_banks);
+                    (ErrorSource::Ia32CorrectedMachineCheck(source), length)
+                }
+                2 => (ErrorSource::Ia32Nmi(&*(entry_pointer as *const Ia32NmiSource)), fixed_length),
+                6 => (ErrorSource::AerRootPort(&*(entry_pointer as *const AerRootPortSource)), fixed_length),
+                7 => (ErrorSource::AerEndpoint(&*(entry_pointer as *const AerEndpointSource)), fixed_length),
+                8 => (ErrorSource::AerBridge(&*(entry_pointer as *const AerBridgeSource)), fixed_length),
+                9 => (ErrorSource::Ghes(&*(entry_pointer as *const GhesSource)), fixed_length),
+                10 => (ErrorSource::GhesV2(&*(entry_pointer as *const GhesV2Source)), fixed_length),
+                11 => {
+                    let source = &*(entry_pointer as *const Ia32DeferredMachineCheckSource);
+                    let length = fixed_length + bank_length(source.num_hardware_banks);
+                    (ErrorSource::Ia32DeferredMachineCheck(source), length)
+                }
+                _ => unreachable!(),
+            }
+        };
+        if length > self.remaining_length as usize {
+            warn!("Invalid error source of type {} in HEST - extending past length of table. Ignoring", typ);
+            return None;
+        }
+
+        self.pointer = unsafe { self.pointer.add(length) };
+        self.remaining_length -= length as u32;
+        self.remaining_sources -= 1;
+        Some(source)
+    }
+}
+
+fn bank_length(num_hardware_banks: u8) -> usize {
+    num_hardware_banks as usize * mem::size_of::<MachineCheckBank>()
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct ErrorSourceFlags: u8 {
+        /// Firmware handles errors from this source first, and then reports them to the OS through
+        /// a GHES.
+        const FIRMWARE_FIRST = 1 << 0;
+        /// For PCIe AER sources, the settings apply to all devices of the type, rather than the
+        /// single device given in the structure.
+        const GLOBAL = 1 << 1;
+        /// Errors from this source are reported through the GHES whose related source ID is this
+        /// source's ID.
+        const GHES_ASSIST = 1 << 2;
+    }
+}
+
+/// Describes how an error source notifies the OS of errors.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HardwareErrorNotification {
+    pub typ: u8,
+    pub length: u8,
+    pub configuration_write_enable: u16,
+    /// The polling interval, in milliseconds, for polled sources.
+    pub poll_interval: u32,
+    /// The interrupt vector (or GSIV) used for interrupt-based notifications.
+    pub vector: u32,
+    pub switch_to_polling_threshold_value: u32,
+    pub switch_to_polling_threshold_window: u32,
+    pub error_threshold_value: u32,
+    pub error_threshold_window: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NotificationType {
+    Polled,
+    ExternalInterrupt,
+    LocalInterrupt,
+    Sci,
+    Nmi,
+    Cmci,
+    Mce,
+    GpioSignal,
+    ArmSea,
+    ArmSei,
+    ExternalInterruptGsiv,
+    SoftwareDelegatedException,
+    Reserved(u8),
+}
+
+impl HardwareErrorNotification {
+    pub fn notification_type(&self) -> NotificationType {
+        match self.typ {
+            0 => NotificationType::Polled,
+            1 => NotificationType::ExternalInterrupt,
+            2 => NotificationType::LocalInterrupt,
+            3 => NotificationType::Sci,
+            4 => NotificationType::Nmi,
+            5 => NotificationType::Cmci,
+            6 => NotificationType::Mce,
+            7 => NotificationType::GpioSignal,
+            8 => NotificationType::ArmSea,
+            9 => NotificationType::ArmSei,
+            10 => NotificationType::ExternalInterruptGsiv,
+            11 => NotificationType::SoftwareDelegatedException,
+            other => NotificationType::Reserved(other),
+        }
+    }
+}
+
+/// Describes one of the machine check banks of an IA-32 machine check error source.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MachineCheckBank {
+    pub bank_number: u8,
+    pub clear_status_on_initialization: u8,
+    pub status_data_format: u8,
+    _reserved: u8,
+    pub control_register_msr_address: u32,
+    pub control_init_data: u64,
+    pub status_register_msr_address: u32,
+    pub address_register_msr_address: u32,
+    pub misc_register_msr_address: u32,
+}
+
+/// Implements the accessors shared by the machine check error sources, which are followed by an
+/// array of [`MachineCheckBank`]s.
+macro_rules! impl_machine_check_source {
+    ($source:ty) => {
+        impl $source {
+            pub fn flags(&self) -> ErrorSourceFlags {
+                ErrorSourceFlags::from_bits_truncate(self.flags)
+            }
+
+            pub fn enabled(&self) -> bool {
+                self.enabled != 0
+            }
+
+            pub fn banks(&self) -> &[MachineCheckBank] {
+                unsafe {
+                    slice::from_raw_parts(
+                        (self as *const $source as *const u8).add(mem::size_of::<$source>())
+                            as *const MachineCheckBank,
+                        self.num_hardware_banks as usize,
+                    )
+                }
+            }
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ia32MachineCheckSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The value the OS should write to `IA32_MCG_CAP` during initialization.
+    pub global_capability_init_data: u64,
+    /// The value the OS should write to `IA32_MCG_CTL` during initialization.
+    pub global_control_init_data: u64,
+    pub num_hardware_banks: u8,
+    _reserved1: [u8; 7],
+}
+
+impl_machine_check_source!(Ia32MachineCheckSource);
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ia32CorrectedMachineCheckSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub notification: HardwareErrorNotification,
+    pub num_hardware_banks: u8,
+    _reserved1: [u8; 3],
+}
+
+impl_machine_check_source!(Ia32CorrectedMachineCheckSource);
+
+/// Describes the deferred machine check errors reported by some AMD processors. This has the same
+/// layout as [`Ia32CorrectedMachineCheckSource`].
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ia32DeferredMachineCheckSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub notification: HardwareErrorNotification,
+    pub num_hardware_banks: u8,
+    _reserved1: [u8; 3],
+}
+
+impl_machine_check_source!(Ia32DeferredMachineCheckSource);
+
+/// Describes the use of NMIs to report non-fatal errors.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ia32NmiSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved: u32,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub max_raw_data_length: u32,
+}
+
+/// Implements the accessors shared by the PCIe Advanced Error Reporting sources.
+macro_rules! impl_aer_source {
+    ($source:ty) => {
+        impl $source {
+            pub fn flags(&self) -> ErrorSourceFlags {
+                ErrorSourceFlags::from_bits_truncate(self.flags)
+            }
+
+            pub fn enabled(&self) -> bool {
+                self.enabled != 0
+            }
+
+            /// The PCIe device described by this source, or `None` if the source applies to all
+            /// devices of its type (see [`ErrorSourceFlags::GLOBAL`]), or the device or function
+            /// number is invalid.
+            pub fn pci_address(&self) -> Option<PciAddress> {
+                let (bus, device, function) = (self.bus, self.device, self.function);
+                if self.flags().contains(ErrorSourceFlags::GLOBAL) || device > 31 || function > 7 {
+                    return None;
+                }
+
+                Some(PciAddress::new(
+                    bus.get_bits(8..24) as u16,
+                    bus.get_bits(0..8) as u8,
+                    device as u8,
+                    function as u8,
+                ))
+            }
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AerRootPortSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The segment (bits `8..24`) and bus (bits `0..8`) of the device.
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+    pub root_error_command: u32,
+}
+
+impl_aer_source!(AerRootPortSource);
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AerEndpointSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The segment (bits `8..24`) and bus (bits `0..8`) of the device.
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+}
+
+impl_aer_source!(AerEndpointSource);
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AerBridgeSource {
+    pub typ: u16,
+    pub source_id: u16,
+    _reserved0: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    /// The segment (bits `8..24`) and bus (bits `0..8`) of the device.
+    pub bus: u32,
+    pub device: u16,
+    pub function: u16,
+    pub device_control: u16,
+    _reserved1: u16,
+    pub uncorrectable_error_mask: u32,
+    pub uncorrectable_error_severity: u32,
+    pub correctable_error_mask: u32,
+    pub advanced_error_capabilities_and_control: u32,
+    pub secondary_uncorrectable_error_mask: u32,
+    pub secondary_uncorrectable_error_severity: u32,
+    pub secondary_advanced_error_capabilities_and_control: u32,
+}
+
+impl_aer_source!(AerBridgeSource);
+
+/// A Generic Hardware Error Source, which reports errors by writing a [`GenericErrorStatusBlock`]
+/// to the memory pointed to by its Error Status Address register.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GhesSource {
+    pub typ: u16,
+    pub source_id: u16,
+    /// The ID of the error source this GHES reports errors for, or `0xffff` if it is not acting on
+    /// behalf of another source.
+    pub related_source_id: u16,
+    pub flags: u8,
+    pub enabled: u8,
+    pub num_records_to_preallocate: u32,
+    pub max_sections_per_record: u32,
+    pub max_raw_data_length: u32,
+    /// A register containing the physical address of the source's error status block.
+    pub error_status_address: RawGenericAddress,
+    pub notification: HardwareErrorNotification,
+    pub error_status_block_length: u32,
+}
+
+impl GhesSource {
+    pub fn enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    pub fn related_source_id(&self) -> Option<u16> {
+        if self.related_source_id == 0xffff { None } else { Some(self.related_source_id) }
+    }
+
+    pub fn error_status_address(&self) -> Result<GenericAddress, AcpiError> {
+        GenericAddress::from_raw(self.error_status_address)
+    }
+
+    /// Map the source's Error Status Address register, giving a [`Ghes`] that can be used to read
+    /// the errors it reports.
+    ///
+    /// ### Safety
+    /// The source's registers must be valid to access.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> Result<Ghes<H>, AcpiError> {
+        Ok(Ghes {
+            error_status_address: unsafe { MappedGas::map_gas(self.error_status_address()?, handler)? },
+            error_status_block_length: self.error_status_block_length as usize,
+            read_ack: None,
+            handler: handler.clone(),
+        })
+    }
+}
+
+/// Version 2 of the Generic Hardware Error Source, which adds a Read Ack register that the OS
+/// writes to once it has consumed the error status block.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GhesV2Source {
+    pub ghes: GhesSource,
+    pub read_ack_register: RawGenericAddress,
+    /// The bits of the Read Ack register that must be preserved when acknowledging an error.
+    pub read_ack_preserve: u64,
+    /// The bits that should be set in the Read Ack register to acknowledge an error.
+    pub read_ack_write: u64,
+}
+
+impl GhesV2Source {
+    pub fn read_ack_register(&self) -> Result<GenericAddress, AcpiError> {
+        GenericAddress::from_raw(self.read_ack_register)
+    }
+
+    /// Map the source's Error Status Address and Read Ack registers, giving a [`Ghes`] that can be
+    /// used to read and acknowledge the errors it reports.
+    ///
+    /// ### Safety
+    /// The source's registers must be valid to access.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> Result<Ghes<H>, AcpiError> {
+        let ghes = self.ghes;
+        let mut mapped = unsafe { ghes.map(handler)? };
+        mapped.read_ack = Some(ReadAck {
+            register: unsafe { MappedGas::map_gas(self.read_ack_register()?, handler)? },
+            preserve: self.read_ack_preserve,
+            write: self.read_ack_write,
+        });
+        Ok(mapped)
+    }
+}
+
+/// A mapped Generic Hardware Error Source.
+pub struct Ghes<H: Handler> {
+    error_status_address: MappedGas<H>,
+    error_status_block_length: usize,
+    read_ack: Option<ReadAck<H>>,
+    handler: H,
+}
+
+struct ReadAck<H: Handler> {
+    register: MappedGas<H>,
+    preserve: u64,
+    write: u64,
+}
+
+impl<H> Ghes<H>
+where
+    H: Handler,
+{
+    /// Read the source's error status block. Returns `None` if the source is not currently
+    /// reporting an error. Once the error has been consumed, it should be acknowledged with
+    /// [`GhesStatus::acknowledge`], so that the firmware can report further errors.
+    pub fn read(&self) -> Result<Option<GhesStatus<'_, H>>, AcpiError> {
+        let address = self.error_status_address.read()?;
+        if address == 0 || self.error_status_block_length == 0 {
+            return Ok(None);
+        }
+
+        let mapping =
+            unsafe { self.handler.map_physical_region(address as usize, self.error_status_block_length) };
+        let status = GhesStatus { ghes: self, mapping };
+        if status.block().is_none() {
+            return Ok(None);
+        }
+        Ok(Some(status))
+    }
+}
+
+/// An error status block that has been read from a [`Ghes`].
+pub struct GhesStatus<'a, H: Handler> {
+    ghes: &'a Ghes<H>,
+    mapping: PhysicalMapping<H, u8>,
+}
+
+impl<H> GhesStatus<'_, H>
+where
+    H: Handler,
+{
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.mapping.virtual_start.as_ptr(), self.mapping.region_length) }
+    }
+
+    /// The error status block. Returns `None` if the block no longer reports any errors.
+    pub fn block(&self) -> Option<GenericErrorStatusBlock<'_>> {
+        GenericErrorStatusBlock::parse(self.bytes())
+    }
+
+    /// Acknowledge the error, by clearing the block status and, for GHESv2 sources, writing to
+    /// the Read Ack register. This signals to the firmware that the block can be reused.
+    pub fn acknowledge(self) -> Result<(), AcpiError> {
+        unsafe { core::ptr::write_volatile(self.mapping.virtual_start.as_ptr() as *mut u32, 0) };
+
+        if let Some(read_ack) = &self.ghes.read_ack {
+            let current = read_ack.register.read()?;
+            read_ack.register.write((current & read_ack.preserve) | read_ack.write)?;
+        }
+        Ok(())
+    }
+}
+
+/// The header of a Generic Error Status Block. These blocks are used to report errors from
+/// generic hardware error sources, and errors that occurred during the previous boot (see the
+/// BERT).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericErrorStatusHeader {
+    pub block_status: u32,
+    /// The offset of the raw error data from the start of the block.
+    pub raw_data_offset: u32,
+    pub raw_data_length: u32,
+    /// The length of the generic error data entries that follow this header.
+    pub data_length: u32,
+    pub error_severity: u32,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct BlockStatus: u32 {
+        const UNCORRECTABLE_ERROR_VALID = 1 << 0;
+        const CORRECTABLE_ERROR_VALID = 1 << 1;
+        const MULTIPLE_UNCORRECTABLE_ERRORS = 1 << 2;
+        const MULTIPLE_CORRECTABLE_ERRORS = 1 << 3;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorSeverity {
+    Recoverable,
+    Fatal,
+    Corrected,
+    /// The error is informational only. This is called `None` by the specification.
+    Informational,
+    Reserved(u32),
+}
+
+impl From<u32> for ErrorSeverity {
+    fn from(severity: u32) -> Self {
+        match severity {
+            0 => ErrorSeverity::Recoverable,
+            1 => ErrorSeverity::Fatal,
+            2 => ErrorSeverity::Corrected,
+            3 => ErrorSeverity::Informational,
+            other => ErrorSeverity::Reserved(other),
+        }
+    }
+}
+
+/// A Generic Error Status Block, containing a set of generic error data entries (each of which
+/// describes an error as a UEFI CPER section), and optionally some raw error data.
+#[derive(Clone, Copy, Debug)]
+pub struct GenericErrorStatusBlock<'a> {
+    pub header: GenericErrorStatusHeader,
+    bytes: &'a [u8],
+}
+
+impl<'a> GenericErrorStatusBlock<'a> {
+    /// Parse the Generic Error Status Block at the start of `bytes`. Returns `None` if the block
+    /// does not report any errors, or if it extends past the end of `bytes`.
+    pub fn parse(bytes: &'a [u8]) -> Option<GenericErrorStatusBlock<'a>> {
+        if bytes.len() < mem::size_of::<GenericErrorStatusHeader>() {
+            return None;
+        }
+        let header = unsafe { (bytes.as_ptr() as *const GenericErrorStatusHeader).read_unaligned() };
+        if header.block_status == 0 {
+            return None;
+        }
+
+        let data_end = mem::size_of::<GenericErrorStatusHeader>() + header.data_length as usize;
+        let length = if header.raw_data_length != 0 {
+            data_end.max(header.raw_data_offset as usize + header.raw_data_length as usize)
+        } else {
+            data_end
+        };
+        if length > bytes.len() {
+            warn!("Generic Error Status Block extends past the end of its region. Ignoring");
+            return None;
+        }
+
+        Some(GenericErrorStatusBlock { header, bytes: &bytes[0..length] })
+    }
+
+    /// The total length of the block, including its raw error data.
+    pub fn length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn block_status(&self) -> BlockStatus {
+        BlockStatus::from_bits_truncate(self.header.block_status)
+    }
+
+    /// The number of generic error data entries, as reported by the block status.
+    pub fn error_data_entry_count(&self) -> u16 {
+        { self.header.block_status }.get_bits(4..14) as u16
+    }
+
+    pub fn severity(&self) -> ErrorSeverity {
+        ErrorSeverity::from(self.header.error_severity)
+    }
+
+    pub fn entries(&self) -> GenericErrorDataIter<'a> {
+        let start = mem::size_of::<GenericErrorStatusHeader>();
+        GenericErrorDataIter { bytes: &self.bytes[start..(start + self.header.data_length as usize)] }
+    }
+
+    pub fn raw_data(&self) -> &'a [u8] {
+        if self.header.raw_data_length == 0 {
+            return &[];
+        }
+        let start = self.header.raw_data_offset as usize;
+        &self.bytes[start..(start + self.header.raw_data_length as usize)]
+    }
+}
+
+/// The header of a generic error data entry. Revisions `0x300` onwards are followed by an 8-byte
+/// timestamp.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct GenericErrorDataHeader {
+    /// The GUID of the UEFI CPER section type that describes the error.
+    pub section_type: [u8; 16],
+    pub error_severity: u32,
+    pub revision: u16,
+    pub validation_bits: u8,
+    pub flags: u8,
+    pub error_data_length: u32,
+    pub fru_id: [u8; 16],
+    pub fru_text: [u8; 20],
+}
+
+/// A single generic error data entry of a [`GenericErrorStatusBlock`].
+#[derive(Clone, Copy, Debug)]
+pub struct GenericErrorData<'a> {
+    pub header: GenericErrorDataHeader,
+    pub timestamp: Option<u64>,
+    /// The error data, which is a CPER section of the type given by the header.
+    pub data: &'a [u8],
+}
+
+impl GenericErrorData<'_> {
+    pub fn severity(&self) -> ErrorSeverity {
+        ErrorSeverity::from(self.header.error_severity)
+    }
+
+    pub fn fru_id(&self) -> Option<[u8; 16]> {
+        if self.header.validation_bits.get_bit(0) { Some(self.header.fru_id) } else { None }
+    }
+
+    pub fn fru_text(&self) -> Option<&str> {
+        if !self.header.validation_bits.get_bit(1) {
+            return None;
+        }
+        let text = &self.header.fru_text;
+        let length = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+        str::from_utf8(&text[0..length]).ok()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericErrorDataIter<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for GenericErrorDataIter<'a> {
+    type Item = GenericErrorData<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.len() < mem::size_of::<GenericErrorDataHeader>() {
+            return None;
+        }
+        let header = unsafe { (self.bytes.as_ptr() as *const GenericErrorDataHeader).read_unaligned() };
+
+        let (timestamp, data_offset) = if header.revision >= 0x300 {
+            let offset = mem::size_of::<GenericErrorDataHeader>();
+            let Some(timestamp) = self.bytes.get(offset..(offset + 8)) else {
+                warn!("Generic error data entry extends past the end of its status block. Ignoring");
+                return None;
+            };
+            let timestamp = u64::from_le_bytes(timestamp.try_into().unwrap());
+            (if header.validation_bits.get_bit(2) { Some(timestamp) } else { None }, offset + 8)
+        } else {
+            (None, mem::size_of::<GenericErrorDataHeader>())
+        };
+
+        let end = data_offset + header.error_data_length as usize;
+        if end > self.bytes.len() {
+            warn!("Generic error data entry extends past the end of its status block. Ignoring");
+            return None;
+        }
+
+        let data = &self.bytes[data_offset..end];
+        self.bytes = &self.bytes[end..];
+        Some(GenericErrorData { header, timestamp, data })
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+};
+use log::warn;
+
+/// Represents the HMAT (Heterogeneous Memory Attribute Table), which describes the performance
+/// of the memory attached to each proximity domain - its latency and bandwidth when accessed from
+/// each initiator proximity domain, and any memory-side caches in front of it. Proximity domains
+/// match those of the SRAT.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Hmat {
+    pub header: SdtHeader,
+    _reserved: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Hmat {
+    const SIGNATURE: Signature = Signature::HMAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Hmat {
+    pub fn entries(self: Pin<&Self>) -> HmatEntryIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Hmat as *const u8 };
+        HmatEntryIter {
+            pointer: unsafe { ptr.add(mem::size_of::<Hmat>()) },
+            remaining_length: self.header.length.saturating_sub(mem::size_of::<Hmat>() as u32),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HmatEntryIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum HmatEntry<'a> {
+    MemoryProximityDomainAttributes(&'a MemoryProximityDomainAttributes),
+    LatencyBandwidthInfo(&'a LatencyBandwidthInfo),
+    MemorySideCacheInfo(&'a MemorySideCacheInfo),
+}
+
+impl<'a> Iterator for HmatEntryIter<'a> {
+    type Item = HmatEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length as usize >= mem::size_of::<EntryHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length > self.remaining_length || (header.length as usize) < mem::size_of::<EntryHeader>() {
+                warn!("Invalid entry of type {} in HMAT - extending past length of table. Ignoring", {
+                    header.typ
+                });
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.add(header.length as usize) };
+            self.remaining_length -= header.length;
+
+            match header.typ {
+                0 if header.length as usize >= mem::size_of::<MemoryProximityDomainAttributes>() => {
+                    return Some(HmatEntry::MemoryProximityDomainAttributes(unsafe {
+                        &*(entry_pointer as *const MemoryProximityDomainAttributes)
+                    }));
+                }
+                1 if header.length as usize >= mem::size_of::<LatencyBandwidthInfo>() => {
+                    return Some(HmatEntry::LatencyBandwidthInfo(unsafe {
+                        &*(entry_pointer as *const LatencyBandwidthInfo)
+                    }));
+                }
+                2 if header.length as usize >= mem::size_of::<MemorySideCacheInfo>() => {
+                    return Some(HmatEntry::MemorySideCacheInfo(unsafe {
+                        &*(entry_pointer as *const MemorySideCacheInfo)
+                    }));
+                }
+                other => warn!("Unrecognised entry in HMAT of type {}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub typ: u16,
+    _reserved: u16,
+    pub length: u32,
+}
+
+/// Associates the memory of a proximity domain with the initiator proximity domain it is
+/// attached to (e.g. the processors of the same socket).
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryProximityDomainAttributes {
+    pub header: EntryHeader,
+    pub flags: u16,
+    _reserved0: u16,
+    pub initiator_proximity_domain: u32,
+    pub memory_proximity_domain: u32,
+    _reserved1: u32,
+    _reserved2: u64,
+    _reserved3: u64,
+}
+
+impl MemoryProximityDomainAttributes {
+    /// The initiator proximity domain the memory is attached to, if the entry describes one.
+    pub fn initiator_proximity_domain(&self) -> Option<u32> {
+        if { self.flags }.get_bit(0) { Some(self.initiator_proximity_domain) } else { None }
+    }
+
+    /// The proximity domain of the memory described by the entry. In tables of revision `1`
+    /// (ACPI 6.2), this is only valid if bit `1` of `flags` is set. `revision` is the revision of
+    /// the HMAT.
+    pub fn memory_proximity_domain(&self, revision: u8) -> Option<u32> {
+        if revision < 2 && !{ self.flags }.get_bit(1) { None } else { Some(self.memory_proximity_domain) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryHierarchy {
+    /// The attributes describe the memory itself.
+    Memory,
+    /// The attributes describe the memory-side cache of the given level in front of the memory.
+    MemorySideCache(u8),
+    Reserved(u8),
+}
+
+/// The kind of attribute described by a [`LatencyBandwidthInfo`] entry. Latencies are in
+/// picoseconds, and bandwidths are in MB/s, as reported by [`LatencyBandwidthInfo::value`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HmatDataType {
+    AccessLatency,
+    ReadLatency,
+    WriteLatency,
+    AccessBandwidth,
+    ReadBandwidth,
+    WriteBandwidth,
+    Reserved(u8),
+}
+
+impl HmatDataType {
+    pub fn is_latency(&self) -> bool {
+        matches!(self, HmatDataType::AccessLatency | HmatDataType::ReadLatency | HmatDataType::WriteLatency)
+    }
+}
+
+impl From<u8> for HmatDataType {
+    fn from(data_type: u8) -> Self {
+        match data_type {
+            0 => HmatDataType::AccessLatency,
+            1 => HmatDataType::ReadLatency,
+            2 => HmatDataType::WriteLatency,
+            3 => HmatDataType::AccessBandwidth,
+            4 => HmatDataType::ReadBandwidth,
+            5 => HmatDataType::WriteBandwidth,
+            other => HmatDataType::Reserved(other),
+        }
+    }
+}
+
+/// The System Locality Latency and Bandwidth Information structure, which gives one attribute
+/// (e.g. read latency) of the memory of each target proximity domain, when accessed from each
+/// initiator proximity domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct LatencyBandwidthInfo {
+    pub header: EntryHeader,
+    pub flags: u8,
+    pub data_type: u8,
+    /// The minimum size of transfer used to measure the attribute, in bytes, if bit `4` of
+    /// `flags` is set.
+    pub min_transfer_size: u8,
+    _reserved0: u8,
+    pub num_initiator_proximity_domains: u32,
+    pub num_target_proximity_domains: u32,
+    _reserved1: u32,
+    /// The unit that the entries of the matrix are multiples of.
+    pub entry_base_unit: u64,
+}
+
+impl LatencyBandwidthInfo {
+    pub fn memory_hierarchy(&self) -> MemoryHierarchy {
+        match self.flags.get_bits(0..4) {
+            0 => MemoryHierarchy::Memory,
+            level @ 1..=3 => MemoryHierarchy::MemorySideCache(level),
+            other => MemoryHierarchy::Reserved(other),
+        }
+    }
+
+    pub fn data_type(&self) -> HmatDataType {
+        HmatDataType::from(self.data_type)
+    }
+
+    pub fn initiator_proximity_domains(&self) -> impl Iterator<Item = u32> + '_ {
+        self.read_u32s(0, self.num_initiator_proximity_domains as usize)
+    }
+
+    pub fn target_proximity_domains(&self) -> impl Iterator<Item = u32> + '_ {
+        self.read_u32s(
+            self.num_initiator_proximity_domains as usize * mem::size_of::<u32>(),
+            self.num_target_proximity_domains as usize,
+        )
+    }
+
+    /// Get the attribute of the memory of the target proximity domain at index `target` of
+    /// [`LatencyBandwidthInfo::target_proximity_domains`], when accessed from the initiator
+    /// proximity domain at index `initiator` of
+    /// [`LatencyBandwidthInfo::initiator_proximity_domains`]. Returns `None` if the attribute is
+    /// not provided, or if the target is unreachable from the initiator.
+    ///
+    /// `revision` is the revision of the HMAT. Tables of revision `1` (ACPI 6.2) give latencies in
+    /// nanoseconds, which are converted to picoseconds.
+    pub fn value(&self, revision: u8, initiator: usize, target: usize) -> Option<u64> {
+        let num_initiators = self.num_initiator_proximity_domains as usize;
+        let num_targets = self.num_target_proximity_domains as usize;
+        if initiator >= num_initiators || target >= num_targets {
+            return None;
+        }
+
+        let offset = mem::size_of::<LatencyBandwidthInfo>()
+            + (num_initiators + num_targets) * mem::size_of::<u32>()
+            + (initiator * num_targets + target) * mem::size_of::<u16>();
+        if offset + mem::size_of::<u16>() > self.header.length as usize {
+            return None;
+        }
+
+        let entry = unsafe {
+            ((self as *const LatencyBandwidthInfo as *const u8).add(offset) as *const u16).read_unaligned()
+        };
+        let value = match entry {
+            0 | 0xffff => return None,
+            entry => (entry as u64).checked_mul(self.entry_base_unit)?,
+        };
+        if revision < 2 && self.data_type().is_latency() { value.checked_mul(1000) } else { Some(value) }
+    }
+
+    /// Read `count` `u32`s starting at `offset` bytes after the fixed part of the structure.
+    fn read_u32s(&self, offset: usize, count: usize) -> impl Iterator<Item = u32> + '_ {
+        let count = count.min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<LatencyBandwidthInfo>() + offset)
+                / mem::size_of::<u32>(),
+        );
+        let base = unsafe {
+            (self as *const LatencyBandwidthInfo as *const u8).add(mem::size_of::<LatencyBandwidthInfo>() + offset)
+        };
+        (0..count).map(move |i| unsafe { (base as *const u32).add(i).read_unaligned() })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheAssociativity {
+    None,
+    DirectMapped,
+    /// A complex cache indexing scheme, such as a hash function.
+    ComplexCacheIndexing,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheWritePolicy {
+    None,
+    WriteBack,
+    WriteThrough,
+    Reserved(u8),
+}
+
+/// Describes a memory-side cache in front of the memory of a proximity domain.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemorySideCacheInfo {
+    pub header: EntryHeader,
+    pub memory_proximity_domain: u32,
+    _reserved0: u32,
+    /// The size of the cache, in bytes.
+    pub memory_side_cache_size: u64,
+    pub cache_attributes: u32,
+    _reserved1: u16,
+    pub num_smbios_handles: u16,
+}
+
+impl MemorySideCacheInfo {
+    /// The total number of levels of memory-side cache in front of the memory.
+    pub fn total_cache_levels(&self) -> u8 {
+        { self.cache_attributes }.get_bits(0..4) as u8
+    }
+
+    /// The level of this cache, where the cache closest to the memory is level `1`.
+    pub fn cache_level(&self) -> u8 {
+        { self.cache_attributes }.get_bits(4..8) as u8
+    }
+
+    pub fn associativity(&self) -> CacheAssociativity {
+        match { self.cache_attributes }.get_bits(8..12) as u8 {
+            0 => CacheAssociativity::None,
+            1 => CacheAssociativity::DirectMapped,
+            2 => CacheAssociativity::ComplexCacheIndexing,
+            other => CacheAssociativity::Reserved(other),
+        }
+    }
+
+    pub fn write_policy(&self) -> CacheWritePolicy {
+        match { self.cache_attributes }.get_bits(12..16) as u8 {
+            0 => CacheWritePolicy::None,
+            1 => CacheWritePolicy::WriteBack,
+            2 => CacheWritePolicy::WriteThrough,
+            other => CacheWritePolicy::Reserved(other),
+        }
+    }
+
+    /// The size of a cache line, in bytes.
+    pub fn line_size(&self) -> u16 {
+        { self.cache_attributes }.get_bits(16..32) as u16
+    }
+
+    /// The SMBIOS handles of the Type 17 structures describing the physical devices that
+    /// implement the cache.
+    pub fn smbios_handles(&self) -> impl Iterator<Item = u16> + '_ {
+        let count = (self.num_smbios_handles as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<MemorySideCacheInfo>())
+                / mem::size_of::<u16>(),
+        );
+        let base = unsafe {
+            (self as *const MemorySideCacheInfo as *const u8).add(mem::size_of::<MemorySideCacheInfo>())
+        };
+        (0..count).map(move |i| unsafe { (base as *const u16).add(i).read_unaligned() })
+    }
+}
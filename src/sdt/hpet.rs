@@ -3,6 +3,7 @@ use crate::{
     AcpiTable,
     AcpiTables,
     Handler,
+    PhysicalMapping,
     address::RawGenericAddress,
     sdt::{SdtHeader, Signature},
 };
@@ -64,6 +65,244 @@ impl HpetInfo {
             },
         })
     }
+
+    /// Map the HPET's register block, giving an [`Hpet`] that can be used to access the timer.
+    ///
+    /// ### Safety
+    /// `base_address` must be the physical address of a real HPET register block, as the mapped
+    /// registers are read and written as such.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> Hpet<H> {
+        let registers = unsafe { handler.map_physical_region(self.base_address, Hpet::<H>::REGISTER_BLOCK_SIZE) };
+        Hpet { registers }
+    }
+}
+
+/// A mapped HPET register block.
+pub struct Hpet<H: Handler> {
+    registers: PhysicalMapping<H, u64>,
+}
+
+/// The capabilities of an HPET, as read from its `General Capabilities and ID` register.
+#[derive(Clone, Copy, Debug)]
+pub struct HpetCapabilities {
+    /// The period of the main counter, in femtoseconds (10^-15 seconds).
+    pub period_fs: u32,
+    pub vendor_id: u16,
+    pub legacy_replacement_capable: bool,
+    pub main_counter_is_64bits: bool,
+    /// The number of comparators implemented. Note that this is one more than the raw field, which
+    /// is reported as-is by [`HpetInfo::num_comparators`].
+    pub num_comparators: u8,
+    pub revision: u8,
+}
+
+impl HpetCapabilities {
+    /// The frequency of the main counter, in Hz, or `None` if the HPET reports a period of `0`.
+    pub fn frequency(&self) -> Option<u64> {
+        1_000_000_000_000_000u64.checked_div(self.period_fs as u64)
+    }
+}
+
+/// The capabilities of a single comparator, as read from its `Configuration and Capability`
+/// register.
+#[derive(Clone, Copy, Debug)]
+pub struct ComparatorCapabilities {
+    /// A bitmap of the I/O APIC inputs that this comparator's interrupt can be routed to.
+    pub ioapic_routing: u32,
+    pub fsb_capable: bool,
+    pub periodic_capable: bool,
+    pub is_64bits: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComparatorMode {
+    /// Fire a single interrupt when the main counter reaches the comparator value.
+    OneShot,
+    /// Fire an interrupt every `period` ticks of the main counter.
+    Periodic,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ComparatorRouting {
+    /// Route the comparator's interrupt to the given input of the I/O APIC. The input must be
+    /// present in [`ComparatorCapabilities::ioapic_routing`].
+    IoApic { input: u8, level_triggered: bool },
+    /// Deliver the comparator's interrupt as a message on the Front Side Bus, by writing `value` to
+    /// `address` (e.g. an MSI).
+    Fsb { address: u32, value: u32 },
+}
+
+impl<H> Hpet<H>
+where
+    H: Handler,
+{
+    const REGISTER_BLOCK_SIZE: usize = 0x400;
+
+    const GENERAL_CAPABILITIES: usize = 0x00;
+    const GENERAL_CONFIG: usize = 0x10;
+    const GENERAL_INTERRUPT_STATUS: usize = 0x20;
+    const MAIN_COUNTER: usize = 0xf0;
+
+    const fn comparator_config(n: u8) -> usize {
+        0x100 + 0x20 * n as usize
+    }
+    const fn comparator_value(n: u8) -> usize {
+        0x108 + 0x20 * n as usize
+    }
+    const fn comparator_fsb_route(n: u8) -> usize {
+        0x110 + 0x20 * n as usize
+    }
+
+    pub fn capabilities(&self) -> HpetCapabilities {
+        let capabilities = self.read(Self::GENERAL_CAPABILITIES);
+        HpetCapabilities {
+            period_fs: capabilities.get_bits(32..64) as u32,
+            vendor_id: capabilities.get_bits(16..32) as u16,
+            legacy_replacement_capable: capabilities.get_bit(15),
+            main_counter_is_64bits: capabilities.get_bit(13),
+            num_comparators: capabilities.get_bits(8..13) as u8 + 1,
+            revision: capabilities.get_bits(0..8) as u8,
+        }
+    }
+
+    /// Start or stop the main counter. The main counter should be stopped while it or the
+    /// comparators are being configured.
+    pub fn set_enabled(&self, enabled: bool) {
+        let mut config = self.read(Self::GENERAL_CONFIG);
+        config.set_bit(0, enabled);
+        self.write(Self::GENERAL_CONFIG, config);
+    }
+
+    /// Enable or disable legacy replacement routing. When enabled, comparator `0` is routed to
+    /// IRQ0 of the 8259 (or I/O APIC input 2), and comparator `1` to IRQ8 (or input 8), replacing
+    /// the PIT and RTC interrupts.
+    pub fn set_legacy_replacement(&self, enabled: bool) {
+        let mut config = self.read(Self::GENERAL_CONFIG);
+        config.set_bit(1, enabled);
+        self.write(Self::GENERAL_CONFIG, config);
+    }
+
+    pub fn read_main_counter(&self) -> u64 {
+        self.read(Self::MAIN_COUNTER)
+    }
+
+    /// Set the value of the main counter. This should only be done while the counter is stopped.
+    pub fn write_main_counter(&self, value: u64) {
+        self.write(Self::MAIN_COUNTER, value);
+    }
+
+    pub fn comparator_capabilities(&self, comparator: u8) -> Result<ComparatorCapabilities, AcpiError> {
+        let config = self.read(Self::comparator_config(self.check_comparator(comparator)?));
+        Ok(ComparatorCapabilities {
+            ioapic_routing: config.get_bits(32..64) as u32,
+            fsb_capable: config.get_bit(15),
+            periodic_capable: config.get_bit(4),
+            is_64bits: config.get_bit(5),
+        })
+    }
+
+    /// Configure a comparator to fire in the given mode, with the given routing. For one-shot
+    /// comparators, `value` is the main counter value at which to fire. For periodic comparators,
+    /// the first interrupt fires when the main counter reaches `value`, and then every `period`
+    /// ticks afterwards.
+    ///
+    /// Periodic mode should only be configured while the main counter is stopped.
+    pub fn configure_comparator(
+        &self,
+        comparator: u8,
+        mode: ComparatorMode,
+        routing: ComparatorRouting,
+        value: u64,
+        period: u64,
+    ) -> Result<(), AcpiError> {
+        let capabilities = self.comparator_capabilities(comparator)?;
+        let config_offset = Self::comparator_config(comparator);
+        let mut config = self.read(config_offset);
+
+        if mode == ComparatorMode::Periodic && !capabilities.periodic_capable {
+            return Err(AcpiError::HpetComparatorUnsupported(comparator));
+        }
+
+        match routing {
+            ComparatorRouting::IoApic { input, level_triggered } => {
+                if input >= 32 || !capabilities.ioapic_routing.get_bit(input as usize) {
+                    return Err(AcpiError::HpetComparatorUnsupported(comparator));
+                }
+                config.set_bit(14, false);
+                config.set_bits(9..14, input as u64);
+                config.set_bit(1, level_triggered);
+            }
+            ComparatorRouting::Fsb { address, value } => {
+                if !capabilities.fsb_capable {
+                    return Err(AcpiError::HpetComparatorUnsupported(comparator));
+                }
+                self.write(Self::comparator_fsb_route(comparator), ((address as u64) << 32) | value as u64);
+                config.set_bit(14, true);
+                config.set_bit(1, false);
+            }
+        }
+
+        config.set_bit(3, mode == ComparatorMode::Periodic);
+        config.set_bit(2, true);
+        self.write(config_offset, config);
+
+        let value_offset = Self::comparator_value(comparator);
+        match mode {
+            ComparatorMode::OneShot => self.write(value_offset, value),
+            ComparatorMode::Periodic => {
+                /*
+                 * When the `ValueSet` bit is set, the next write to the comparator value sets the
+                 * accumulator (the next time the comparator fires), and the following write sets
+                 * the period.
+                 */
+                let mut config = self.read(config_offset);
+                config.set_bit(6, true);
+                self.write(config_offset, config);
+                self.write(value_offset, value);
+                self.write(value_offset, period);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stop a comparator from generating interrupts.
+    pub fn disable_comparator(&self, comparator: u8) -> Result<(), AcpiError> {
+        let config_offset = Self::comparator_config(self.check_comparator(comparator)?);
+        let mut config = self.read(config_offset);
+        config.set_bit(2, false);
+        self.write(config_offset, config);
+        Ok(())
+    }
+
+    /// Returns whether a level-triggered comparator's interrupt is active.
+    pub fn interrupt_active(&self, comparator: u8) -> Result<bool, AcpiError> {
+        let comparator = self.check_comparator(comparator)?;
+        Ok(self.read(Self::GENERAL_INTERRUPT_STATUS).get_bit(comparator as usize))
+    }
+
+    /// Clear a level-triggered comparator's interrupt.
+    pub fn clear_interrupt(&self, comparator: u8) -> Result<(), AcpiError> {
+        let comparator = self.check_comparator(comparator)?;
+        self.write(Self::GENERAL_INTERRUPT_STATUS, 1 << comparator);
+        Ok(())
+    }
+
+    fn check_comparator(&self, comparator: u8) -> Result<u8, AcpiError> {
+        if comparator < self.capabilities().num_comparators {
+            Ok(comparator)
+        } else {
+            Err(AcpiError::HpetComparatorUnsupported(comparator))
+        }
+    }
+
+    fn read(&self, offset: usize) -> u64 {
+        unsafe { core::ptr::read_volatile(self.registers.virtual_start.as_ptr().byte_add(offset)) }
+    }
+
+    fn write(&self, offset: usize, value: u64) {
+        unsafe { core::ptr::write_volatile(self.registers.virtual_start.as_ptr().byte_add(offset), value) }
+    }
 }
 
 #[repr(C, packed)]
@@ -0,0 +1,567 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+    str,
+};
+use log::warn;
+
+/// Represents the IORT (I/O Remapping Table), which describes the I/O topology of an Arm system:
+/// how the IDs of requests from PCI root complexes and platform devices are mapped through SMMUs
+/// to the stream IDs they use, and on to the device IDs used by GIC Interrupt Translation Services.
+///
+/// Nodes refer to each other by their offset from the start of the table. Like the MADT, this is
+/// a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Iort {
+    pub header: SdtHeader,
+    pub num_nodes: u32,
+    pub node_array_offset: u32,
+    _reserved: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Iort {
+    const SIGNATURE: Signature = Signature::IORT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+/// The maximum number of mappings that will be followed when resolving an ID, to avoid looping
+/// forever on a malformed table.
+const MAX_MAPPING_DEPTH: usize = 16;
+
+impl Iort {
+    pub fn nodes(self: Pin<&Self>) -> IortNodeIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Iort as *const u8 };
+        IortNodeIter {
+            table: ptr,
+            offset: self.node_array_offset,
+            remaining_nodes: self.num_nodes,
+            table_length: self.header.length,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the node at the given offset from the start of the table. This is how nodes refer to
+    /// each other, e.g. in the output reference of an [`IdMapping`].
+    pub fn node_at(self: Pin<&Self>, offset: u32) -> Option<IortNode<'_>> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Iort as *const u8 };
+        unsafe { node_at(ptr, offset, self.header.length) }
+    }
+
+    /// Find the root complex node for the given PCI segment.
+    pub fn root_complex(self: Pin<&Self>, segment: u32) -> Option<&RootComplexNode> {
+        self.nodes().find_map(|node| match node {
+            IortNode::RootComplex(root_complex) if { root_complex.pci_segment_number } == segment => {
+                Some(root_complex)
+            }
+            _ => None,
+        })
+    }
+
+    /// Map an ID one step through the ID mappings of `node`. Returns the node that the ID is
+    /// output to, and the output ID, or `None` if no mapping covers the ID.
+    pub fn map_id<'a>(self: Pin<&'a Self>, node: &IortNode<'a>, id: u32) -> Option<(IortNode<'a>, u32)> {
+        let mapping = node.id_mappings().iter().find(|mapping| mapping.contains(id))?;
+        let output_node = self.node_at(mapping.output_reference)?;
+        Some((output_node, mapping.map(id)?))
+    }
+
+    /// Map an ID through the ID mappings of `node`, and of each node it is output to, until it
+    /// reaches a node that does not map it further (usually an ITS group). Returns that node, and
+    /// the final ID, or `None` if the ID is not mapped by `node`.
+    pub fn resolve_id<'a>(self: Pin<&'a Self>, node: IortNode<'a>, id: u32) -> Option<(IortNode<'a>, u32)> {
+        let (mut node, mut id) = self.map_id(&node, id)?;
+        for _ in 0..MAX_MAPPING_DEPTH {
+            match self.map_id(&node, id) {
+                Some((next_node, next_id)) => (node, id) = (next_node, next_id),
+                None => return Some((node, id)),
+            }
+        }
+
+        warn!("IORT ID mappings are too deep, or contain a loop. Ignoring");
+        None
+    }
+
+    /// Find the SMMU that requests from the given PCI requester ID are translated by, and the
+    /// stream ID they use. Returns `None` if the requester is not behind an SMMU.
+    pub fn pci_stream_id(self: Pin<&Self>, segment: u32, requester_id: u16) -> Option<(IortNode<'_>, u32)> {
+        let root_complex = IortNode::RootComplex(self.root_complex(segment)?);
+        let (node, id) = self.map_id(&root_complex, requester_id as u32)?;
+        match node {
+            IortNode::SmmuV1V2(_) | IortNode::SmmuV3(_) => Some((node, id)),
+            _ => None,
+        }
+    }
+
+    /// Find the ITS group that MSIs from the given PCI requester ID are sent to, and the device ID
+    /// they use. This follows the mappings through any SMMU the requester is behind.
+    pub fn pci_its_device_id(self: Pin<&Self>, segment: u32, requester_id: u16) -> Option<(&ItsGroupNode, u32)> {
+        let root_complex = IortNode::RootComplex(self.root_complex(segment)?);
+        match self.resolve_id(root_complex, requester_id as u32)? {
+            (IortNode::ItsGroup(its_group), id) => Some((its_group, id)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct IortNodeIter<'a> {
+    table: *const u8,
+    offset: u32,
+    remaining_nodes: u32,
+    table_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Iterator for IortNodeIter<'a> {
+    type Item = IortNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_nodes > 0 {
+            self.remaining_nodes -= 1;
+
+            if self.offset as usize + mem::size_of::<NodeHeader>() > self.table_length as usize {
+                warn!("Invalid node in IORT - extending past length of table. Ignoring");
+                return None;
+            }
+            let header = unsafe { *(self.table.add(self.offset as usize) as *const NodeHeader) };
+            if (header.length as usize) < mem::size_of::<NodeHeader>()
+                || self.offset + header.length as u32 > self.table_length
+            {
+                warn!("Invalid node of type {} in IORT - extending past length of table. Ignoring", header.typ);
+                return None;
+            }
+
+            let node = unsafe { node_at(self.table, self.offset, self.table_length) };
+            self.offset += header.length as u32;
+            if node.is_some() {
+                return node;
+            }
+        }
+
+        None
+    }
+}
+
+/// ### Safety
+/// `table` must point to an IORT that is `table_length` bytes long.
+unsafe fn node_at<'a>(table: *const u8, offset: u32, table_length: u32) -> Option<IortNode<'a>> {
+    if offset as usize + mem::size_of::<NodeHeader>() > table_length as usize {
+        return None;
+    }
+
+    let node_pointer = unsafe { table.add(offset as usize) };
+    let header = unsafe { *(node_pointer as *const NodeHeader) };
+    if offset + header.length as u32 > table_length {
+        return None;
+    }
+
+    macro_rules! node {
+        ($variant:ident, $node:ty) => {{
+            if (header.length as usize) < mem::size_of::<$node>() {
+                warn!("IORT node of type {} is too short. Ignoring", header.typ);
+                return None;
+            }
+            Some(IortNode::$variant(unsafe { &*(node_pointer as *const $node) }))
+        }};
+    }
+
+    match header.typ {
+        0 => node!(ItsGroup, ItsGroupNode),
+        1 => node!(NamedComponent, NamedComponentNode),
+        2 => node!(RootComplex, RootComplexNode),
+        3 => node!(SmmuV1V2, SmmuV1V2Node),
+        4 => node!(SmmuV3, SmmuV3Node),
+        5 => node!(Pmcg, PmcgNode),
+        6 => node!(Rmr, RmrNode),
+        other => {
+            warn!("Unrecognised node in IORT of type {}", other);
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum IortNode<'a> {
+    ItsGroup(&'a ItsGroupNode),
+    NamedComponent(&'a NamedComponentNode),
+    RootComplex(&'a RootComplexNode),
+    SmmuV1V2(&'a SmmuV1V2Node),
+    SmmuV3(&'a SmmuV3Node),
+    /// A Performance Monitoring Counter Group, associated with an SMMUv3.
+    Pmcg(&'a PmcgNode),
+    /// A Reserved Memory Range node, which describes memory that must be identity-mapped through
+    /// the SMMUs for the devices that map to it.
+    Rmr(&'a RmrNode),
+}
+
+impl<'a> IortNode<'a> {
+    pub fn header(&self) -> &'a NodeHeader {
+        match *self {
+            IortNode::ItsGroup(node) => &node.header,
+            IortNode::NamedComponent(node) => &node.header,
+            IortNode::RootComplex(node) => &node.header,
+            IortNode::SmmuV1V2(node) => &node.header,
+            IortNode::SmmuV3(node) => &node.header,
+            IortNode::Pmcg(node) => &node.header,
+            IortNode::Rmr(node) => &node.header,
+        }
+    }
+
+    pub fn id_mappings(&self) -> &'a [IdMapping] {
+        let header = self.header();
+        unsafe { node_array(header, header.id_array_offset, header.num_id_mappings as usize) }
+    }
+}
+
+/// Read a field of type `T` at `offset` from the start of a node, if the node is long enough to
+/// contain it.
+///
+/// ### Safety
+/// `header` must be the header of a node in an IORT.
+unsafe fn node_field<T: Copy>(header: &NodeHeader, offset: usize) -> Option<T> {
+    if offset + mem::size_of::<T>() > header.length as usize {
+        return None;
+    }
+    Some(unsafe { ((header as *const NodeHeader as *const u8).add(offset) as *const T).read_unaligned() })
+}
+
+/// Get an array of `count` elements of type `T`, at `offset` from the start of a node.
+///
+/// ### Safety
+/// `header` must be the header of a node in an IORT.
+unsafe fn node_array<T>(header: &NodeHeader, offset: u32, count: usize) -> &[T] {
+    if offset as usize + count * mem::size_of::<T>() > header.length as usize {
+        warn!("Array in IORT node extends past the end of the node. Ignoring");
+        return &[];
+    }
+    unsafe {
+        slice::from_raw_parts((header as *const NodeHeader as *const u8).add(offset as usize) as *const T, count)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeHeader {
+    pub typ: u8,
+    pub length: u16,
+    pub revision: u8,
+    /// A unique identifier for the node. This was reserved before revision E of the IORT
+    /// specification.
+    pub identifier: u32,
+    pub num_id_mappings: u32,
+    /// The offset of the ID mapping array from the start of the node.
+    pub id_array_offset: u32,
+}
+
+/// Maps a range of input IDs (e.g. PCI requester IDs) to a range of output IDs (e.g. SMMU stream
+/// IDs or ITS device IDs) on another node.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IdMapping {
+    pub input_base: u32,
+    /// The number of IDs in the range, minus one.
+    pub num_ids: u32,
+    pub output_base: u32,
+    /// The offset of the output node from the start of the table.
+    pub output_reference: u32,
+    pub flags: IdMappingFlags,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct IdMappingFlags: u32 {
+        /// This mapping does not map a range of input IDs, and instead gives the single ID that
+        /// the node itself uses (e.g. for MSIs from an SMMU). `num_ids` is zero, and the input
+        /// fields should be ignored.
+        const SINGLE_MAPPING = 1;
+    }
+}
+
+impl IdMapping {
+    pub fn is_single_mapping(&self) -> bool {
+        { self.flags }.contains(IdMappingFlags::SINGLE_MAPPING)
+    }
+
+    /// Whether this mapping covers the given input ID. Single mappings never cover an input ID.
+    pub fn contains(&self, id: u32) -> bool {
+        !self.is_single_mapping() && id >= self.input_base && id - self.input_base <= self.num_ids
+    }
+
+    /// Map an input ID to its output ID, if this mapping covers it.
+    pub fn map(&self, id: u32) -> Option<u32> {
+        if self.contains(id) { self.output_base.checked_add(id - self.input_base) } else { None }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MemoryAccessProperties {
+    pub cache_coherency: u32,
+    pub allocation_hints: u8,
+    _reserved: u16,
+    pub memory_access_flags: u8,
+}
+
+impl MemoryAccessProperties {
+    /// Whether the device is fully coherent.
+    pub fn is_coherent(&self) -> bool {
+        self.cache_coherency == 1
+    }
+}
+
+/// A group of GIC Interrupt Translation Services. Each ITS is identified by the ID given in its
+/// MADT entry.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ItsGroupNode {
+    pub header: NodeHeader,
+    pub num_its: u32,
+}
+
+impl ItsGroupNode {
+    pub fn its_identifiers(&self) -> impl Iterator<Item = u32> + '_ {
+        /*
+         * The identifiers aren't guaranteed to be aligned, so they can't be returned as a `&[u32]`.
+         */
+        let bytes: &[[u8; 4]] =
+            unsafe { node_array(&self.header, mem::size_of::<ItsGroupNode>() as u32, self.num_its as usize) };
+        bytes.iter().map(|&id| u32::from_le_bytes(id))
+    }
+}
+
+/// A platform device, identified by its path in the namespace.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NamedComponentNode {
+    pub header: NodeHeader,
+    pub flags: u32,
+    pub memory_access_properties: MemoryAccessProperties,
+    /// The width of the device's memory addressing capability, in bits.
+    pub device_memory_address_size_limit: u8,
+}
+
+impl NamedComponentNode {
+    /// The fully-qualified path of the device in the namespace.
+    pub fn object_name(&self) -> Option<&str> {
+        let bytes: &[u8] = unsafe {
+            node_array(
+                &self.header,
+                mem::size_of::<NamedComponentNode>() as u32,
+                (self.header.length as usize).saturating_sub(mem::size_of::<NamedComponentNode>()),
+            )
+        };
+        let length = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        str::from_utf8(&bytes[0..length]).ok()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RootComplexNode {
+    pub header: NodeHeader,
+    pub memory_access_properties: MemoryAccessProperties,
+    pub ats_attribute: u32,
+    pub pci_segment_number: u32,
+    /// The width of the root complex's memory addressing capability, in bits.
+    pub memory_address_size_limit: u8,
+    pub pasid_capabilities: u16,
+    _reserved: u8,
+    pub flags: u32,
+}
+
+impl RootComplexNode {
+    pub fn supports_ats(&self) -> bool {
+        self.ats_attribute & 1 != 0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SmmuV1V2Model {
+    V1,
+    V2,
+    CoreLink400,
+    CoreLink500,
+    CoreLink401,
+    CaviumThunderX,
+    Reserved(u32),
+}
+
+/// An interrupt used by an SMMUv1 or SMMUv2.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SmmuInterrupt {
+    pub gsiv: u32,
+    /// Bit 0 is set if the interrupt is edge-triggered, and clear if it is level-triggered.
+    pub flags: u32,
+}
+
+impl SmmuInterrupt {
+    pub fn is_edge_triggered(&self) -> bool {
+        self.flags & 1 != 0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SmmuV1V2Node {
+    pub header: NodeHeader,
+    pub base_address: u64,
+    pub span: u64,
+    pub model: u32,
+    pub flags: u32,
+    pub global_interrupt_array_offset: u32,
+    pub num_context_interrupts: u32,
+    pub context_interrupt_array_offset: u32,
+    pub num_pmu_interrupts: u32,
+    pub pmu_interrupt_array_offset: u32,
+}
+
+impl SmmuV1V2Node {
+    pub fn model(&self) -> SmmuV1V2Model {
+        match self.model {
+            0 => SmmuV1V2Model::V1,
+            1 => SmmuV1V2Model::V2,
+            2 => SmmuV1V2Model::CoreLink400,
+            3 => SmmuV1V2Model::CoreLink500,
+            4 => SmmuV1V2Model::CoreLink401,
+            5 => SmmuV1V2Model::CaviumThunderX,
+            other => SmmuV1V2Model::Reserved(other),
+        }
+    }
+
+    pub fn is_coherent(&self) -> bool {
+        self.flags & (1 << 1) != 0
+    }
+
+    /// The global non-secure interrupt and global non-secure configuration interrupt, in that
+    /// order.
+    pub fn global_interrupts(&self) -> &[SmmuInterrupt] {
+        unsafe { node_array(&self.header, self.global_interrupt_array_offset, 2) }
+    }
+
+    pub fn context_interrupts(&self) -> &[SmmuInterrupt] {
+        unsafe {
+            node_array(&self.header, self.context_interrupt_array_offset, self.num_context_interrupts as usize)
+        }
+    }
+
+    pub fn pmu_interrupts(&self) -> &[SmmuInterrupt] {
+        unsafe { node_array(&self.header, self.pmu_interrupt_array_offset, self.num_pmu_interrupts as usize) }
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct SmmuV3Flags: u32 {
+        const COHACC_OVERRIDE = 1;
+        const HTTU_OVERRIDE_UPDATE_ACCESS = 1 << 1;
+        const HTTU_OVERRIDE_UPDATE_DIRTY = 1 << 2;
+        /// The `proximity_domain` field is valid.
+        const PROXIMITY_DOMAIN_VALID = 1 << 3;
+        /// The `device_id_mapping_index` field is valid.
+        const DEVICE_ID_MAPPING_INDEX_VALID = 1 << 4;
+    }
+}
+
+/// An SMMUv3 node. Nodes of older revisions end after `sync_gsiv` or the proximity domain, so
+/// the proximity domain and device ID mapping index are only read if the node is long enough to
+/// contain them.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct SmmuV3Node {
+    pub header: NodeHeader,
+    pub base_address: u64,
+    pub flags: SmmuV3Flags,
+    _reserved: u32,
+    pub vatos_address: u64,
+    pub model: u32,
+    /// The GSIV of the event queue interrupt, or `0` if the SMMU signals it with an MSI.
+    pub event_gsiv: u32,
+    pub pri_gsiv: u32,
+    pub gerr_gsiv: u32,
+    pub sync_gsiv: u32,
+}
+
+impl SmmuV3Node {
+    pub fn proximity_domain(&self) -> Option<u32> {
+        if { self.flags }.contains(SmmuV3Flags::PROXIMITY_DOMAIN_VALID) {
+            unsafe { node_field(&self.header, mem::size_of::<SmmuV3Node>()) }
+        } else {
+            None
+        }
+    }
+
+    /// The index of the single ID mapping that gives the device ID used for MSIs from the SMMU.
+    pub fn device_id_mapping_index(&self) -> Option<u32> {
+        if { self.flags }.contains(SmmuV3Flags::DEVICE_ID_MAPPING_INDEX_VALID) {
+            unsafe { node_field(&self.header, mem::size_of::<SmmuV3Node>() + 4) }
+        } else {
+            None
+        }
+    }
+
+    /// The ID mapping that gives the device ID used for MSIs from the SMMU itself, if it uses them.
+    pub fn msi_id_mapping(&self) -> Option<&IdMapping> {
+        IortNode::SmmuV3(self).id_mappings().get(self.device_id_mapping_index()? as usize)
+    }
+}
+
+/// A PMCG node. Nodes of revision `0` end after `node_reference`, so the `Page 1` base address is
+/// only read if the node is long enough to contain it.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PmcgNode {
+    pub header: NodeHeader,
+    pub page0_base_address: u64,
+    /// The GSIV of the overflow interrupt, or `0` if the PMCG signals it with an MSI.
+    pub overflow_gsiv: u32,
+    /// The offset of the node this PMCG is associated with.
+    pub node_reference: u32,
+}
+
+impl PmcgNode {
+    /// The base address of the PMCG's `Page 1` registers, if the node reports it.
+    pub fn page1_base_address(&self) -> Option<u64> {
+        unsafe { node_field(&self.header, mem::size_of::<PmcgNode>()) }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RmrNode {
+    pub header: NodeHeader,
+    pub flags: u32,
+    pub num_memory_ranges: u32,
+    pub memory_range_array_offset: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RmrMemoryRange {
+    pub base_address: u64,
+    pub length: u64,
+    _reserved: u32,
+}
+
+impl RmrNode {
+    /// Whether the OS may map the ranges at a different input address to their physical address,
+    /// rather than identity-mapping them.
+    pub fn remap_permitted(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    pub fn memory_ranges(&self) -> &[RmrMemoryRange] {
+        unsafe { node_array(&self.header, self.memory_range_array_offset, self.num_memory_ranges as usize) }
+    }
+}
@@ -0,0 +1,473 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+};
+use log::warn;
+use pci_types::PciAddress;
+
+/// Represents the IVRS (I/O Virtualization Reporting Structure), which describes the AMD-Vi
+/// IOMMUs present in the system, the devices each of them is responsible for, and memory regions
+/// with special requirements for DMA.
+///
+/// Like the MADT, this is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Ivrs {
+    pub header: SdtHeader,
+    pub iv_info: u32,
+    _reserved: u64,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Ivrs {
+    const SIGNATURE: Signature = Signature::IVRS;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Ivrs {
+    pub fn entries(self: Pin<&Self>) -> IvrsEntryIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Ivrs as *const u8 };
+        IvrsEntryIter {
+            pointer: unsafe { ptr.add(mem::size_of::<Ivrs>()) },
+            remaining_length: self.header.length - mem::size_of::<Ivrs>() as u32,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The maximum guest virtual address size supported, in bits.
+    pub fn guest_virtual_address_size(&self) -> u8 {
+        match { self.iv_info }.get_bits(5..7) {
+            0 => 48,
+            1 => 57,
+            _ => 0,
+        }
+    }
+
+    /// The maximum physical address size supported, in bits.
+    pub fn physical_address_size(&self) -> u8 {
+        { self.iv_info }.get_bits(8..15) as u8
+    }
+
+    /// The maximum virtual address size supported, in bits.
+    pub fn virtual_address_size(&self) -> u8 {
+        { self.iv_info }.get_bits(15..22) as u8
+    }
+
+    /// Whether the platform supports remapping of DMA outside of the ranges described by IVMD
+    /// blocks before the OS takes control.
+    pub fn has_preboot_dma_remapping(&self) -> bool {
+        { self.iv_info }.get_bit(1)
+    }
+
+    pub fn supports_extended_features(&self) -> bool {
+        { self.iv_info }.get_bit(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct IvrsEntryIter<'a> {
+    pointer: *const u8,
+    /*
+     * The iterator can only have at most `u32::MAX` remaining bytes, because the length of the
+     * whole SDT can only be at most `u32::MAX`.
+     */
+    remaining_length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum IvrsEntry<'a> {
+    /// An I/O Virtualization Hardware Definition block, of type `10h`.
+    Ivhd10h(&'a Ivhd),
+    /// An I/O Virtualization Hardware Definition block, of type `11h`.
+    Ivhd11h(&'a IvhdExtended),
+    /// An I/O Virtualization Hardware Definition block, of type `40h`. This has the same layout as
+    /// a type `11h` block, but its device entries can also describe ACPI namespace devices.
+    Ivhd40h(&'a IvhdExtended),
+    /// An I/O Virtualization Memory Definition block, of type `20h`, `21h`, or `22h`.
+    Ivmd(&'a Ivmd),
+}
+
+impl<'a> Iterator for IvrsEntryIter<'a> {
+    type Item = IvrsEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_length > 0 {
+            if (self.remaining_length as usize) < mem::size_of::<EntryHeader>() {
+                warn!("IVRS has {} trailing bytes after its last entry. Ignoring", self.remaining_length);
+                return None;
+            }
+
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const EntryHeader) };
+
+            if header.length as u32 > self.remaining_length
+                || (header.length as usize) < mem::size_of::<EntryHeader>()
+            {
+                warn!("Invalid entry of type {} in IVRS - extending past length of table. Ignoring", header.typ);
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.byte_offset(header.length as isize) };
+            self.remaining_length = self.remaining_length.saturating_sub(header.length as u32);
+
+            let minimum_length = match header.typ {
+                0x10 => mem::size_of::<Ivhd>(),
+                0x11 | 0x40 => mem::size_of::<IvhdExtended>(),
+                0x20..=0x22 => mem::size_of::<Ivmd>(),
+                _ => 0,
+            };
+            if (header.length as usize) < minimum_length {
+                warn!("Entry of type {:#x} in IVRS is too short. Ignoring", header.typ);
+                continue;
+            }
+
+            match header.typ {
+                0x10 => return Some(IvrsEntry::Ivhd10h(unsafe { &*(entry_pointer as *const Ivhd) })),
+                0x11 => return Some(IvrsEntry::Ivhd11h(unsafe { &*(entry_pointer as *const IvhdExtended) })),
+                0x40 => return Some(IvrsEntry::Ivhd40h(unsafe { &*(entry_pointer as *const IvhdExtended) })),
+                0x20..=0x22 => return Some(IvrsEntry::Ivmd(unsafe { &*(entry_pointer as *const Ivmd) })),
+                other => warn!("Unrecognised entry in IVRS of type {:#x}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub typ: u8,
+    pub flags: u8,
+    pub length: u16,
+}
+
+/// An I/O Virtualization Hardware Definition (IVHD) block of type `10h`, which describes a
+/// single IOMMU.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ivhd {
+    pub header: EntryHeader,
+    /// The PCI device ID (bus, device, and function) of the IOMMU.
+    pub device_id: u16,
+    /// The offset of the IOMMU's capability block in its configuration space.
+    pub capability_offset: u16,
+    pub iommu_base_address: u64,
+    pub pci_segment_group: u16,
+    pub iommu_info: u16,
+    pub iommu_feature_reporting: u32,
+}
+
+/// An IVHD block of type `11h` or `40h`. These report the IOMMU's Extended Feature Register,
+/// and have different attributes to a type `10h` block.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IvhdExtended {
+    pub header: EntryHeader,
+    pub device_id: u16,
+    pub capability_offset: u16,
+    pub iommu_base_address: u64,
+    pub pci_segment_group: u16,
+    pub iommu_info: u16,
+    pub iommu_attributes: u32,
+    /// A copy of the IOMMU's Extended Feature Register.
+    pub efr_register_image: u64,
+    /// A copy of the IOMMU's Extended Feature 2 Register.
+    pub efr2_register_image: u64,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct IvhdFlags: u8 {
+        const HT_TUNNEL = 1;
+        const PASS_POSTED_WRITE = 1 << 1;
+        const RESPONSE_PASS_POSTED_WRITE = 1 << 2;
+        const ISOCHRONOUS = 1 << 3;
+        const IOTLB_SUPPORTED = 1 << 4;
+        const COHERENT = 1 << 5;
+        const PREFETCH_SUPPORTED = 1 << 6;
+        const PPR_SUPPORTED = 1 << 7;
+    }
+}
+
+macro_rules! impl_ivhd {
+    ($ivhd:ty) => {
+        impl $ivhd {
+            pub fn flags(&self) -> IvhdFlags {
+                IvhdFlags::from_bits_retain(self.header.flags)
+            }
+
+            /// The PCI address of the IOMMU itself.
+            pub fn pci_address(&self) -> PciAddress {
+                device_id_to_pci_address(self.pci_segment_group, self.device_id)
+            }
+
+            /// The MSI message number the IOMMU uses for its event log and other interrupts.
+            pub fn msi_number(&self) -> u8 {
+                { self.iommu_info }.get_bits(0..5) as u8
+            }
+
+            /// The HyperTransport unit ID of the IOMMU.
+            pub fn unit_id(&self) -> u8 {
+                { self.iommu_info }.get_bits(8..13) as u8
+            }
+
+            /// Iterate over the device entries of this block, which describe the devices the IOMMU
+            /// is responsible for.
+            pub fn device_entries(&self) -> DeviceEntryIter<'_> {
+                let length = (self.header.length as usize).saturating_sub(mem::size_of::<$ivhd>());
+                let bytes = unsafe {
+                    slice::from_raw_parts((self as *const $ivhd as *const u8).add(mem::size_of::<$ivhd>()), length)
+                };
+                DeviceEntryIter { bytes }
+            }
+        }
+    };
+}
+
+impl_ivhd!(Ivhd);
+impl_ivhd!(IvhdExtended);
+
+/// An I/O Virtualization Memory Definition (IVMD) block, which describes a region of memory with
+/// special requirements for DMA from one or more devices.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Ivmd {
+    pub header: EntryHeader,
+    /// For type `21h`, the device the block applies to. For type `22h`, the first device in the
+    /// range. Reserved for type `20h`.
+    pub device_id: u16,
+    /// For type `22h`, the last device in the range. Reserved otherwise.
+    pub auxiliary_data: u16,
+    _reserved: u64,
+    pub start_address: u64,
+    pub memory_block_length: u64,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct IvmdFlags: u8 {
+        /// The range must be identity-mapped for the devices.
+        const UNITY = 1;
+        const READ = 1 << 1;
+        const WRITE = 1 << 2;
+        /// The range is an exclusion range - it is not translated for the devices.
+        const EXCLUSION_RANGE = 1 << 3;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IvmdDevices {
+    All,
+    Single(u16),
+    /// An inclusive range of device IDs.
+    Range(u16, u16),
+}
+
+impl Ivmd {
+    pub fn flags(&self) -> IvmdFlags {
+        IvmdFlags::from_bits_retain(self.header.flags)
+    }
+
+    /// The devices that this block applies to.
+    pub fn devices(&self) -> IvmdDevices {
+        match self.header.typ {
+            0x21 => IvmdDevices::Single(self.device_id),
+            0x22 => IvmdDevices::Range(self.device_id, self.auxiliary_data),
+            _ => IvmdDevices::All,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Settings applied to the Device Table Entries of the devices described by a device entry.
+    #[derive(Clone, Copy, Debug)]
+    pub struct DteSetting: u8 {
+        const INIT_PASS = 1;
+        const EINT_PASS = 1 << 1;
+        const NMI_PASS = 1 << 2;
+        const SYS_MGT_0 = 1 << 4;
+        const SYS_MGT_1 = 1 << 5;
+        const LINT0_PASS = 1 << 6;
+        const LINT1_PASS = 1 << 7;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpecialDeviceVariety {
+    IoApic,
+    Hpet,
+    Reserved(u8),
+}
+
+/// Describes one or more of the devices an IOMMU is responsible for. Ranges are described by a
+/// `*StartOfRange` entry, followed by an `EndOfRange` entry.
+#[derive(Clone, Copy, Debug)]
+pub enum DeviceEntry<'a> {
+    /// Applies to all devices on the IOMMU's segment.
+    All {
+        dte: DteSetting,
+    },
+    Select {
+        device_id: u16,
+        dte: DteSetting,
+    },
+    StartOfRange {
+        device_id: u16,
+        dte: DteSetting,
+    },
+    EndOfRange {
+        device_id: u16,
+    },
+    /// Requests from the device use the device ID `source_id` instead of their own.
+    AliasSelect {
+        device_id: u16,
+        dte: DteSetting,
+        source_id: u16,
+    },
+    AliasStartOfRange {
+        device_id: u16,
+        dte: DteSetting,
+        source_id: u16,
+    },
+    ExtendedSelect {
+        device_id: u16,
+        dte: DteSetting,
+        extended_setting: u32,
+    },
+    ExtendedStartOfRange {
+        device_id: u16,
+        dte: DteSetting,
+        extended_setting: u32,
+    },
+    /// An I/O APIC or HPET. `handle` is its I/O APIC ID or HPET number, and `device_id` is the
+    /// PCI device ID it uses for interrupt requests.
+    Special {
+        variety: SpecialDeviceVariety,
+        handle: u8,
+        device_id: u16,
+        dte: DteSetting,
+    },
+    /// An ACPI namespace device, identified by its `_HID`, `_CID`, and `_UID`.
+    AcpiDevice {
+        device_id: u16,
+        dte: DteSetting,
+        hid: &'a [u8; 8],
+        cid: &'a [u8; 8],
+        uid: &'a [u8],
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct DeviceEntryIter<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for DeviceEntryIter<'a> {
+    type Item = DeviceEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let typ = *self.bytes.first()?;
+
+            /*
+             * The length of each entry is encoded in the top two bits of its type, apart from
+             * variable-length entries.
+             */
+            let length = match typ {
+                0x00..=0x3f => 4,
+                0x40..=0x7f => 8,
+                0x80..=0xbf => 16,
+                0xc0..=0xff => {
+                    if self.bytes.len() < 22 {
+                        warn!("Invalid variable-length device entry in IVRS. Ignoring");
+                        return None;
+                    }
+                    22 + self.bytes[21] as usize
+                }
+            };
+            if length > self.bytes.len() {
+                warn!(
+                    "Invalid device entry of type {:#x} in IVRS - extending past length of block. Ignoring",
+                    typ
+                );
+                return None;
+            }
+
+            let (entry, rest) = self.bytes.split_at(length);
+            self.bytes = rest;
+
+            let device_id = u16::from_le_bytes([entry[1], entry[2]]);
+            let dte = DteSetting::from_bits_retain(entry[3]);
+            return Some(match typ {
+                0 => continue,
+                1 => DeviceEntry::All { dte },
+                2 => DeviceEntry::Select { device_id, dte },
+                3 => DeviceEntry::StartOfRange { device_id, dte },
+                4 => DeviceEntry::EndOfRange { device_id },
+                0x42 => DeviceEntry::AliasSelect {
+                    device_id,
+                    dte,
+                    source_id: u16::from_le_bytes([entry[5], entry[6]]),
+                },
+                0x43 => DeviceEntry::AliasStartOfRange {
+                    device_id,
+                    dte,
+                    source_id: u16::from_le_bytes([entry[5], entry[6]]),
+                },
+                0x46 => DeviceEntry::ExtendedSelect {
+                    device_id,
+                    dte,
+                    extended_setting: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                },
+                0x47 => DeviceEntry::ExtendedStartOfRange {
+                    device_id,
+                    dte,
+                    extended_setting: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                },
+                0x48 => DeviceEntry::Special {
+                    variety: match entry[7] {
+                        1 => SpecialDeviceVariety::IoApic,
+                        2 => SpecialDeviceVariety::Hpet,
+                        other => SpecialDeviceVariety::Reserved(other),
+                    },
+                    handle: entry[4],
+                    device_id: u16::from_le_bytes([entry[5], entry[6]]),
+                    dte,
+                },
+                0xf0 => DeviceEntry::AcpiDevice {
+                    device_id,
+                    dte,
+                    hid: entry[4..12].try_into().unwrap(),
+                    cid: entry[12..20].try_into().unwrap(),
+                    uid: &entry[22..],
+                },
+                other => {
+                    warn!("Unrecognised device entry in IVRS of type {:#x}", other);
+                    continue;
+                }
+            });
+        }
+    }
+}
+
+/// Convert a 16-bit device ID, as used by the IVRS, into a `PciAddress` on the given segment.
+pub fn device_id_to_pci_address(segment: u16, device_id: u16) -> PciAddress {
+    PciAddress::new(
+        segment,
+        device_id.get_bits(8..16) as u8,
+        device_id.get_bits(3..8) as u8,
+        device_id.get_bits(0..3) as u8,
+    )
+}
@@ -19,6 +19,8 @@ pub enum MadtError {
     MpsIntiInvalidPolarity,
     MpsIntiInvalidTriggerMode,
     WakeupApsTimeout,
+    /// The MADT describes a GIC, but does not contain a GIC Distributor entry.
+    GicdNotFound,
 }
 
 /// Represents the MADT - this contains the MADT header fields. You can then iterate over a `Madt`
@@ -105,6 +107,10 @@ pub enum MadtEntry<'a> {
     GicRedistributor(&'a GicRedistributorEntry),
     GicInterruptTranslationService(&'a GicInterruptTranslationServiceEntry),
     MultiprocessorWakeup(&'a MultiprocessorWakeupEntry),
+    Rintc(&'a RintcEntry),
+    Imsic(&'a ImsicEntry),
+    Aplic(&'a AplicEntry),
+    Plic(&'a PlicEntry),
 }
 
 impl<'a> Iterator for MadtEntryIter<'a> {
@@ -144,7 +150,7 @@ impl<'a> Iterator for MadtEntryIter<'a> {
                          * These entry types are reserved by the ACPI standard. We should skip them
                          * if they appear in a real MADT.
                          */
-                        0x11..=0x7f => {}
+                        0x11..=0x17 | 0x1c..=0x7f => {}
 
                         /*
                          * These entry types are reserved for OEM use. Atm, we just skip them too.
@@ -175,7 +181,11 @@ impl<'a> Iterator for MadtEntryIter<'a> {
                 (0xd => MadtEntry::GicMsiFrame as GicMsiFrameEntry),
                 (0xe => MadtEntry::GicRedistributor as GicRedistributorEntry),
                 (0xf => MadtEntry::GicInterruptTranslationService as GicInterruptTranslationServiceEntry),
-                (0x10 => MadtEntry::MultiprocessorWakeup as MultiprocessorWakeupEntry)
+                (0x10 => MadtEntry::MultiprocessorWakeup as MultiprocessorWakeupEntry),
+                (0x18 => MadtEntry::Rintc as RintcEntry),
+                (0x19 => MadtEntry::Imsic as ImsicEntry),
+                (0x1a => MadtEntry::Aplic as AplicEntry),
+                (0x1b => MadtEntry::Plic as PlicEntry)
             );
         }
 
@@ -397,6 +407,76 @@ pub struct MultiprocessorWakeupEntry {
     pub mailbox_address: u64,
 }
 
+/// RISC-V Hart Local Interrupt Controller. Describes a single hart, and its local interrupt
+/// controller.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct RintcEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    _reserved: u8,
+    pub flags: u32,
+    pub hart_id: u64,
+    pub acpi_processor_uid: u32,
+    /// The ID of the external interrupt controller (APLIC or PLIC) that this hart's external
+    /// interrupt input is connected to, if the hart does not have an IMSIC.
+    pub external_interrupt_controller_id: u32,
+    /// The physical address of this hart's IMSIC interrupt files, or `0` if it does not have one.
+    pub imsic_base_address: u64,
+    pub imsic_size: u32,
+}
+
+/// RISC-V Incoming MSI Controller. Describes the properties shared by the IMSICs of every hart.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ImsicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    _reserved: u8,
+    pub flags: u32,
+    /// The number of interrupt identities supported by each hart's supervisor-level interrupt file.
+    pub num_ids: u16,
+    /// The number of interrupt identities supported by each guest interrupt file.
+    pub num_guest_ids: u16,
+    pub guest_index_bits: u8,
+    pub hart_index_bits: u8,
+    pub group_index_bits: u8,
+    pub group_index_shift: u8,
+}
+
+/// RISC-V Advanced Platform-Level Interrupt Controller.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct AplicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    pub aplic_id: u8,
+    pub flags: u32,
+    pub hardware_id: [u8; 8],
+    /// The number of interrupt delivery controls, or `0` if the APLIC is in MSI-delivery mode.
+    pub num_idcs: u16,
+    pub num_sources: u16,
+    pub global_system_interrupt_base: u32,
+    pub aplic_address: u64,
+    pub aplic_size: u32,
+}
+
+/// RISC-V Platform-Level Interrupt Controller.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct PlicEntry {
+    pub header: EntryHeader,
+    pub version: u8,
+    pub plic_id: u8,
+    pub hardware_id: [u8; 8],
+    pub num_irqs: u16,
+    pub max_priority: u16,
+    pub flags: u32,
+    pub plic_size: u32,
+    pub plic_address: u64,
+    pub global_system_interrupt_base: u32,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum MpProtectedModeWakeupCommand {
     Noop = 0,
@@ -1,12 +1,28 @@
+pub mod bert;
 pub mod bgrt;
+pub mod dbg2;
+pub mod dmar;
+pub mod einj;
+pub mod erst;
 pub mod facs;
 pub mod fadt;
+pub mod fpdt;
+pub mod gtdt;
+pub mod hest;
+pub mod hmat;
 pub mod hpet;
+pub mod iort;
+pub mod ivrs;
 pub mod madt;
 pub mod mcfg;
+pub mod pptt;
+pub mod rhct;
 pub mod slit;
 pub mod spcr;
 pub mod srat;
+pub mod tcpa;
+pub mod tpm2;
+pub mod wdat;
 
 use crate::AcpiError;
 use core::{fmt, mem::MaybeUninit, str};
@@ -45,6 +61,7 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * FPDT - Firmware Performance Data Table
 /// * GTDT - Generic Timer Description Table
 /// * HEST - Hardware Error Source Table
+/// * HMAT - Heterogeneous Memory Attribute Table
 /// * MSCT - Maximum System Characteristics Table
 /// * MPST - Memory Power StateTable
 /// * NFIT - NVDIMM Firmware Interface Table
@@ -52,6 +69,7 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * PCCT - Platform Communications Channel Table
 /// * PHAT - Platform Health Assessment Table
 /// * PMTT - Platform Memory Topology Table
+/// * PPTT - Processor Properties Topology Table
 /// * PSDT - Persistent System Description Table
 /// * RASF - ACPI RAS Feature Table
 /// * RSDT - Root System Description Table
@@ -86,6 +104,7 @@ impl<T: Copy, const MIN_REVISION: u8> ExtendedField<T, MIN_REVISION> {
 /// * MSDM - Microsoft Data Management Table
 /// * PRMT - Platform Runtime Mechanism Table
 /// * RGRT - Regulatory Graphics Resource Table
+/// * RHCT - RISC-V Hart Capabilities Table
 /// * SDEI - Software Delegated Exceptions Interface table
 /// * SLIC - Microsoft Software Licensing table
 /// * SPCR - Microsoft Serial Port Console Redirection table
@@ -212,12 +231,14 @@ impl Signature {
     pub const FPDT: Signature = Signature(*b"FPDT");
     pub const GTDT: Signature = Signature(*b"GTDT");
     pub const HEST: Signature = Signature(*b"HEST");
+    pub const HMAT: Signature = Signature(*b"HMAT");
     pub const MSCT: Signature = Signature(*b"MSCT");
     pub const MPST: Signature = Signature(*b"MPST");
     pub const NFIT: Signature = Signature(*b"NFIT");
     pub const PCCT: Signature = Signature(*b"PCCT");
     pub const PHAT: Signature = Signature(*b"PHAT");
     pub const PMTT: Signature = Signature(*b"PMTT");
+    pub const PPTT: Signature = Signature(*b"PPTT");
     pub const PSDT: Signature = Signature(*b"PSDT");
     pub const RASF: Signature = Signature(*b"RASF");
     pub const SBST: Signature = Signature(*b"SBST");
@@ -244,6 +265,7 @@ impl Signature {
     pub const MSDM: Signature = Signature(*b"MSDM");
     pub const PRMT: Signature = Signature(*b"PRMT");
     pub const RGRT: Signature = Signature(*b"RGRT");
+    pub const RHCT: Signature = Signature(*b"RHCT");
     pub const SDEI: Signature = Signature(*b"SDEI");
     pub const SLIC: Signature = Signature(*b"SLIC");
     pub const SPCR: Signature = Signature(*b"SPCR");
@@ -0,0 +1,326 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+};
+use log::warn;
+
+/// Represents the PPTT (Processor Properties Topology Table), which describes the topology of the
+/// processors in the system as a tree of [`ProcessorHierarchyNode`]s - e.g. packages, clusters,
+/// cores, and threads - along with the caches private to each node. Nodes and caches refer to
+/// each other by their offset from the start of the table.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Pptt {
+    pub header: SdtHeader,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Pptt {
+    const SIGNATURE: Signature = Signature::PPTT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Pptt {
+    /// Iterate over the entries of the table, along with their offset from the start of the
+    /// table, which is how other entries refer to them.
+    pub fn entries(self: Pin<&Self>) -> PpttEntryIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Pptt as *const u8 };
+        PpttEntryIter {
+            table: ptr,
+            offset: mem::size_of::<Pptt>() as u32,
+            length: self.header.length,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the entry at the given offset from the start of the table.
+    pub fn entry_at(self: Pin<&Self>, offset: u32) -> Option<PpttEntry<'_>> {
+        if offset < mem::size_of::<Pptt>() as u32 {
+            return None;
+        }
+
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Pptt as *const u8 };
+        PpttEntryIter { table: ptr, offset, length: self.header.length, _phantom: PhantomData }
+            .next()
+            .map(|(_, entry)| entry)
+    }
+
+    /// Find the leaf node of the processor with the given ACPI processor UID, along with its
+    /// offset.
+    pub fn processor_node(self: Pin<&Self>, acpi_processor_uid: u32) -> Option<(u32, &ProcessorHierarchyNode)> {
+        self.entries().find_map(|(offset, entry)| match entry {
+            PpttEntry::ProcessorHierarchy(node)
+                if node.flags().contains(ProcessorHierarchyFlags::ACPI_PROCESSOR_ID_VALID)
+                    && { node.acpi_processor_id } == acpi_processor_uid
+                    && !self.has_children(offset) =>
+            {
+                Some((offset, node))
+            }
+            _ => None,
+        })
+    }
+
+    /// Whether any processor hierarchy node has the node at the given offset as its parent.
+    pub fn has_children(self: Pin<&Self>, offset: u32) -> bool {
+        self.entries()
+            .any(|(_, entry)| matches!(entry, PpttEntry::ProcessorHierarchy(node) if { node.parent } == offset))
+    }
+}
+
+#[derive(Debug)]
+pub struct PpttEntryIter<'a> {
+    table: *const u8,
+    offset: u32,
+    length: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PpttEntry<'a> {
+    ProcessorHierarchy(&'a ProcessorHierarchyNode),
+    Cache(&'a CacheTypeStructure),
+    /// The ID structure, which was removed in ACPI 6.3.
+    Id(&'a EntryHeader),
+}
+
+impl<'a> Iterator for PpttEntryIter<'a> {
+    type Item = (u32, PpttEntry<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset as usize + mem::size_of::<EntryHeader>() <= self.length as usize {
+            let offset = self.offset;
+            let entry_pointer = unsafe { self.table.add(offset as usize) };
+            let header = unsafe { *(entry_pointer as *const EntryHeader) };
+
+            if offset + header.length as u32 > self.length
+                || (header.length as usize) < mem::size_of::<EntryHeader>()
+            {
+                warn!("Invalid entry of type {} in PPTT - extending past length of table. Ignoring", header.typ);
+                return None;
+            }
+
+            self.offset += header.length as u32;
+
+            match header.typ {
+                0 if header.length as usize >= mem::size_of::<ProcessorHierarchyNode>() => {
+                    return Some((
+                        offset,
+                        PpttEntry::ProcessorHierarchy(unsafe {
+                            &*(entry_pointer as *const ProcessorHierarchyNode)
+                        }),
+                    ));
+                }
+                1 if header.length as usize >= mem::size_of::<CacheTypeStructure>() => {
+                    return Some((
+                        offset,
+                        PpttEntry::Cache(unsafe { &*(entry_pointer as *const CacheTypeStructure) }),
+                    ));
+                }
+                2 => return Some((offset, PpttEntry::Id(unsafe { &*(entry_pointer as *const EntryHeader) }))),
+                other => warn!("Unrecognised entry in PPTT of type {}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct EntryHeader {
+    pub typ: u8,
+    pub length: u8,
+}
+
+bitflags::bitflags! {
+    #[derive(Clone, Copy, Debug)]
+    pub struct ProcessorHierarchyFlags: u32 {
+        /// The node represents the boundary of a physical package.
+        const PHYSICAL_PACKAGE = 1;
+        /// The node's `acpi_processor_id` matches a processor in the MADT, or a processor
+        /// container in the namespace.
+        const ACPI_PROCESSOR_ID_VALID = 1 << 1;
+        /// The node represents a thread of a processor. Only valid on leaf nodes.
+        const PROCESSOR_IS_A_THREAD = 1 << 2;
+        const NODE_IS_A_LEAF = 1 << 3;
+        /// All of the node's children are implemented identically.
+        const IDENTICAL_IMPLEMENTATION = 1 << 4;
+    }
+}
+
+/// A node in the processor topology. Leaf nodes represent processors, and other nodes represent
+/// groups of processors, such as cores, clusters, and packages.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct ProcessorHierarchyNode {
+    pub header: EntryHeader,
+    _reserved: u16,
+    pub flags: u32,
+    /// The offset of the node's parent from the start of the table, or `0` if it has no parent.
+    pub parent: u32,
+    /// For leaf nodes, the ACPI processor UID of the processor in the MADT. For other nodes,
+    /// the `_UID` of the matching processor container in the namespace.
+    pub acpi_processor_id: u32,
+    pub num_private_resources: u32,
+}
+
+impl ProcessorHierarchyNode {
+    pub fn flags(&self) -> ProcessorHierarchyFlags {
+        ProcessorHierarchyFlags::from_bits_truncate(self.flags)
+    }
+
+    /// The offset of the node's parent from the start of the table, if it has one.
+    pub fn parent(&self) -> Option<u32> {
+        if self.parent == 0 { None } else { Some(self.parent) }
+    }
+
+    /// The offsets, from the start of the table, of the resources (usually caches) that are
+    /// private to this node.
+    pub fn private_resources(&self) -> impl Iterator<Item = u32> + '_ {
+        let count = (self.num_private_resources as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<ProcessorHierarchyNode>())
+                / mem::size_of::<u32>(),
+        );
+        let base = unsafe {
+            (self as *const ProcessorHierarchyNode as *const u8).add(mem::size_of::<ProcessorHierarchyNode>())
+        };
+        (0..count).map(move |i| unsafe { (base as *const u32).add(i).read_unaligned() })
+    }
+}
+
+bitflags::bitflags! {
+    /// Which of the fields of a [`CacheTypeStructure`] are valid.
+    #[derive(Clone, Copy, Debug)]
+    pub struct CacheFlags: u32 {
+        const SIZE_VALID = 1;
+        const NUMBER_OF_SETS_VALID = 1 << 1;
+        const ASSOCIATIVITY_VALID = 1 << 2;
+        const ALLOCATION_TYPE_VALID = 1 << 3;
+        const CACHE_TYPE_VALID = 1 << 4;
+        const WRITE_POLICY_VALID = 1 << 5;
+        const LINE_SIZE_VALID = 1 << 6;
+        const CACHE_ID_VALID = 1 << 7;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheType {
+    Data,
+    Instruction,
+    Unified,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheAllocationType {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheWritePolicy {
+    WriteBack,
+    WriteThrough,
+}
+
+/// Describes a cache. Caches form chains, from the caches private to a processor out towards
+/// memory, through their `next_level_of_cache` fields.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct CacheTypeStructure {
+    pub header: EntryHeader,
+    _reserved: u16,
+    pub flags: u32,
+    /// The offset of the next level of cache from the start of the table, or `0` if this is the
+    /// last level.
+    pub next_level_of_cache: u32,
+    pub size: u32,
+    pub number_of_sets: u32,
+    pub associativity: u8,
+    pub attributes: u8,
+    pub line_size: u16,
+}
+
+impl CacheTypeStructure {
+    pub fn flags(&self) -> CacheFlags {
+        CacheFlags::from_bits_truncate(self.flags)
+    }
+
+    pub fn next_level_of_cache(&self) -> Option<u32> {
+        if self.next_level_of_cache == 0 { None } else { Some(self.next_level_of_cache) }
+    }
+
+    /// The size of the cache, in bytes.
+    pub fn size(&self) -> Option<u32> {
+        if self.flags().contains(CacheFlags::SIZE_VALID) { Some(self.size) } else { None }
+    }
+
+    pub fn number_of_sets(&self) -> Option<u32> {
+        if self.flags().contains(CacheFlags::NUMBER_OF_SETS_VALID) { Some(self.number_of_sets) } else { None }
+    }
+
+    /// The number of ways of the cache.
+    pub fn associativity(&self) -> Option<u8> {
+        if self.flags().contains(CacheFlags::ASSOCIATIVITY_VALID) { Some(self.associativity) } else { None }
+    }
+
+    pub fn allocation_type(&self) -> Option<CacheAllocationType> {
+        if !self.flags().contains(CacheFlags::ALLOCATION_TYPE_VALID) {
+            return None;
+        }
+        Some(match self.attributes.get_bits(0..2) {
+            0 => CacheAllocationType::Read,
+            1 => CacheAllocationType::Write,
+            _ => CacheAllocationType::ReadWrite,
+        })
+    }
+
+    pub fn cache_type(&self) -> Option<CacheType> {
+        if !self.flags().contains(CacheFlags::CACHE_TYPE_VALID) {
+            return None;
+        }
+        Some(match self.attributes.get_bits(2..4) {
+            0 => CacheType::Data,
+            1 => CacheType::Instruction,
+            _ => CacheType::Unified,
+        })
+    }
+
+    pub fn write_policy(&self) -> Option<CacheWritePolicy> {
+        if !self.flags().contains(CacheFlags::WRITE_POLICY_VALID) {
+            return None;
+        }
+        Some(if self.attributes.get_bit(4) { CacheWritePolicy::WriteThrough } else { CacheWritePolicy::WriteBack })
+    }
+
+    /// The size of a cache line, in bytes.
+    pub fn line_size(&self) -> Option<u16> {
+        if self.flags().contains(CacheFlags::LINE_SIZE_VALID) { Some(self.line_size) } else { None }
+    }
+
+    /// A unique ID for the cache. This field was added in ACPI 6.4, and so is only present in
+    /// newer tables.
+    pub fn cache_id(&self) -> Option<u32> {
+        if !self.flags().contains(CacheFlags::CACHE_ID_VALID)
+            || (self.header.length as usize) < mem::size_of::<CacheTypeStructure>() + mem::size_of::<u32>()
+        {
+            return None;
+        }
+        Some(unsafe {
+            ((self as *const CacheTypeStructure as *const u8).add(mem::size_of::<CacheTypeStructure>())
+                as *const u32)
+                .read_unaligned()
+        })
+    }
+}
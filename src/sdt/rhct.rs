@@ -0,0 +1,250 @@
+use crate::{
+    AcpiTable,
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{
+    marker::{PhantomData, PhantomPinned},
+    mem,
+    pin::Pin,
+    slice,
+    str,
+};
+use log::warn;
+
+/// Represents the RHCT (RISC-V Hart Capabilities Table), which describes the capabilities of the
+/// harts in a RISC-V system, such as their ISA strings, cache management operations, and MMU
+/// types. Each hart is described by a [`HartInfoNode`], which refers to the nodes describing its
+/// capabilities by their offset from the start of the table.
+///
+/// Like the MADT, this is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Rhct {
+    pub header: SdtHeader,
+    pub flags: u32,
+    /// The frequency of the system counter (the `time` CSR), in Hz.
+    pub time_base_frequency: u64,
+    pub num_nodes: u32,
+    /// The offset of the node array from the start of the table.
+    pub node_offset: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Rhct {
+    const SIGNATURE: Signature = Signature::RHCT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Rhct {
+    /// Whether the timer interrupt cannot wake the hart from suspend states.
+    pub fn timer_cannot_wake_cpu(&self) -> bool {
+        { self.flags }.get_bit(0)
+    }
+
+    pub fn nodes(self: Pin<&Self>) -> RhctNodeIter<'_> {
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Rhct as *const u8 };
+        let offset = self.node_offset.min(self.header.length);
+        RhctNodeIter {
+            pointer: unsafe { ptr.add(offset as usize) },
+            remaining_length: self.header.length - offset,
+            remaining_nodes: self.num_nodes,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get the node at the given offset from the start of the table. This is how hart info nodes
+    /// refer to the nodes describing a hart's capabilities.
+    pub fn node_at(self: Pin<&Self>, offset: u32) -> Option<RhctNode<'_>> {
+        let length = self.header.length;
+        if offset as usize + mem::size_of::<NodeHeader>() > length as usize {
+            return None;
+        }
+
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Rhct as *const u8 };
+        RhctNodeIter {
+            pointer: unsafe { ptr.add(offset as usize) },
+            remaining_length: length - offset,
+            remaining_nodes: 1,
+            _phantom: PhantomData,
+        }
+        .next()
+    }
+
+    /// Find the hart info node of the hart with the given ACPI processor UID.
+    pub fn hart_info(self: Pin<&Self>, acpi_processor_uid: u32) -> Option<&HartInfoNode> {
+        self.nodes().find_map(|node| match node {
+            RhctNode::HartInfo(info) if { info.acpi_processor_uid } == acpi_processor_uid => Some(info),
+            _ => None,
+        })
+    }
+
+    /// Iterate over the nodes describing the capabilities of the hart with the given ACPI
+    /// processor UID.
+    pub fn hart_capabilities(self: Pin<&Self>, acpi_processor_uid: u32) -> impl Iterator<Item = RhctNode<'_>> {
+        self.hart_info(acpi_processor_uid)
+            .into_iter()
+            .flat_map(HartInfoNode::offsets)
+            .filter_map(move |offset| self.node_at(offset))
+    }
+}
+
+#[derive(Debug)]
+pub struct RhctNodeIter<'a> {
+    pointer: *const u8,
+    remaining_length: u32,
+    remaining_nodes: u32,
+    _phantom: PhantomData<&'a ()>,
+}
+
+#[derive(Debug)]
+pub enum RhctNode<'a> {
+    IsaString(&'a IsaStringNode),
+    Cmo(&'a CmoNode),
+    Mmu(&'a MmuNode),
+    HartInfo(&'a HartInfoNode),
+}
+
+impl<'a> Iterator for RhctNodeIter<'a> {
+    type Item = RhctNode<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining_nodes > 0 && self.remaining_length as usize >= mem::size_of::<NodeHeader>() {
+            let entry_pointer = self.pointer;
+            let header = unsafe { *(self.pointer as *const NodeHeader) };
+
+            if header.length as u32 > self.remaining_length
+                || (header.length as usize) < mem::size_of::<NodeHeader>()
+            {
+                warn!("Invalid node of type {} in RHCT - extending past length of table. Ignoring", {
+                    header.typ
+                });
+                return None;
+            }
+
+            self.pointer = unsafe { self.pointer.byte_offset(header.length as isize) };
+            self.remaining_length = self.remaining_length.saturating_sub(header.length as u32);
+            self.remaining_nodes -= 1;
+
+            match header.typ {
+                0 => return Some(RhctNode::IsaString(unsafe { &*(entry_pointer as *const IsaStringNode) })),
+                1 => return Some(RhctNode::Cmo(unsafe { &*(entry_pointer as *const CmoNode) })),
+                2 => return Some(RhctNode::Mmu(unsafe { &*(entry_pointer as *const MmuNode) })),
+                0xffff => return Some(RhctNode::HartInfo(unsafe { &*(entry_pointer as *const HartInfoNode) })),
+                other => warn!("Unrecognised node in RHCT of type {}", other),
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct NodeHeader {
+    pub typ: u16,
+    pub length: u16,
+    pub revision: u16,
+}
+
+/// Describes the ISA of a hart, as a string such as `rv64imafdc_zicsr_zifencei`.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct IsaStringNode {
+    pub header: NodeHeader,
+    pub isa_length: u16,
+}
+
+impl IsaStringNode {
+    pub fn isa(&self) -> Option<&str> {
+        let length = (self.isa_length as usize)
+            .min((self.header.length as usize).saturating_sub(mem::size_of::<IsaStringNode>()));
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                (self as *const IsaStringNode as *const u8).add(mem::size_of::<IsaStringNode>()),
+                length,
+            )
+        };
+        let length = bytes.iter().position(|&b| b == 0).unwrap_or(length);
+        str::from_utf8(&bytes[0..length]).ok()
+    }
+}
+
+/// Describes the block sizes of the Cache Management Operation extensions supported by a hart.
+/// Each size is given as a power of two, or is `0` if the extension is not supported.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct CmoNode {
+    pub header: NodeHeader,
+    _reserved: u8,
+    /// The block size of `Zicbom` operations, as a power of two.
+    pub cbom_block_size: u8,
+    /// The block size of `Zicbop` operations, as a power of two.
+    pub cbop_block_size: u8,
+    /// The block size of `Zicboz` operations, as a power of two.
+    pub cboz_block_size: u8,
+}
+
+impl CmoNode {
+    pub fn zicbom_block_size(&self) -> Option<usize> {
+        if self.cbom_block_size == 0 { None } else { 1usize.checked_shl(self.cbom_block_size as u32) }
+    }
+
+    pub fn zicbop_block_size(&self) -> Option<usize> {
+        if self.cbop_block_size == 0 { None } else { 1usize.checked_shl(self.cbop_block_size as u32) }
+    }
+
+    pub fn zicboz_block_size(&self) -> Option<usize> {
+        if self.cboz_block_size == 0 { None } else { 1usize.checked_shl(self.cboz_block_size as u32) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MmuType {
+    Sv39,
+    Sv48,
+    Sv57,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct MmuNode {
+    pub header: NodeHeader,
+    _reserved: u8,
+    pub mmu_type: u8,
+}
+
+impl MmuNode {
+    pub fn mmu_type(&self) -> MmuType {
+        match self.mmu_type {
+            0 => MmuType::Sv39,
+            1 => MmuType::Sv48,
+            2 => MmuType::Sv57,
+            other => MmuType::Reserved(other),
+        }
+    }
+}
+
+/// Associates a hart with the nodes that describe its capabilities.
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct HartInfoNode {
+    pub header: NodeHeader,
+    pub num_offsets: u16,
+    pub acpi_processor_uid: u32,
+}
+
+impl HartInfoNode {
+    /// The offsets, from the start of the table, of the nodes describing this hart's capabilities.
+    pub fn offsets(&self) -> impl Iterator<Item = u32> + '_ {
+        let count = (self.num_offsets as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<HartInfoNode>()) / mem::size_of::<u32>(),
+        );
+        let base = unsafe { (self as *const HartInfoNode as *const u8).add(mem::size_of::<HartInfoNode>()) };
+        (0..count).map(move |i| unsafe { (base as *const u32).add(i).read_unaligned() })
+    }
+}
@@ -208,7 +208,7 @@ bitflags::bitflags! {
 }
 
 #[repr(u8)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum SpcrInterfaceType {
     /// Full 16550 interface
     Full16550,
@@ -0,0 +1,61 @@
+use crate::{
+    AcpiTable,
+    sdt::{
+        SdtHeader,
+        Signature,
+        tpm2::{TcgEventLogArea, TcgPlatformClass},
+    },
+};
+use core::{marker::PhantomPinned, mem, pin::Pin};
+
+/// Represents the TCPA table, which describes where the firmware has placed the TCG event log on
+/// systems with a TPM 1.2. The layout of the rest of the table depends on the platform class.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Tcpa {
+    pub header: SdtHeader,
+    pub platform_class: u16,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Tcpa {
+    const SIGNATURE: Signature = Signature::TCPA;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Tcpa {
+    pub fn platform_class(&self) -> TcgPlatformClass {
+        TcgPlatformClass::from(self.platform_class)
+    }
+
+    /// The location of the TCG event log. The client table has a 32-bit log length directly after
+    /// the platform class, while the server table has a reserved field and a 64-bit log length.
+    pub fn log_area(self: Pin<&Self>) -> Option<TcgEventLogArea> {
+        let (length_offset, length_size) = match self.platform_class() {
+            TcgPlatformClass::Client => (0, mem::size_of::<u32>()),
+            TcgPlatformClass::Server => (2, mem::size_of::<u64>()),
+            TcgPlatformClass::Reserved(_) => return None,
+        };
+        let address_offset = mem::size_of::<Tcpa>() + length_offset + length_size;
+        if (self.header.length as usize) < address_offset + mem::size_of::<u64>() {
+            return None;
+        }
+
+        let ptr = unsafe { Pin::into_inner_unchecked(self) as *const Tcpa as *const u8 };
+        let minimum_length = unsafe {
+            let ptr = ptr.add(mem::size_of::<Tcpa>() + length_offset);
+            if length_size == mem::size_of::<u32>() {
+                (ptr as *const u32).read_unaligned() as u64
+            } else {
+                (ptr as *const u64).read_unaligned()
+            }
+        };
+        let start_address = unsafe { (ptr.add(address_offset) as *const u64).read_unaligned() };
+        TcgEventLogArea::new(start_address, minimum_length)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::{
+    AcpiTable,
+    Handler,
+    PhysicalMapping,
+    sdt::{SdtHeader, Signature},
+};
+use core::{marker::PhantomPinned, mem, pin::Pin, slice};
+
+/// Represents the TPM2 table, which describes how to communicate with a TPM 2.0 device, and, from
+/// revision 4, where the firmware has placed the TCG event log.
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Tpm2 {
+    pub header: SdtHeader,
+    pub platform_class: u16,
+    _reserved: u16,
+    /// The physical address of the CRB control area, or of the FIFO register interface if the
+    /// start method is [`Tpm2StartMethod::MemoryMapped`].
+    pub control_area_address: u64,
+    pub start_method: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Tpm2 {
+    const SIGNATURE: Signature = Signature::TPM2;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Tpm2 {
+    /// The start method parameters are followed by the log area fields, which have a fixed
+    /// offset, and so the parameters take up at most 12 bytes.
+    const MAX_PARAMETERS_LENGTH: usize = 12;
+
+    pub fn platform_class(&self) -> TcgPlatformClass {
+        TcgPlatformClass::from(self.platform_class)
+    }
+
+    pub fn start_method(&self) -> Tpm2StartMethod {
+        Tpm2StartMethod::from(self.start_method)
+    }
+
+    /// The raw parameters specific to the start method.
+    pub fn start_method_parameters(self: Pin<&Self>) -> &[u8] {
+        let length =
+            (self.header.length as usize).saturating_sub(mem::size_of::<Tpm2>()).min(Self::MAX_PARAMETERS_LENGTH);
+        unsafe {
+            slice::from_raw_parts(
+                (Pin::into_inner_unchecked(self) as *const Tpm2 as *const u8).add(mem::size_of::<Tpm2>()),
+                length,
+            )
+        }
+    }
+
+    /// The parameters specific to the start method, decoded for the start methods that define
+    /// them.
+    pub fn platform_parameters(self: Pin<&Self>) -> Tpm2PlatformParameters<'_> {
+        let parameters = self.start_method_parameters();
+        match self.start_method() {
+            Tpm2StartMethod::MemoryMapped | Tpm2StartMethod::CommandResponseBuffer => Tpm2PlatformParameters::None,
+            Tpm2StartMethod::CommandResponseBufferWithArmSmc
+                if parameters.len() >= mem::size_of::<Tpm2ArmSmcParameters>() =>
+            {
+                Tpm2PlatformParameters::ArmSmc(unsafe {
+                    (parameters.as_ptr() as *const Tpm2ArmSmcParameters).read_unaligned()
+                })
+            }
+            _ => Tpm2PlatformParameters::Other(parameters),
+        }
+    }
+
+    /// The location of the TCG event log, if the table describes it.
+    pub fn log_area(self: Pin<&Self>) -> Option<TcgEventLogArea> {
+        let offset = mem::size_of::<Tpm2>() + Self::MAX_PARAMETERS_LENGTH;
+        if (self.header.length as usize) < offset + mem::size_of::<u32>() + mem::size_of::<u64>() {
+            return None;
+        }
+
+        let ptr = unsafe { (Pin::into_inner_unchecked(self) as *const Tpm2 as *const u8).add(offset) };
+        let minimum_length = unsafe { (ptr as *const u32).read_unaligned() };
+        let start_address = unsafe { (ptr.add(mem::size_of::<u32>()) as *const u64).read_unaligned() };
+        TcgEventLogArea::new(start_address, minimum_length as u64)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TcgPlatformClass {
+    Client,
+    Server,
+    Reserved(u16),
+}
+
+impl From<u16> for TcgPlatformClass {
+    fn from(class: u16) -> Self {
+        match class {
+            0 => TcgPlatformClass::Client,
+            1 => TcgPlatformClass::Server,
+            other => TcgPlatformClass::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tpm2StartMethod {
+    /// Commands are started by evaluating the TPM's ACPI `_DSM` method.
+    Acpi,
+    /// The TPM uses the FIFO (TIS) interface.
+    MemoryMapped,
+    /// The TPM uses the Command Response Buffer interface.
+    CommandResponseBuffer,
+    /// The TPM uses the Command Response Buffer interface, but commands are started by
+    /// evaluating the TPM's ACPI `_DSM` method.
+    CommandResponseBufferWithAcpi,
+    /// The TPM uses the Command Response Buffer interface, but commands are started by making an
+    /// Arm SMC call.
+    CommandResponseBufferWithArmSmc,
+    Reserved(u32),
+}
+
+impl From<u32> for Tpm2StartMethod {
+    fn from(method: u32) -> Self {
+        match method {
+            2 => Tpm2StartMethod::Acpi,
+            6 => Tpm2StartMethod::MemoryMapped,
+            7 => Tpm2StartMethod::CommandResponseBuffer,
+            8 => Tpm2StartMethod::CommandResponseBufferWithAcpi,
+            11 => Tpm2StartMethod::CommandResponseBufferWithArmSmc,
+            other => Tpm2StartMethod::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Tpm2PlatformParameters<'a> {
+    /// The start method does not use any parameters.
+    None,
+    ArmSmc(Tpm2ArmSmcParameters),
+    /// The raw parameters of a start method that this library does not decode.
+    Other(&'a [u8]),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct Tpm2ArmSmcParameters {
+    /// The GSI used to signal command completion, if `interrupt_flags` has bit `0` set.
+    pub interrupt: u32,
+    pub interrupt_flags: u8,
+    pub operation_flags: u8,
+    _reserved: u16,
+    /// The SMC function ID used to start a command.
+    pub smc_function_id: u32,
+}
+
+/// The location of the TCG event log, into which the firmware records the measurements it has
+/// made during boot. This is described by both the TPM2 and the TCPA tables.
+#[derive(Clone, Copy, Debug)]
+pub struct TcgEventLogArea {
+    pub start_address: u64,
+    /// The minimum length of the log area. The firmware may not have filled all of it.
+    pub minimum_length: u64,
+}
+
+impl TcgEventLogArea {
+    pub(crate) fn new(start_address: u64, minimum_length: u64) -> Option<TcgEventLogArea> {
+        if start_address == 0 || minimum_length == 0 {
+            return None;
+        }
+        Some(TcgEventLogArea { start_address, minimum_length })
+    }
+
+    /// Map the log area, so that the event log can be read.
+    ///
+    /// ### Safety
+    /// The log area must be memory that is valid to read. This is the case for areas reported by
+    /// the firmware, but the fields of a `TcgEventLogArea` can be changed freely.
+    pub unsafe fn map<H: Handler>(&self, handler: &H) -> TcgEventLog<H> {
+        let mapping =
+            unsafe { handler.map_physical_region(self.start_address as usize, self.minimum_length as usize) };
+        TcgEventLog { mapping }
+    }
+}
+
+/// A mapped TCG event log area.
+pub struct TcgEventLog<H: Handler> {
+    mapping: PhysicalMapping<H, u8>,
+}
+
+impl<H> TcgEventLog<H>
+where
+    H: Handler,
+{
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.mapping.virtual_start.as_ptr(), self.mapping.region_length) }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    AcpiError,
+    AcpiTable,
+    Handler,
+    address::{GenericAddress, MappedGas, RawGenericAddress},
+    sdt::{SdtHeader, Signature},
+};
+use bit_field::BitField;
+use core::{marker::PhantomPinned, mem, pin::Pin, slice};
+use pci_types::PciAddress;
+
+/// Represents the WDAT (Watchdog Action Table), which describes a hardware watchdog timer and how
+/// to control it. Each operation on the watchdog is performed by executing the instructions
+/// associated with a [`WatchdogAction`].
+///
+/// This is a variable-sized structure, and so this type is `!Unpin`.
+#[repr(C, packed)]
+#[derive(Debug)]
+pub struct Wdat {
+    pub header: SdtHeader,
+    pub watchdog_header_length: u32,
+    /// The PCI segment of the watchdog. The segment, bus, device, and function are all set to
+    /// `0xff` (`0xffff` for the segment) if the watchdog is not a PCI device.
+    pub pci_segment: u16,
+    pub pci_bus: u8,
+    pub pci_device: u8,
+    pub pci_function: u8,
+    _reserved0: [u8; 3],
+    /// The length of one count of the watchdog's countdown, in milliseconds.
+    pub timer_period: u32,
+    pub max_count: u32,
+    pub min_count: u32,
+    pub watchdog_flags: u8,
+    _reserved1: [u8; 3],
+    pub instruction_entry_count: u32,
+    _pinned: PhantomPinned,
+}
+
+unsafe impl AcpiTable for Wdat {
+    const SIGNATURE: Signature = Signature::WDAT;
+
+    fn header(&self) -> &SdtHeader {
+        &self.header
+    }
+}
+
+impl Wdat {
+    /// The PCI device that implements the watchdog, if it is a PCI device.
+    pub fn pci_address(&self) -> Option<PciAddress> {
+        if self.pci_segment == 0xffff || self.pci_device > 31 || self.pci_function > 7 {
+            return None;
+        }
+        Some(PciAddress::new(self.pci_segment, self.pci_bus, self.pci_device, self.pci_function))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.watchdog_flags.get_bit(0)
+    }
+
+    /// Whether the watchdog is stopped while the system is in a sleeping state.
+    pub fn stopped_in_sleep_state(&self) -> bool {
+        self.watchdog_flags.get_bit(7)
+    }
+
+    pub fn entries(self: Pin<&Self>) -> &[WatchdogInstructionEntry] {
+        let count = (self.instruction_entry_count as usize).min(
+            (self.header.length as usize).saturating_sub(mem::size_of::<Wdat>())
+                / mem::size_of::<WatchdogInstructionEntry>(),
+        );
+        unsafe {
+            slice::from_raw_parts(
+                (Pin::into_inner_unchecked(self) as *const Wdat as *const u8).add(mem::size_of::<Wdat>())
+                    as *const WatchdogInstructionEntry,
+                count,
+            )
+        }
+    }
+
+    /// Get the instructions that make up the given action, in the order they should be executed.
+    pub fn instructions(
+        self: Pin<&Self>,
+        action: WatchdogAction,
+    ) -> impl Iterator<Item = &WatchdogInstructionEntry> {
+        self.entries().iter().filter(move |entry| WatchdogAction::from(entry.action) == action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WatchdogAction {
+    Reset,
+    QueryCurrentCountdownPeriod,
+    QueryCountdownPeriod,
+    SetCountdownPeriod,
+    QueryRunningState,
+    SetRunningState,
+    QueryStoppedState,
+    SetStoppedState,
+    QueryReboot,
+    SetReboot,
+    QueryShutdown,
+    SetShutdown,
+    QueryWatchdogStatus,
+    SetWatchdogStatus,
+    Reserved(u8),
+}
+
+impl From<u8> for WatchdogAction {
+    fn from(action: u8) -> Self {
+        match action {
+            0x01 => WatchdogAction::Reset,
+            0x04 => WatchdogAction::QueryCurrentCountdownPeriod,
+            0x05 => WatchdogAction::QueryCountdownPeriod,
+            0x06 => WatchdogAction::SetCountdownPeriod,
+            0x08 => WatchdogAction::QueryRunningState,
+            0x09 => WatchdogAction::SetRunningState,
+            0x0a => WatchdogAction::QueryStoppedState,
+            0x0b => WatchdogAction::SetStoppedState,
+            0x10 => WatchdogAction::QueryReboot,
+            0x11 => WatchdogAction::SetReboot,
+            0x12 => WatchdogAction::QueryShutdown,
+            0x13 => WatchdogAction::SetShutdown,
+            0x20 => WatchdogAction::QueryWatchdogStatus,
+            0x21 => WatchdogAction::SetWatchdogStatus,
+            other => WatchdogAction::Reserved(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WatchdogInstruction {
+    /// Read the register, and compare the masked result against the entry's value.
+    ReadValue,
+    /// Read the register, giving the masked result.
+    ReadCountdown,
+    /// Write the entry's value to the register.
+    WriteValue,
+    /// Write the action's parameter to the register.
+    WriteCountdown,
+    Reserved(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, packed)]
+pub struct WatchdogInstructionEntry {
+    pub action: u8,
+    /// The instruction in bits `0..7`, and the `PRESERVE_REGISTER` flag in bit `7`.
+    pub instruction_flags: u8,
+    _reserved: u16,
+    pub register_region: RawGenericAddress,
+    pub value: u32,
+    pub mask: u32,
+}
+
+impl WatchdogInstructionEntry {
+    pub fn instruction(&self) -> WatchdogInstruction {
+        match self.instruction_flags.get_bits(0..7) {
+            0 => WatchdogInstruction::ReadValue,
+            1 => WatchdogInstruction::ReadCountdown,
+            2 => WatchdogInstruction::WriteValue,
+            3 => WatchdogInstruction::WriteCountdown,
+            other => WatchdogInstruction::Reserved(other),
+        }
+    }
+
+    /// Whether bits of the register outside of the instruction's mask must be preserved when
+    /// writing to it.
+    pub fn preserve_register(&self) -> bool {
+        self.instruction_flags.get_bit(7)
+    }
+
+    pub fn register(&self) -> Result<GenericAddress, AcpiError> {
+        GenericAddress::from_raw(self.register_region)
+    }
+
+    /// Map the register this instruction operates on.
+    ///
+    /// ### Safety
+    /// The register must be valid to read from and write to.
+    pub unsafe fn map_register<H: Handler>(&self, handler: &H) -> Result<MappedGas<H>, AcpiError> {
+        unsafe { MappedGas::map_gas(self.register()?, handler) }
+    }
+
+    /// Read the instruction's register, masked by the instruction's mask.
+    pub fn read_register<H: Handler>(&self, register: &MappedGas<H>) -> Result<u64, AcpiError> {
+        Ok(register.read()? & self.mask as u64)
+    }
+
+    /// Write `value`, masked by the instruction's mask, to the instruction's register, preserving
+    /// the other bits of the register if required.
+    pub fn write_register<H: Handler>(&self, register: &MappedGas<H>, value: u64) -> Result<(), AcpiError> {
+        let mask = self.mask as u64;
+        let value = value & mask;
+        if self.preserve_register() {
+            let current = register.read()?;
+            register.write((current & !mask) | value)
+        } else {
+            register.write(value)
+        }
+    }
+}
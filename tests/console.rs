@@ -0,0 +1,128 @@
+use acpi::{
+    AcpiError,
+    address::AddressSpace,
+    console::{ConsoleDescriptor, ConsoleInterrupt, Parity, StopBits, UartKind},
+    sdt::spcr::{Spcr, SpcrFlowControl, SpcrInterruptType},
+};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+use core::fmt::Write;
+
+/// A 16550's Line Status Register, with the Transmitter Holding Register Empty bit set.
+const LSR_THRE: u32 = 1 << 5;
+
+/// Build an SPCR of the given revision, with the given interface type, register block and
+/// interrupts.
+fn spcr_table(
+    revision: u8,
+    interface_type: u8,
+    base_address: &[u8],
+    interrupt_type: SpcrInterruptType,
+    irq: u8,
+    global_system_interrupt: u32,
+) -> Vec<u8> {
+    let mut body = vec![interface_type, 0, 0, 0];
+    body.extend_from_slice(base_address);
+    body.extend_from_slice(&[interrupt_type.bits(), irq]);
+    body.extend_from_slice(&global_system_interrupt.to_le_bytes());
+    // 115200 baud, no parity, one stop bit, RTS/CTS flow control and a VT-UTF8 terminal
+    body.extend_from_slice(&[7, 0, 1, SpcrFlowControl::RTS_CTS.bits(), 2, 0]);
+    body.extend_from_slice(&0xffffu16.to_le_bytes());
+    body.extend_from_slice(&0xffffu16.to_le_bytes());
+    body.extend_from_slice(&[0, 0, 0]);
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.push(0);
+    body.extend_from_slice(&24_000_000u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&2u16.to_le_bytes());
+    body.extend_from_slice(&88u16.to_le_bytes());
+    body.extend_from_slice(b".\0");
+    sdt(b"SPCR", revision, &body)
+}
+
+#[test]
+fn test_console_from_spcr() {
+    let fixture = build_tables(&[spcr_table(2, 0x00, &gas(1, 8, 1, 0x3f8), SpcrInterruptType::DUAL_8259, 4, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+
+    let console = ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap();
+    assert_eq!(console.kind, UartKind::Uart16550);
+    assert_eq!(console.address.address_space, AddressSpace::SystemIo);
+    assert_eq!(console.address.address, 0x3f8);
+    assert_eq!(console.baud_rate.unwrap().get(), 115200);
+    assert_eq!((console.parity, console.stop_bits), (Parity::None, StopBits::One));
+    assert_eq!(console.flow_control.bits(), SpcrFlowControl::RTS_CTS.bits());
+    assert_eq!(console.interrupt, Some(ConsoleInterrupt::Irq(4)));
+    // The clock frequency is only reported from revision 3
+    assert_eq!(console.clock_frequency, None);
+
+    // A GSI is preferred over an IRQ
+    let interrupt_type = SpcrInterruptType::DUAL_8259 | SpcrInterruptType::ARMH_GIC;
+    let fixture = build_tables(&[spcr_table(4, 0x03, &gas(0, 32, 3, 0x0900_0000), interrupt_type, 4, 33)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let console = ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap();
+    assert_eq!(console.kind, UartKind::Pl011);
+    assert_eq!(console.interrupt, Some(ConsoleInterrupt::Gsi(33)));
+    assert_eq!(console.clock_frequency.unwrap().get(), 24_000_000);
+
+    // Console redirection is disabled
+    let fixture = build_tables(&[spcr_table(2, 0x00, &[0; 12], SpcrInterruptType::empty(), 0, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    assert!(ConsoleDescriptor::from_spcr(&spcr).unwrap().is_none());
+}
+
+#[test]
+fn test_16550_io_console() {
+    let fixture = build_tables(&[spcr_table(2, 0x00, &gas(1, 8, 1, 0x3f8), SpcrInterruptType::DUAL_8259, 4, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let descriptor = ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap();
+    let mut console = unsafe { descriptor.map(&fixture.handler) }.unwrap();
+
+    fixture.handler.set_io_port(0x3fd, LSR_THRE);
+    fixture.handler.take_io_writes();
+    write!(console, "a\nb").unwrap();
+    assert_eq!(
+        fixture.handler.take_io_writes(),
+        [(0x3f8, b'a' as u32), (0x3f8, b'\r' as u32), (0x3f8, b'\n' as u32), (0x3f8, b'b' as u32)]
+    );
+}
+
+#[test]
+fn test_mmio_consoles() {
+    // A 16550 with its registers spaced out to 32 bits
+    let mut registers = Box::new([0u32; 0x12]);
+    registers[5] = LSR_THRE;
+    let base = registers.as_mut_ptr();
+    let fixture =
+        build_tables(&[spcr_table(2, 0x12, &gas(0, 32, 3, base as u64), SpcrInterruptType::empty(), 0, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let console = unsafe { ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap().map(&fixture.handler) }.unwrap();
+    console.write_bytes(b"ok");
+    assert_eq!(unsafe { base.read_volatile() }, b'k' as u32);
+
+    // A PL011, with space in its transmit FIFO
+    let mut registers = Box::new([0u32; 0x12]);
+    let base = registers.as_mut_ptr();
+    let fixture =
+        build_tables(&[spcr_table(2, 0x03, &gas(0, 32, 3, base as u64), SpcrInterruptType::empty(), 0, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let console = unsafe { ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap().map(&fixture.handler) }.unwrap();
+    console.write_byte(b'!');
+    assert_eq!(unsafe { base.read_volatile() }, b'!' as u32);
+    assert_eq!(unsafe { base.add(0x18 / 4).read_volatile() }, 0);
+}
+
+#[test]
+fn test_unsupported_consoles() {
+    // The Arm DCC isn't accessed through a register block
+    let fixture = build_tables(&[spcr_table(2, 0x0f, &gas(0, 32, 3, 0x1000), SpcrInterruptType::empty(), 0, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let console = ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap();
+    assert_eq!(console.kind, UartKind::ArmDcc);
+    assert!(matches!(unsafe { console.map(&fixture.handler) }, Err(AcpiError::LibUnimplemented)));
+
+    // A PL011 can't be accessed through I/O ports
+    let fixture = build_tables(&[spcr_table(2, 0x03, &gas(1, 8, 1, 0x3f8), SpcrInterruptType::empty(), 0, 0)]);
+    let spcr = fixture.tables.find_table::<Spcr>().unwrap();
+    let console = ConsoleDescriptor::from_spcr(&spcr).unwrap().unwrap();
+    assert!(matches!(unsafe { console.map(&fixture.handler) }, Err(AcpiError::LibUnimplemented)));
+}
@@ -0,0 +1,104 @@
+use acpi::{
+    address::AddressSpace,
+    console::{ConsoleDescriptor, UartKind},
+    sdt::{
+        dbg2::{Dbg2, Dbg2PortType, Dbg2UsbSubtype},
+        spcr::SpcrInterfaceType,
+    },
+};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+
+/// A device information structure, laid out as the registers, then their sizes, then the
+/// namespace string. `num_registers` can be larger than `registers.len()` to describe registers
+/// that extend past the end of the structure.
+fn device(
+    port_type: u16,
+    port_subtype: u16,
+    num_registers: u8,
+    registers: &[(Vec<u8>, u32)],
+    namespace: &str,
+) -> Vec<u8> {
+    const HEADER_LENGTH: usize = 22;
+
+    let base_address_register_offset = HEADER_LENGTH;
+    let address_size_offset = base_address_register_offset + registers.len() * 12;
+    let namespace_string_offset = address_size_offset + registers.len() * 4;
+    let length = namespace_string_offset + namespace.len() + 1;
+
+    let mut device = vec![0];
+    device.extend_from_slice(&(length as u16).to_le_bytes());
+    device.push(num_registers);
+    device.extend_from_slice(&(namespace.len() as u16 + 1).to_le_bytes());
+    device.extend_from_slice(&(namespace_string_offset as u16).to_le_bytes());
+    device.extend_from_slice(&0u16.to_le_bytes());
+    device.extend_from_slice(&0u16.to_le_bytes());
+    device.extend_from_slice(&port_type.to_le_bytes());
+    device.extend_from_slice(&port_subtype.to_le_bytes());
+    device.extend_from_slice(&0u16.to_le_bytes());
+    device.extend_from_slice(&(base_address_register_offset as u16).to_le_bytes());
+    device.extend_from_slice(&(address_size_offset as u16).to_le_bytes());
+    for (register, _) in registers {
+        device.extend_from_slice(register);
+    }
+    for (_, size) in registers {
+        device.extend_from_slice(&size.to_le_bytes());
+    }
+    device.extend_from_slice(namespace.as_bytes());
+    device.push(0);
+    device
+}
+
+fn dbg2(devices: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&44u32.to_le_bytes());
+    body.extend_from_slice(&(devices.len() as u32).to_le_bytes());
+    body.extend_from_slice(&devices.concat());
+    sdt(b"DBG2", 0, &body)
+}
+
+#[test]
+fn test_devices() {
+    let fixture = build_tables(&[dbg2(&[
+        device(0x8000, 0x0000, 1, &[(gas(1, 8, 1, 0x3f8), 8)], "\\_SB.COM1"),
+        device(0x8002, 0x0000, 1, &[(gas(0, 64, 4, 0xfe00_0000), 0x1000)], "\\_SB.PCI0.XHC"),
+        // The registers extend past the end of the structure
+        device(0x8003, 0x8086, 2, &[(gas(0, 32, 3, 0xfe10_0000), 0x100)], "."),
+    ])]);
+    let dbg2 = fixture.tables.find_table::<Dbg2>().unwrap();
+    let devices: Vec<_> = dbg2.get().devices().collect();
+    assert_eq!(devices.len(), 3);
+
+    assert_eq!(devices[0].port_type(), Dbg2PortType::Serial(SpcrInterfaceType::Full16550));
+    assert_eq!(devices[0].base_address_registers().len(), 1);
+    assert_eq!({ devices[0].base_address_registers()[0].address }, 0x3f8);
+    assert_eq!(devices[0].address_sizes().collect::<Vec<_>>(), [8]);
+    assert_eq!(devices[0].namespace_string(), Ok("\\_SB.COM1"));
+    assert!(devices[0].oem_data().is_empty());
+
+    assert_eq!(devices[1].port_type(), Dbg2PortType::Usb(Dbg2UsbSubtype::Xhci));
+    assert_eq!(devices[1].address_sizes().collect::<Vec<_>>(), [0x1000]);
+    assert_eq!(devices[1].namespace_string(), Ok("\\_SB.PCI0.XHC"));
+
+    assert_eq!(devices[2].port_type(), Dbg2PortType::Net { vendor_id: 0x8086 });
+    assert!(devices[2].base_address_registers().is_empty());
+    assert_eq!(devices[2].address_sizes().count(), 0);
+    assert_eq!(devices[2].namespace_string(), Ok("."));
+}
+
+#[test]
+fn test_console_from_dbg2_device() {
+    let fixture = build_tables(&[dbg2(&[
+        device(0x8000, 0x0000, 1, &[(gas(1, 8, 1, 0x3f8), 8)], "\\_SB.COM1"),
+        device(0x8002, 0x0000, 1, &[(gas(0, 64, 4, 0xfe00_0000), 0x1000)], "\\_SB.PCI0.XHC"),
+    ])]);
+    let dbg2 = fixture.tables.find_table::<Dbg2>().unwrap();
+    let mut devices = dbg2.get().devices();
+
+    let console = ConsoleDescriptor::from_dbg2_device(devices.next().unwrap()).unwrap().unwrap();
+    assert_eq!(console.kind, UartKind::Uart16550);
+    assert_eq!(console.address.address_space, AddressSpace::SystemIo);
+    assert_eq!(console.address.address, 0x3f8);
+    assert_eq!(console.baud_rate, None);
+
+    assert!(ConsoleDescriptor::from_dbg2_device(devices.next().unwrap()).unwrap().is_none());
+}
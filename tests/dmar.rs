@@ -0,0 +1,65 @@
+use acpi::{platform::iommu::DmarInfo, sdt::dmar::DeviceScopeType};
+use aml_test_tools::tables::{build_tables, sdt};
+use pci_types::PciAddress;
+
+fn device_scope(typ: u8, enumeration_id: u8, start_bus: u8, path: &[(u8, u8)]) -> Vec<u8> {
+    let mut scope = vec![typ, 6 + 2 * path.len() as u8, 0, 0, enumeration_id, start_bus];
+    for &(device, function) in path {
+        scope.extend_from_slice(&[device, function]);
+    }
+    scope
+}
+
+fn drhd(flags: u8, size: u8, segment: u16, register_base_address: u64, scopes: &[Vec<u8>]) -> Vec<u8> {
+    let scopes = scopes.concat();
+    let mut drhd = Vec::new();
+    drhd.extend_from_slice(&0u16.to_le_bytes());
+    drhd.extend_from_slice(&(16 + scopes.len() as u16).to_le_bytes());
+    drhd.extend_from_slice(&[flags, size]);
+    drhd.extend_from_slice(&segment.to_le_bytes());
+    drhd.extend_from_slice(&register_base_address.to_le_bytes());
+    drhd.extend_from_slice(&scopes);
+    drhd
+}
+
+fn dmar(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![38, 0b001];
+    body.extend_from_slice(&[0; 10]);
+    body.extend_from_slice(&entries.concat());
+    sdt(b"DMAR", 1, &body)
+}
+
+#[test]
+fn test_device_scope_resolution() {
+    let fixture = build_tables(&[dmar(&[drhd(
+        0,
+        1,
+        0,
+        0xfed9_0000,
+        &[
+            // An endpoint behind the bridge at 00:1c.0
+            device_scope(1, 0, 0, &[(0x1c, 0), (0, 1)]),
+            // An invalid device number, which should be skipped
+            device_scope(1, 0, 0, &[(0x20, 0)]),
+            // An empty path, which should be skipped
+            device_scope(1, 0, 0, &[]),
+            device_scope(3, 8, 0xf0, &[(0x1f, 0)]),
+        ],
+    )])]);
+    fixture.handler.set_pci_u8(PciAddress::new(0, 0, 0x1c, 0), 0x19, 3);
+
+    let info = DmarInfo::new(&fixture.tables).unwrap();
+    assert_eq!(info.address_width, 39);
+    assert!(info.supports_interrupt_remapping);
+    assert_eq!(info.remapping_units.len(), 1);
+
+    let unit = &info.remapping_units[0];
+    assert_eq!(unit.register_base_address, 0xfed9_0000);
+    assert_eq!(unit.register_size, 0x2000);
+    assert_eq!(unit.devices.len(), 2);
+    assert_eq!(unit.devices[0].typ, DeviceScopeType::PciEndpoint);
+    assert_eq!(unit.devices[0].address, PciAddress::new(0, 3, 0, 1));
+    assert_eq!(unit.devices[1].typ, DeviceScopeType::IoApic);
+    assert_eq!(unit.devices[1].address, PciAddress::new(0, 0xf0, 0x1f, 0));
+    assert_eq!(unit.devices[1].enumeration_id, 8);
+}
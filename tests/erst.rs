@@ -0,0 +1,187 @@
+use acpi::{AcpiError, platform::erst::ErrorRecordStore, sdt::erst::CommandStatus};
+use aml_test_tools::tables::{TestTables, build_tables, gas, sdt};
+
+const OPERATION_PORT: u16 = 0x10;
+const RECORD_OFFSET_PORT: u16 = 0x14;
+const EXECUTE_PORT: u16 = 0x18;
+const BUSY_PORT: u16 = 0x1c;
+const STATUS_PORT: u16 = 0x20;
+const RECORD_ID_PORT: u16 = 0x28;
+const RECORD_COUNT_PORT: u16 = 0x30;
+const ERROR_LOG_ADDRESS_PORT: u16 = 0x40;
+const ERROR_LOG_LENGTH_PORT: u16 = 0x48;
+const ERROR_LOG_ATTRIBUTES_PORT: u16 = 0x50;
+const SRC_BASE_PORT: u16 = 0x60;
+const MOVE_OFFSET_PORT: u16 = 0x68;
+
+const BEGIN_WRITE: u32 = 1;
+const BEGIN_READ: u32 = 2;
+const BEGIN_CLEAR: u32 = 3;
+const END: u32 = 4;
+
+fn instruction(action: u8, instruction: u8, port: u16, bit_width: u8, value: u64, mask: u64) -> Vec<u8> {
+    let mut entry = vec![action, instruction, 0, 0];
+    entry.extend_from_slice(&gas(1, bit_width, 3, port as u64));
+    entry.extend_from_slice(&value.to_le_bytes());
+    entry.extend_from_slice(&mask.to_le_bytes());
+    entry
+}
+
+const READ_REGISTER: u8 = 0x00;
+const READ_REGISTER_VALUE: u8 = 0x01;
+const WRITE_REGISTER: u8 = 0x02;
+const WRITE_REGISTER_VALUE: u8 = 0x03;
+const GOTO: u8 = 0x0f;
+const SET_SRC_ADDRESS_BASE: u8 = 0x10;
+const MOVE_DATA: u8 = 0x12;
+
+/// An ERST with instructions for the record operations, followed by `extra`.
+fn erst(extra: &[Vec<u8>]) -> Vec<u8> {
+    let mut instructions = vec![
+        instruction(0x00, WRITE_REGISTER_VALUE, OPERATION_PORT, 32, BEGIN_WRITE as u64, 0xff),
+        instruction(0x01, WRITE_REGISTER_VALUE, OPERATION_PORT, 32, BEGIN_READ as u64, 0xff),
+        instruction(0x02, WRITE_REGISTER_VALUE, OPERATION_PORT, 32, BEGIN_CLEAR as u64, 0xff),
+        instruction(0x03, WRITE_REGISTER_VALUE, OPERATION_PORT, 32, END as u64, 0xff),
+        instruction(0x04, WRITE_REGISTER, RECORD_OFFSET_PORT, 32, 0, 0xffff_ffff),
+        instruction(0x05, WRITE_REGISTER_VALUE, EXECUTE_PORT, 32, 1, 0xff),
+        instruction(0x06, READ_REGISTER_VALUE, BUSY_PORT, 32, 1, 1),
+        instruction(0x07, READ_REGISTER, STATUS_PORT, 32, 0, 0xff),
+        instruction(0x09, WRITE_REGISTER, RECORD_ID_PORT, 64, 0, u64::MAX),
+        instruction(0x0a, READ_REGISTER, RECORD_COUNT_PORT, 32, 0, 0xffff_ffff),
+        instruction(0x0d, READ_REGISTER, ERROR_LOG_ADDRESS_PORT, 64, 0, u64::MAX),
+        instruction(0x0e, READ_REGISTER, ERROR_LOG_LENGTH_PORT, 32, 0, 0xffff_ffff),
+        instruction(0x0f, READ_REGISTER, ERROR_LOG_ATTRIBUTES_PORT, 32, 0, 0xff),
+    ];
+    instructions.extend_from_slice(extra);
+    let mut body = Vec::new();
+    body.extend_from_slice(&48u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&(instructions.len() as u32).to_le_bytes());
+    body.extend_from_slice(&instructions.concat());
+    sdt(b"ERST", 1, &body)
+}
+
+/// Build an ERST whose error log is `error_log`.
+fn fixture(error_log: &mut [u8]) -> TestTables {
+    let fixture = build_tables(&[erst(&[])]);
+    let address = error_log.as_mut_ptr() as u64;
+    fixture.handler.set_io_port(ERROR_LOG_ADDRESS_PORT, address as u32);
+    fixture.handler.set_io_port(ERROR_LOG_ADDRESS_PORT + 4, (address >> 32) as u32);
+    fixture.handler.set_io_port(ERROR_LOG_LENGTH_PORT, error_log.len() as u32);
+    fixture
+}
+
+/// A fake CPER record of the given length, with the length in its header.
+fn record(length: usize) -> Vec<u8> {
+    let mut record: Vec<u8> = (0..length).map(|i| i as u8).collect();
+    record[20..24].copy_from_slice(&(length as u32).to_le_bytes());
+    record
+}
+
+#[test]
+fn test_record_operations() {
+    let mut error_log = vec![0u8; 256];
+    let fixture = fixture(&mut error_log);
+    fixture.handler.set_io_port(RECORD_COUNT_PORT, 2);
+
+    let mut store = ErrorRecordStore::new(&fixture.tables).unwrap();
+    assert_eq!(store.record_count().unwrap(), 2);
+
+    let written = record(128);
+    store.write_record(&written).unwrap();
+    assert_eq!(
+        fixture.handler.take_io_writes(),
+        [(OPERATION_PORT, BEGIN_WRITE), (RECORD_OFFSET_PORT, 0), (EXECUTE_PORT, 1), (OPERATION_PORT, END)]
+    );
+
+    let mut buffer = [0u8; 64];
+    assert_eq!(store.read_record(0x1234_5678_9abc, &mut buffer).unwrap(), 128);
+    assert_eq!(buffer, written[0..64]);
+    assert_eq!(
+        fixture.handler.take_io_writes(),
+        [
+            (OPERATION_PORT, BEGIN_READ),
+            (RECORD_OFFSET_PORT, 0),
+            (RECORD_ID_PORT, 0x5678_9abc),
+            (RECORD_ID_PORT + 4, 0x1234),
+            (EXECUTE_PORT, 1),
+            (OPERATION_PORT, END)
+        ]
+    );
+
+    assert!(matches!(
+        store.write_record(&record(512)),
+        Err(AcpiError::ErstCommandFailed(CommandStatus::NotEnoughSpace))
+    ));
+    assert!(fixture.handler.take_io_writes().is_empty());
+}
+
+#[test]
+fn test_end_operation_on_failure() {
+    let mut error_log = vec![0u8; 256];
+    let fixture = fixture(&mut error_log);
+    let mut store = ErrorRecordStore::new(&fixture.tables).unwrap();
+
+    fixture.handler.set_io_port(STATUS_PORT, 0x5);
+    assert!(matches!(store.clear_record(5), Err(AcpiError::ErstCommandFailed(CommandStatus::RecordNotFound))));
+    assert_eq!(
+        fixture.handler.take_io_writes(),
+        [
+            (OPERATION_PORT, BEGIN_CLEAR),
+            (RECORD_ID_PORT, 5),
+            (RECORD_ID_PORT + 4, 0),
+            (EXECUTE_PORT, 1),
+            (OPERATION_PORT, END)
+        ]
+    );
+}
+
+#[test]
+fn test_end_operation_on_timeout() {
+    let mut error_log = vec![0u8; 256];
+    let fixture = fixture(&mut error_log);
+    let mut store = ErrorRecordStore::new(&fixture.tables).unwrap();
+
+    // The firmware never finishes the operation
+    fixture.handler.set_io_port(BUSY_PORT, 1);
+    assert!(matches!(store.clear_record(5), Err(AcpiError::Timeout)));
+    assert!(fixture.handler.stalled() >= 1_000_000);
+    assert_eq!(fixture.handler.take_io_writes().last(), Some(&(OPERATION_PORT, END)));
+}
+
+#[test]
+fn test_non_volatile_attribute() {
+    let mut error_log = vec![0u8; 256];
+    let fixture = fixture(&mut error_log);
+    assert!(!ErrorRecordStore::new(&fixture.tables).unwrap().is_non_volatile());
+
+    // Bit `0` is reserved, and must not be taken to mean the store is non-volatile
+    fixture.handler.set_io_port(ERROR_LOG_ATTRIBUTES_PORT, 0x1);
+    assert!(!ErrorRecordStore::new(&fixture.tables).unwrap().is_non_volatile());
+
+    fixture.handler.set_io_port(ERROR_LOG_ATTRIBUTES_PORT, 0x2);
+    assert!(ErrorRecordStore::new(&fixture.tables).unwrap().is_non_volatile());
+}
+
+#[test]
+fn test_goto_loop() {
+    // `GET_RECORD_IDENTIFIER` jumps back to its first instruction forever
+    let fixture = build_tables(&[erst(&[instruction(0x08, GOTO, 0, 8, 0, 0)])]);
+    let store = ErrorRecordStore::new(&fixture.tables).unwrap();
+    assert!(matches!(store.next_record_id(), Err(AcpiError::Timeout)));
+}
+
+#[test]
+fn test_move_data_overflow() {
+    let fixture = build_tables(&[erst(&[
+        instruction(0x08, SET_SRC_ADDRESS_BASE, SRC_BASE_PORT, 64, 0, u64::MAX),
+        instruction(0x08, MOVE_DATA, MOVE_OFFSET_PORT, 32, 0, 0xffff_ffff),
+    ])]);
+    let store = ErrorRecordStore::new(&fixture.tables).unwrap();
+
+    // The source address wraps past the end of the address space
+    fixture.handler.set_io_port(SRC_BASE_PORT, 0xffff_ff00);
+    fixture.handler.set_io_port(SRC_BASE_PORT + 4, 0xffff_ffff);
+    fixture.handler.set_io_port(MOVE_OFFSET_PORT, 0x100);
+    assert!(matches!(store.next_record_id(), Err(AcpiError::InvalidGenericAddress)));
+}
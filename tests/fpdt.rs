@@ -0,0 +1,123 @@
+use acpi::sdt::fpdt::{FbptRecord, Fpdt, FpdtRecord, S3ptRecord};
+use aml_test_tools::tables::{build_tables, sdt};
+
+fn pointer_record(record_type: u16, address: u64) -> Vec<u8> {
+    let mut record = record_type.to_le_bytes().to_vec();
+    record.extend_from_slice(&[16, 1]);
+    record.extend_from_slice(&0u32.to_le_bytes());
+    record.extend_from_slice(&address.to_le_bytes());
+    record
+}
+
+fn fpdt_table(records: &[Vec<u8>]) -> Vec<u8> {
+    sdt(b"FPDT", 1, &records.concat())
+}
+
+/// Build a performance table with the given signature and records.
+fn performance_table(signature: &[u8; 4], records: &[Vec<u8>]) -> Vec<u8> {
+    let records = records.concat();
+    let mut table = signature.to_vec();
+    table.extend_from_slice(&(8 + records.len() as u32).to_le_bytes());
+    table.extend_from_slice(&records);
+    table
+}
+
+fn basic_boot_record(timestamps: [u64; 5]) -> Vec<u8> {
+    let mut record = 2u16.to_le_bytes().to_vec();
+    record.extend_from_slice(&[48, 2]);
+    record.extend_from_slice(&0u32.to_le_bytes());
+    record.extend(timestamps.iter().flat_map(|timestamp| timestamp.to_le_bytes()));
+    record
+}
+
+fn resume_record(resume_count: u32, full_resume: u64, average_resume: u64) -> Vec<u8> {
+    let mut record = 0u16.to_le_bytes().to_vec();
+    record.extend_from_slice(&[24, 1]);
+    record.extend_from_slice(&resume_count.to_le_bytes());
+    record.extend_from_slice(&full_resume.to_le_bytes());
+    record.extend_from_slice(&average_resume.to_le_bytes());
+    record
+}
+
+fn suspend_record(suspend_start: u64, suspend_end: u64) -> Vec<u8> {
+    let mut record = 1u16.to_le_bytes().to_vec();
+    record.extend_from_slice(&[20, 1]);
+    record.extend_from_slice(&suspend_start.to_le_bytes());
+    record.extend_from_slice(&suspend_end.to_le_bytes());
+    record
+}
+
+/// Place a performance table in memory, returning the memory and the table's address.
+fn place(table: &[u8]) -> (Box<[u64]>, u64) {
+    let mut memory = vec![0u64; table.len().div_ceil(8)].into_boxed_slice();
+    let base = memory.as_mut_ptr() as *mut u8;
+    unsafe { std::ptr::copy_nonoverlapping(table.as_ptr(), base, table.len()) };
+    (memory, base as u64)
+}
+
+#[test]
+fn test_boot_performance() {
+    // An unknown record is skipped
+    let unknown = vec![0x10, 0x30, 8, 1, 0, 0, 0, 0];
+    let (_fbpt, fbpt_address) = place(&performance_table(
+        b"FBPT",
+        &[unknown.clone(), basic_boot_record([1_000, 2_000_000, 2_500_000, 3_000_000, 3_000_500])],
+    ));
+    let fixture = build_tables(&[fpdt_table(&[pointer_record(0, fbpt_address), unknown])]);
+    let fpdt = fixture.tables.find_table::<Fpdt>().unwrap();
+
+    let records: Vec<_> = fpdt.get().records().collect();
+    assert!(matches!(records[..], [FpdtRecord::FirmwareBasicBootPointer(_), FpdtRecord::Unknown(_)]));
+    assert_eq!(fpdt.get().fbpt_address(), Some(fbpt_address));
+    assert_eq!(fpdt.get().s3pt_address(), None);
+    assert!(fpdt.get().map_s3pt(&fixture.handler).is_none());
+
+    let fbpt = fpdt.get().map_fbpt(&fixture.handler).unwrap();
+    let records: Vec<_> = fbpt.records().collect();
+    assert!(matches!(records[..], [FbptRecord::Unknown(_), FbptRecord::BasicBoot(_)]));
+    let basic_boot = fbpt.basic_boot().unwrap();
+    assert_eq!({ basic_boot.reset_end }, 1_000);
+    assert_eq!({ basic_boot.os_loader_load_image_start }, 2_000_000);
+    assert_eq!({ basic_boot.os_loader_start_image_start }, 2_500_000);
+    assert_eq!({ basic_boot.exit_boot_services_entry }, 3_000_000);
+    assert_eq!({ basic_boot.exit_boot_services_exit }, 3_000_500);
+}
+
+#[test]
+fn test_s3_performance() {
+    let (_s3pt, s3pt_address) =
+        place(&performance_table(b"S3PT", &[resume_record(3, 250_000, 200_000), suspend_record(10_000, 12_000)]));
+    let fixture = build_tables(&[fpdt_table(&[pointer_record(1, s3pt_address)])]);
+    let fpdt = fixture.tables.find_table::<Fpdt>().unwrap();
+    assert!(fpdt.get().map_fbpt(&fixture.handler).is_none());
+
+    let s3pt = fpdt.get().map_s3pt(&fixture.handler).unwrap();
+    let records: Vec<_> = s3pt.records().collect();
+    let [S3ptRecord::Resume(resume), S3ptRecord::Suspend(suspend)] = records[..] else {
+        panic!("Expected a resume record and a suspend record")
+    };
+    assert_eq!({ resume.resume_count }, 3);
+    assert_eq!(({ resume.full_resume }, { resume.average_resume }), (250_000, 200_000));
+    assert_eq!(({ suspend.suspend_start }, { suspend.suspend_end }), (10_000, 12_000));
+}
+
+#[test]
+fn test_invalid_performance_tables() {
+    // The FPDT points to a table with the wrong signature
+    let (_s3pt, s3pt_address) = place(&performance_table(b"S3PT", &[resume_record(1, 0, 0)]));
+    let fixture = build_tables(&[fpdt_table(&[pointer_record(0, s3pt_address)])]);
+    let fpdt = fixture.tables.find_table::<Fpdt>().unwrap();
+    assert!(fpdt.get().map_fbpt(&fixture.handler).is_none());
+
+    // The basic boot record is truncated, and a record extends past the end of the table
+    let mut truncated = basic_boot_record([0; 5]);
+    truncated[2] = 40;
+    truncated.truncate(40);
+    let overlong = vec![0x10, 0x30, 64, 1, 0, 0, 0, 0];
+    let (_fbpt, fbpt_address) = place(&performance_table(b"FBPT", &[truncated, overlong]));
+    let fixture = build_tables(&[fpdt_table(&[pointer_record(0, fbpt_address)])]);
+    let fpdt = fixture.tables.find_table::<Fpdt>().unwrap();
+    let fbpt = fpdt.get().map_fbpt(&fixture.handler).unwrap();
+    assert!(matches!(fbpt.records().collect::<Vec<_>>()[..], [FbptRecord::Unknown(_)]));
+    assert!(fbpt.basic_boot().is_none());
+}
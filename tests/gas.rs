@@ -0,0 +1,48 @@
+use acpi::{
+    AcpiError,
+    address::{AddressSpace, GasMapper, GenericAddress},
+};
+use aml_test_tools::handlers::simulated_handler::SimulatedHandler;
+
+fn memory_register(address: u64) -> GenericAddress {
+    GenericAddress {
+        address_space: AddressSpace::SystemMemory,
+        bit_width: 32,
+        bit_offset: 0,
+        access_size: 3,
+        address,
+    }
+}
+
+#[test]
+fn test_shared_mappings() {
+    let mut registers = [0u32; 4];
+    let base = registers.as_mut_ptr();
+    let mut mapper = GasMapper::new(SimulatedHandler::new());
+
+    let first = unsafe { mapper.map_gas(memory_register(base as u64)) }.unwrap();
+    let second = unsafe { mapper.map_gas(memory_register(base.wrapping_add(2) as u64)) }.unwrap();
+    first.write(0x1234).unwrap();
+    second.write(0x5678).unwrap();
+
+    assert_eq!(unsafe { base.read_volatile() }, 0x1234);
+    assert_eq!(unsafe { base.add(2).read_volatile() }, 0x5678);
+    assert_eq!(second.read().unwrap(), 0x5678);
+    assert_eq!(second.gas().address, base as u64 + 8);
+}
+
+#[test]
+fn test_overflowing_mappings() {
+    let mut mapper = GasMapper::new(SimulatedHandler::new());
+
+    // The register itself extends past the end of the address space
+    assert!(matches!(
+        unsafe { mapper.map_gas(memory_register(u64::MAX - 1)) },
+        Err(AcpiError::InvalidGenericAddress)
+    ));
+    // The register fits, but the page it lies in can't be mapped
+    assert!(matches!(
+        unsafe { mapper.map_gas(memory_register(u64::MAX - 7)) },
+        Err(AcpiError::InvalidGenericAddress)
+    ));
+}
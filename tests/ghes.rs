@@ -0,0 +1,244 @@
+use acpi::{
+    cper::{
+        ARM_PROCESSOR_SECTION,
+        ArmErrorType,
+        CperSection,
+        MemoryErrorType,
+        PCIE_SECTION,
+        PLATFORM_MEMORY_SECTION,
+        PROCESSOR_GENERIC_SECTION,
+    },
+    sdt::hest::{BlockStatus, ErrorSeverity, ErrorSource, GenericErrorStatusBlock, Hest, NotificationType},
+};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+use pci_types::PciAddress;
+
+/// The index of the Error Status Address register in the simulated memory.
+const ERROR_STATUS_ADDRESS: usize = 0;
+/// The index of the Read Ack register in the simulated memory.
+const READ_ACK: usize = 1;
+/// The byte offset of the error status block in the simulated memory.
+const BLOCK_OFFSET: usize = 16;
+const BLOCK_LENGTH: u32 = 1024;
+
+fn ghes_v2(source_id: u16, error_status_address: u64, read_ack_register: u64) -> Vec<u8> {
+    let mut source = 10u16.to_le_bytes().to_vec();
+    source.extend_from_slice(&source_id.to_le_bytes());
+    source.extend_from_slice(&0xffffu16.to_le_bytes());
+    source.extend_from_slice(&[0, 1]);
+    source.extend_from_slice(&1u32.to_le_bytes());
+    source.extend_from_slice(&4u32.to_le_bytes());
+    source.extend_from_slice(&0u32.to_le_bytes());
+    source.extend_from_slice(&gas(0, 64, 4, error_status_address));
+    // An SCI notification
+    source.extend_from_slice(&[3, 28, 0, 0]);
+    source.extend_from_slice(&[0; 24]);
+    source.extend_from_slice(&BLOCK_LENGTH.to_le_bytes());
+    source.extend_from_slice(&gas(0, 64, 4, read_ack_register));
+    source.extend_from_slice(&0xff00u64.to_le_bytes());
+    source.extend_from_slice(&0x01u64.to_le_bytes());
+    source
+}
+
+fn hest(sources: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = (sources.len() as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&sources.concat());
+    sdt(b"HEST", 1, &body)
+}
+
+/// A generic error data entry. Entries from revision `0x300` have a (valid) timestamp.
+fn error_data(section_type: [u8; 16], severity: u32, revision: u16, data: &[u8]) -> Vec<u8> {
+    let mut entry = section_type.to_vec();
+    entry.extend_from_slice(&severity.to_le_bytes());
+    entry.extend_from_slice(&revision.to_le_bytes());
+    let validation_bits = if revision >= 0x300 { 0b110 } else { 0b010 };
+    entry.extend_from_slice(&[validation_bits, 0]);
+    entry.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    entry.extend_from_slice(&[0; 16]);
+    let mut fru_text = [0; 20];
+    fru_text[0..5].copy_from_slice(b"DIMM0");
+    entry.extend_from_slice(&fru_text);
+    if revision >= 0x300 {
+        entry.extend_from_slice(&0x2026_1018u64.to_le_bytes());
+    }
+    entry.extend_from_slice(data);
+    entry
+}
+
+fn status_block(block_status: u32, severity: u32, entries: &[Vec<u8>], raw_data: &[u8]) -> Vec<u8> {
+    let data_length = entries.iter().map(Vec::len).sum::<usize>() as u32;
+    let mut block = block_status.to_le_bytes().to_vec();
+    block.extend_from_slice(&(20 + data_length).to_le_bytes());
+    block.extend_from_slice(&(raw_data.len() as u32).to_le_bytes());
+    block.extend_from_slice(&data_length.to_le_bytes());
+    block.extend_from_slice(&severity.to_le_bytes());
+    block.extend_from_slice(&entries.concat());
+    block.extend_from_slice(raw_data);
+    block
+}
+
+/// A memory error section, reporting a multi-bit ECC error at the given physical address.
+fn memory_section(physical_address: u64) -> Vec<u8> {
+    let mut section = vec![0; 80];
+    section[0..8].copy_from_slice(&(1u64 << 1 | 1 << 3 | 1 << 6 | 1 << 14).to_le_bytes());
+    section[16..24].copy_from_slice(&physical_address.to_le_bytes());
+    section[32..34].copy_from_slice(&1u16.to_le_bytes());
+    section[38..40].copy_from_slice(&5u16.to_le_bytes());
+    section[72] = 3;
+    section
+}
+
+fn pcie_section(device: u8, function: u8) -> Vec<u8> {
+    let mut section = vec![0; 208];
+    section[0..8].copy_from_slice(&(1u64 << 0 | 1 << 3 | 1 << 4).to_le_bytes());
+    section[8..12].copy_from_slice(&4u32.to_le_bytes());
+    section[31] = function;
+    section[32] = device;
+    section[33..35].copy_from_slice(&2u16.to_le_bytes());
+    section[35] = 0x3a;
+    section[40..48].copy_from_slice(&0x1122_3344_5566_7788u64.to_le_bytes());
+    section
+}
+
+/// An Arm processor error section, with a single TLB error and a single register context.
+fn arm_section(validation_bits: u32) -> Vec<u8> {
+    let mut section = validation_bits.to_le_bytes().to_vec();
+    section.extend_from_slice(&1u16.to_le_bytes());
+    section.extend_from_slice(&1u16.to_le_bytes());
+    section.extend_from_slice(&96u32.to_le_bytes());
+    section.extend_from_slice(&[0; 4]);
+    section.extend_from_slice(&0x0100u64.to_le_bytes());
+    section.extend_from_slice(&0x410f_d0c1u64.to_le_bytes());
+    section.extend_from_slice(&1u32.to_le_bytes());
+    section.extend_from_slice(&0u32.to_le_bytes());
+
+    section.extend_from_slice(&[0, 32]);
+    section.extend_from_slice(&(1u16 << 4).to_le_bytes());
+    section.extend_from_slice(&[1, 0, 0, 0]);
+    section.extend_from_slice(&0u64.to_le_bytes());
+    section.extend_from_slice(&0xffff_0000_1234_5000u64.to_le_bytes());
+    section.extend_from_slice(&0x8_1234_5000u64.to_le_bytes());
+
+    section.extend_from_slice(&0u16.to_le_bytes());
+    section.extend_from_slice(&5u16.to_le_bytes());
+    section.extend_from_slice(&16u32.to_le_bytes());
+    section.extend_from_slice(&[0xaa; 16]);
+    section
+}
+
+#[test]
+fn test_ghes_read_and_acknowledge() {
+    let mut memory = Box::new([0u64; 160]);
+    let base = memory.as_mut_ptr();
+    let block_address = base as u64 + BLOCK_OFFSET as u64;
+    let fixture = build_tables(&[hest(&[ghes_v2(4, base as u64, unsafe { base.add(READ_ACK) } as u64)])]);
+
+    let hest = fixture.tables.find_table::<Hest>().unwrap();
+    let Some(ErrorSource::GhesV2(source)) = hest.get().error_source(4) else { panic!("Expected a GHESv2") };
+    assert!(source.ghes.enabled());
+    assert_eq!(source.ghes.related_source_id(), None);
+    assert_eq!(source.ghes.notification.notification_type(), NotificationType::Sci);
+    let ghes = unsafe { source.map(&fixture.handler) }.unwrap();
+
+    // The firmware hasn't reported an error yet
+    assert!(ghes.read().unwrap().is_none());
+
+    let block = status_block(
+        BlockStatus::CORRECTABLE_ERROR_VALID.bits() | 1 << 4,
+        2,
+        &[error_data(PLATFORM_MEMORY_SECTION, 2, 0x300, &memory_section(0x8000_1000))],
+        &[],
+    );
+    unsafe {
+        std::ptr::copy_nonoverlapping(block.as_ptr(), (base as *mut u8).add(BLOCK_OFFSET), block.len());
+        base.add(ERROR_STATUS_ADDRESS).write_volatile(block_address);
+        base.add(READ_ACK).write_volatile(0xabcd);
+    }
+
+    let status = ghes.read().unwrap().unwrap();
+    let block = status.block().unwrap();
+    assert!(block.block_status().contains(BlockStatus::CORRECTABLE_ERROR_VALID));
+    assert_eq!(block.error_data_entry_count(), 1);
+    assert_eq!(block.severity(), ErrorSeverity::Corrected);
+    let entry = block.entries().next().unwrap();
+    assert_eq!(entry.timestamp, Some(0x2026_1018));
+    assert_eq!(entry.fru_text(), Some("DIMM0"));
+    let CperSection::Memory(section) = entry.section() else { panic!("Expected a memory error section") };
+    assert_eq!(section.physical_address(), Some(0x8000_1000));
+    assert_eq!(section.memory_error_type(), Some(MemoryErrorType::MultiBitEcc));
+
+    // Acknowledging the error clears the block status, and sets the Read Ack bit
+    status.acknowledge().unwrap();
+    assert_eq!(unsafe { base.add(READ_ACK).read_volatile() }, 0xab01);
+    assert!(ghes.read().unwrap().is_none());
+}
+
+#[test]
+fn test_cper_sections() {
+    let block = status_block(
+        BlockStatus::UNCORRECTABLE_ERROR_VALID.bits() | 4 << 4,
+        0,
+        &[
+            error_data(PLATFORM_MEMORY_SECTION, 0, 0x300, &memory_section(0x8000_1000)),
+            error_data(PCIE_SECTION, 0, 0x201, &pcie_section(0x1c, 2)),
+            error_data(ARM_PROCESSOR_SECTION, 1, 0x300, &arm_section(0b101)),
+            // A processor generic section that is too short
+            error_data(PROCESSOR_GENERIC_SECTION, 0, 0x201, &[0; 16]),
+        ],
+        &[0xde, 0xad],
+    );
+    let block = GenericErrorStatusBlock::parse(&block).unwrap();
+    assert_eq!(block.length(), 20 + 152 + 272 + 168 + 80 + 2);
+    assert_eq!(block.severity(), ErrorSeverity::Recoverable);
+    assert_eq!(block.raw_data(), [0xde, 0xad]);
+
+    let sections: Vec<_> = block.entries().map(|entry| entry.section()).collect();
+    assert_eq!(sections.len(), 4);
+
+    let CperSection::Memory(memory) = sections[0] else { panic!("Expected a memory error section") };
+    assert_eq!((memory.node(), memory.card(), memory.bank()), (Some(1), None, Some(5)));
+    assert_eq!(memory.physical_address_mask(), None);
+
+    let CperSection::Pcie(pcie) = sections[1] else { panic!("Expected a PCIe error section") };
+    assert_eq!(pcie.port_type(), Some(4));
+    assert_eq!(pcie.pci_address(), Some(PciAddress::new(2, 0x3a, 0x1c, 2)));
+    assert_eq!(pcie.device_serial_number(), Some(0x1122_3344_5566_7788));
+    assert_eq!(pcie.aer_info(), None);
+
+    let CperSection::Arm(arm) = sections[2] else { panic!("Expected an Arm processor error section") };
+    assert_eq!(arm.mpidr(), Some(0x0100));
+    assert_eq!(arm.midr(), 0x410f_d0c1);
+    assert_eq!(arm.running_state(), Some(true));
+    assert_eq!(arm.error_info.len(), 1);
+    assert_eq!(arm.error_info[0].error_type(), ArmErrorType::Tlb);
+    assert_eq!(arm.error_info[0].virtual_fault_address(), None);
+    assert_eq!(arm.error_info[0].physical_fault_address(), Some(0x8_1234_5000));
+    let contexts: Vec<_> = arm.contexts().collect();
+    assert_eq!(contexts.len(), 1);
+    assert_eq!({ contexts[0].header.register_context_type }, 5);
+    assert_eq!(contexts[0].registers, [0xaa; 16]);
+
+    assert!(matches!(sections[3], CperSection::Unknown { section_type: PROCESSOR_GENERIC_SECTION, .. }));
+}
+
+#[test]
+fn test_invalid_cper_sections() {
+    // A device number that doesn't fit in a PCI address
+    let CperSection::Pcie(pcie) = CperSection::parse(PCIE_SECTION, &pcie_section(0x20, 0)) else {
+        panic!("Expected a PCIe error section")
+    };
+    assert_eq!(pcie.pci_address(), None);
+
+    // The MPIDR and running state are not valid, but the MIDR is always reported
+    let section = arm_section(0);
+    let CperSection::Arm(arm) = CperSection::parse(ARM_PROCESSOR_SECTION, &section) else {
+        panic!("Expected an Arm processor error section")
+    };
+    assert_eq!(arm.mpidr(), None);
+    assert_eq!(arm.midr(), 0x410f_d0c1);
+    assert_eq!(arm.running_state(), None);
+
+    // The block extends past the end of its region
+    let block = status_block(1, 0, &[error_data(PCIE_SECTION, 0, 0x201, &pcie_section(0, 0))], &[]);
+    assert!(GenericErrorStatusBlock::parse(&block[0..100]).is_none());
+}
@@ -0,0 +1,149 @@
+use acpi::{
+    AcpiError,
+    platform::{InterruptModel, ProcessorState, interrupt::GicVersion},
+    sdt::madt::{MadtError, TriggerMode},
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+/// GICC flags for an enabled processor.
+const ENABLED: u32 = 1 << 0;
+/// GICC flags for an edge-triggered performance interrupt.
+const PERFORMANCE_EDGE: u32 = 1 << 1;
+/// GICC flags for a processor that can be brought online later.
+const ONLINE_CAPABLE: u32 = 1 << 3;
+
+fn gicc(cpu_interface_number: u32, processor_uid: u32, flags: u32, mpidr: u64) -> Vec<u8> {
+    let mut entry = vec![0xb, 82, 0, 0];
+    entry.extend_from_slice(&cpu_interface_number.to_le_bytes());
+    entry.extend_from_slice(&processor_uid.to_le_bytes());
+    entry.extend_from_slice(&flags.to_le_bytes());
+    entry.extend_from_slice(&1u32.to_le_bytes());
+    entry.extend_from_slice(&23u32.to_le_bytes());
+    entry.extend_from_slice(&(0x8000_0000 + processor_uid as u64 * 0x1000).to_le_bytes());
+    entry.extend_from_slice(&0x2c00_0000u64.to_le_bytes());
+    entry.extend_from_slice(&0x2c02_0000u64.to_le_bytes());
+    entry.extend_from_slice(&0x2c01_0000u64.to_le_bytes());
+    entry.extend_from_slice(&25u32.to_le_bytes());
+    entry.extend_from_slice(&0u64.to_le_bytes());
+    entry.extend_from_slice(&mpidr.to_le_bytes());
+    entry.extend_from_slice(&[1, 0]);
+    entry.extend_from_slice(&21u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry
+}
+
+fn gicd(address: u64, version: u8) -> Vec<u8> {
+    let mut entry = vec![0xc, 24, 0, 0];
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&[version, 0, 0, 0]);
+    entry
+}
+
+fn msi_frame(id: u32, address: u64, spis: Option<(u16, u16)>) -> Vec<u8> {
+    let mut entry = vec![0xd, 24, 0, 0];
+    entry.extend_from_slice(&id.to_le_bytes());
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&(spis.is_some() as u32).to_le_bytes());
+    let (base, count) = spis.unwrap_or_default();
+    entry.extend_from_slice(&count.to_le_bytes());
+    entry.extend_from_slice(&base.to_le_bytes());
+    entry
+}
+
+fn redistributor(address: u64, length: u32) -> Vec<u8> {
+    let mut entry = vec![0xe, 16, 0, 0];
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&length.to_le_bytes());
+    entry
+}
+
+fn its(id: u32, address: u64) -> Vec<u8> {
+    let mut entry = vec![0xf, 20, 0, 0];
+    entry.extend_from_slice(&id.to_le_bytes());
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry
+}
+
+fn madt(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![0; 8];
+    body.extend_from_slice(&entries.concat());
+    sdt(b"APIC", 6, &body)
+}
+
+#[test]
+fn test_gicv3_model() {
+    let fixture = build_tables(&[madt(&[
+        // A disabled processor comes first, so the first enabled processor is the boot processor
+        gicc(0, 0, ONLINE_CAPABLE, 0x0000),
+        gicc(1, 1, ENABLED | PERFORMANCE_EDGE, 0x0100),
+        gicc(2, 2, ENABLED, 0x0200),
+        gicd(0x2f00_0000, 3),
+        redistributor(0x2f10_0000, 0x10_0000),
+        its(7, 0x2f02_0000),
+    ])]);
+    let (model, processor_info) = InterruptModel::new(&fixture.tables).unwrap();
+
+    let InterruptModel::Gic(gic) = model else { panic!("Expected a GIC interrupt model") };
+    assert_eq!(gic.distributor.address, 0x2f00_0000);
+    assert_eq!(gic.distributor.version, GicVersion::V3);
+    assert_eq!(gic.redistributors.len(), 1);
+    assert_eq!(gic.redistributors[0].discovery_range_base_address, 0x2f10_0000);
+    assert_eq!(gic.redistributors[0].discovery_range_length, 0x10_0000);
+    assert_eq!(gic.its.len(), 1);
+    assert_eq!((gic.its[0].id, gic.its[0].address), (7, 0x2f02_0000));
+    assert!(gic.msi_frames.is_empty());
+
+    let processor_info = processor_info.unwrap();
+    let boot_processor = processor_info.boot_processor;
+    assert_eq!(boot_processor.processor_uid, 1);
+    assert_eq!(boot_processor.local_apic_id, 1);
+    assert_eq!(boot_processor.state, ProcessorState::Running);
+    assert!(!boot_processor.is_ap);
+    assert_eq!(boot_processor.rintc, None);
+
+    let gicc = boot_processor.gicc.unwrap();
+    assert_eq!(gicc.mpidr, 0x0100);
+    assert_eq!(gicc.parked_address, 0x8000_1000);
+    assert_eq!(gicc.performance_interrupt_gsiv, 23);
+    assert_eq!(gicc.performance_interrupt_trigger_mode, TriggerMode::Edge);
+    assert_eq!(gicc.vgic_maintenance_interrupt_trigger_mode, TriggerMode::Level);
+    assert_eq!(gicc.gic_hypervisor_registers_address, 0x2c01_0000);
+    assert_eq!(gicc.spe_overflow_interrupt, 21);
+    assert!(!gicc.online_capable);
+
+    let states: Vec<_> = processor_info.application_processors.iter().map(|processor| processor.state).collect();
+    assert_eq!(states, [ProcessorState::Disabled, ProcessorState::Parked]);
+    assert!(processor_info.application_processors[0].gicc.unwrap().online_capable);
+}
+
+#[test]
+fn test_gicv2m_model() {
+    let fixture = build_tables(&[madt(&[
+        gicc(0, 0, ENABLED, 0),
+        gicd(0x0800_0000, 2),
+        msi_frame(0, 0x0802_0000, None),
+        msi_frame(1, 0x0803_0000, Some((64, 32))),
+    ])]);
+    let (model, processor_info) = InterruptModel::new(&fixture.tables).unwrap();
+
+    let InterruptModel::Gic(gic) = model else { panic!("Expected a GIC interrupt model") };
+    assert_eq!(gic.distributor.version, GicVersion::V2);
+    let frames: Vec<_> = gic.msi_frames.iter().map(|frame| (frame.id, frame.address, frame.spis)).collect();
+    assert_eq!(frames, [(0, 0x0802_0000, None), (1, 0x0803_0000, Some((64, 32)))]);
+    assert!(processor_info.unwrap().application_processors.is_empty());
+}
+
+#[test]
+fn test_invalid_gic_model() {
+    // There is no distributor
+    let fixture = build_tables(&[madt(&[gicc(0, 0, ENABLED, 0)])]);
+    assert!(matches!(InterruptModel::new(&fixture.tables), Err(AcpiError::InvalidMadt(MadtError::GicdNotFound))));
+
+    // A GIC distributor can't be mixed with APIC entries
+    let local_apic = vec![0, 8, 0, 0, 1, 0, 0, 0];
+    let fixture = build_tables(&[madt(&[gicd(0x0800_0000, 2), local_apic])]);
+    assert!(InterruptModel::new(&fixture.tables).is_err());
+}
@@ -0,0 +1,254 @@
+use acpi::{
+    AcpiError,
+    address::{AddressSpace, GenericAddress},
+    aml::{Interpreter, namespace::AmlName},
+    registers::{GpeRegisterBlock, Pm1Event},
+};
+use aml_test_tools::{TestResult, handlers::simulated_handler::SimulatedHandler, run_test_for_string};
+use std::{str::FromStr, sync::Arc};
+
+const GPE0_STATUS: u16 = SimulatedHandler::GPE0_PORT;
+const GPE0_ENABLE: u16 = SimulatedHandler::GPE0_PORT + 2;
+const GPE1_STATUS: u16 = 0x440;
+const GPE1_ENABLE: u16 = 0x442;
+const DEBUG_PORT: u16 = 0x80;
+
+/// `GPE0` has a level event `1` and an edge event `2` handled at runtime, and a wake event `3`.
+/// `\_SB.GPE1` is a GPE Block Device wired to IRQ 9, with its own event `0` and a wake event `1`.
+const AML: &str = r#"DefinitionBlock("", "DSDT", 2, "RSACPI", "GPE", 1) {
+    OperationRegion(DBG, SystemIO, 0x80, 1)
+    Field(DBG, ByteAcc, NoLock, Preserve) {
+        DBGB, 8
+    }
+
+    Scope(\_GPE) {
+        Method(_L01, 0, NotSerialized) {
+            DBGB = 0x01
+        }
+
+        Method(_E02, 0, NotSerialized) {
+            DBGB = 0x02
+        }
+
+        Method(_L03, 0, NotSerialized) {
+            DBGB = 0x03
+        }
+    }
+
+    PowerResource(WPR0, 0, 0) {
+        Method(_STA, 0, NotSerialized) {
+            Return (Zero)
+        }
+
+        Method(_ON, 0, NotSerialized) {
+            DBGB = 0xA0
+        }
+
+        Method(_OFF, 0, NotSerialized) {
+            DBGB = 0xA1
+        }
+    }
+
+    Scope(\_SB) {
+        Device(GPE1) {
+            Name(_HID, "ACPI0006")
+            Name(_CRS, ResourceTemplate() {
+                IO(Decode16, 0x440, 0x440, 1, 4)
+                Interrupt(ResourceConsumer, Level, ActiveHigh, Shared) { 9 }
+            })
+
+            Method(_E00, 0, NotSerialized) {
+                DBGB = 0x10
+            }
+        }
+
+        Device(LID0) {
+            Name(_PRW, Package() { 0x03, 0x03, \WPR0 })
+            Method(_DSW, 3, NotSerialized) {
+                DBGB = 0xD0 | Arg0
+            }
+        }
+
+        Device(BTN0) {
+            Name(_PRW, Package() { 0x04, 0x01 })
+            Method(_PSW, 1, NotSerialized) {
+                DBGB = 0xE0 | Arg0
+            }
+        }
+
+        Device(KBD0) {
+            Name(_PRW, Package() { Package() { \_SB.GPE1, 0x01 }, 0x03 })
+        }
+    }
+}
+"#;
+
+fn gpe_interpreter() -> (Interpreter<SimulatedHandler>, SimulatedHandler) {
+    let handler = SimulatedHandler::new();
+    #[allow(clippy::arc_with_non_send_sync)]
+    let mut interpreter = Interpreter::new(handler.clone(), 2, Arc::new(handler.fixed_registers()), None);
+    assert_eq!(run_test_for_string(AML, &mut interpreter), TestResult::Pass);
+    interpreter.initialize_gpes().unwrap();
+
+    // The simulated status registers are not cleared by writing `1`s to them, so clear them here
+    for port in [GPE0_STATUS, GPE0_STATUS + 1, GPE1_STATUS, GPE1_STATUS + 1] {
+        handler.set_io_port(port, 0);
+    }
+    (interpreter, handler)
+}
+
+fn debug_writes(handler: &SimulatedHandler) -> Vec<u32> {
+    handler.take_io_writes().into_iter().filter(|&(port, _)| port == DEBUG_PORT).map(|(_, value)| value).collect()
+}
+
+#[test]
+fn test_initialize_gpes() {
+    let (interpreter, handler) = gpe_interpreter();
+
+    // Only events with handlers that are not used for wake are enabled
+    assert_eq!(handler.io_port(GPE0_ENABLE), 0b0110);
+    assert_eq!(handler.io_port(GPE0_ENABLE + 1), 0);
+    assert_eq!(handler.io_port(GPE1_ENABLE), 0b0001);
+    assert_eq!(handler.io_port(GPE1_ENABLE + 1), 0);
+
+    let mut wake_devices = interpreter.wake_devices();
+    wake_devices.sort_by_key(|device| device.device.as_string());
+    let summary: Vec<_> = wake_devices
+        .iter()
+        .map(|device| {
+            (
+                device.device.as_string(),
+                device.gpe.block_device.as_ref().map(AmlName::as_string),
+                device.gpe.number,
+                device.deepest_sleep_state,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("\\_SB_.BTN0".to_string(), None, 4, 1),
+            ("\\_SB_.KBD0".to_string(), Some("\\_SB_.GPE1".to_string()), 1, 3),
+            ("\\_SB_.LID0".to_string(), None, 3, 3),
+        ]
+    );
+    assert_eq!(wake_devices[2].power_resources, [AmlName::from_str("\\WPR0").unwrap()]);
+}
+
+#[test]
+fn test_arm_wake_gpes() {
+    let (interpreter, handler) = gpe_interpreter();
+    handler.take_io_writes();
+
+    interpreter.arm_wake_gpes(3).unwrap();
+    // Only wake events for devices that can wake the system from `S3` are enabled
+    assert_eq!(handler.io_port(GPE0_ENABLE), 0b1000);
+    assert_eq!(handler.io_port(GPE1_ENABLE), 0b0010);
+    // The power resource is turned on, and then `_DSW` is called to enable wake
+    assert_eq!(debug_writes(&handler), [0xa0, 0xd1]);
+
+    interpreter.disarm_wake_gpes().unwrap();
+    assert_eq!(handler.io_port(GPE0_ENABLE), 0b0110);
+    assert_eq!(handler.io_port(GPE1_ENABLE), 0b0001);
+    // `LID0`'s wake is disabled before its power resource is turned off
+    let mut writes = debug_writes(&handler);
+    let position = |value| writes.iter().position(|&write| write == value).unwrap();
+    assert!(position(0xd0) < position(0xa1));
+    writes.sort();
+    assert_eq!(writes, [0xa1, 0xd0, 0xe0]);
+
+    // `BTN0` can only wake the system from `S1`, and only has a `_PSW`
+    interpreter.arm_wake_gpes(1).unwrap();
+    assert_eq!(handler.io_port(GPE0_ENABLE), 0b1_1000);
+    let mut writes = debug_writes(&handler);
+    writes.sort();
+    assert_eq!(writes, [0xa0, 0xd1, 0xe1]);
+}
+
+#[test]
+fn test_fixed_event_acknowledged() {
+    let (interpreter, handler) = gpe_interpreter();
+    let power_button = Pm1Event::PowerButton as u32;
+    let sleep_button = Pm1Event::SleepButton as u32;
+
+    // Both buttons have been pressed, but only the power button is enabled
+    handler.set_io_port(
+        SimulatedHandler::PM1A_EVENT_PORT,
+        1 << power_button | 1 << sleep_button | 1 << (16 + power_button),
+    );
+    handler.take_io_writes();
+
+    let events = interpreter.handle_sci().unwrap();
+    assert!(events.power_button);
+    assert!(!events.sleep_button);
+    assert_eq!(events.gpes_handled, 0);
+
+    // The power button is acknowledged by writing a `1` to its status bit only, preserving the
+    // enable bits
+    assert_eq!(
+        handler.take_io_writes(),
+        [(SimulatedHandler::PM1A_EVENT_PORT, 1 << power_button | 1 << (16 + power_button))]
+    );
+}
+
+#[test]
+fn test_gpe_dispatch_order() {
+    let (interpreter, handler) = gpe_interpreter();
+    handler.set_io_port(GPE0_STATUS, 0b0110);
+    handler.take_io_writes();
+
+    let events = interpreter.handle_sci().unwrap();
+    assert_eq!(events.gpes_handled, 2);
+
+    // The edge event is cleared before its handler runs, and the level event after
+    assert_eq!(
+        handler.take_io_writes(),
+        [(GPE0_STATUS, 0b0100), (DEBUG_PORT, 0x01), (GPE0_STATUS, 0b0010), (DEBUG_PORT, 0x02)]
+    );
+}
+
+#[test]
+fn test_unhandled_gpe_disabled() {
+    let (interpreter, handler) = gpe_interpreter();
+    // Event `5` has no handler, but has been enabled anyway
+    handler.set_io_port(GPE0_STATUS, 0b10_0000);
+    handler.set_io_port(GPE0_ENABLE, 0b10_0110);
+    handler.take_io_writes();
+
+    assert_eq!(interpreter.handle_sci().unwrap().gpes_handled, 0);
+    assert_eq!(handler.io_port(GPE0_ENABLE), 0b0110);
+    assert_eq!(handler.take_io_writes(), [(GPE0_ENABLE, 0b0110), (GPE0_STATUS, 0b10_0000)]);
+}
+
+#[test]
+fn test_gpe_block_interrupt() {
+    let (interpreter, handler) = gpe_interpreter();
+    handler.set_io_port(GPE1_STATUS, 0b0001);
+    handler.take_io_writes();
+
+    // The block is not dispatched from the SCI
+    assert_eq!(interpreter.handle_sci().unwrap().gpes_handled, 0);
+    assert_eq!(handler.take_io_writes(), []);
+
+    assert_eq!(interpreter.handle_gpe_interrupt(9).unwrap(), 1);
+    assert_eq!(handler.take_io_writes(), [(GPE1_STATUS, 0b0001), (DEBUG_PORT, 0x10)]);
+    assert_eq!(interpreter.handle_gpe_interrupt(10).unwrap(), 0);
+}
+
+#[test]
+fn test_gpe_block_io_range() {
+    let block = |address: u64, length: usize| {
+        let base = GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width: 8,
+            bit_offset: 0,
+            access_size: 1,
+            address,
+        };
+        unsafe { GpeRegisterBlock::new(base, length, SimulatedHandler::new()) }
+    };
+
+    assert!(block(0xfffc, 4).is_ok());
+    assert!(matches!(block(0xfffe, 4), Err(AcpiError::InvalidGenericAddress)));
+    assert!(matches!(block(0x1_0000, 2), Err(AcpiError::InvalidGenericAddress)));
+}
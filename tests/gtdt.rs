@@ -0,0 +1,172 @@
+use acpi::sdt::{
+    gtdt::{Gtdt, GtdtInfo, PlatformTimer, TimerInterrupt},
+    madt::{Polarity, TriggerMode},
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+/// Timer flags for an edge-triggered, active-low interrupt.
+const EDGE_ACTIVE_LOW: u32 = 0b011;
+/// Timer flags for an always-on, level-triggered, active-high interrupt.
+const ALWAYS_ON: u32 = 0b100;
+
+/// A GT block with a frame for each of the given `(physical_gsiv, virtual_gsiv)` pairs.
+fn gt_block(cnt_ctl_base: u64, frames: &[(u32, u32)]) -> Vec<u8> {
+    let length = 20 + frames.len() * 40;
+    let mut block = vec![0];
+    block.extend_from_slice(&(length as u16).to_le_bytes());
+    block.push(0);
+    block.extend_from_slice(&cnt_ctl_base.to_le_bytes());
+    block.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    block.extend_from_slice(&20u32.to_le_bytes());
+    for (i, &(physical_gsiv, virtual_gsiv)) in frames.iter().enumerate() {
+        block.extend_from_slice(&[i as u8, 0, 0, 0]);
+        block.extend_from_slice(&(cnt_ctl_base + 0x1_0000 * (i as u64 + 1)).to_le_bytes());
+        block.extend_from_slice(&u64::MAX.to_le_bytes());
+        block.extend_from_slice(&physical_gsiv.to_le_bytes());
+        block.extend_from_slice(&EDGE_ACTIVE_LOW.to_le_bytes());
+        block.extend_from_slice(&virtual_gsiv.to_le_bytes());
+        block.extend_from_slice(&0u32.to_le_bytes());
+        // Frame `1` is secure, and always on
+        block.extend_from_slice(&(i as u32 * 0b11).to_le_bytes());
+    }
+    block
+}
+
+fn watchdog(refresh_frame: u64, control_frame: u64, gsiv: u32, flags: u32) -> Vec<u8> {
+    let mut watchdog = vec![1];
+    watchdog.extend_from_slice(&28u16.to_le_bytes());
+    watchdog.push(0);
+    watchdog.extend_from_slice(&refresh_frame.to_le_bytes());
+    watchdog.extend_from_slice(&control_frame.to_le_bytes());
+    watchdog.extend_from_slice(&gsiv.to_le_bytes());
+    watchdog.extend_from_slice(&flags.to_le_bytes());
+    watchdog
+}
+
+/// Build a GTDT with the architected timers on PPIs `29`, `30`, `27` and `26` (and `28` for the EL2
+/// virtual timer, from revision 3).
+fn gtdt(revision: u8, platform_timers: &[Vec<u8>]) -> Vec<u8> {
+    let header_length: u32 = if revision >= 3 { 104 } else { 96 };
+
+    let mut body = u64::MAX.to_le_bytes().to_vec();
+    body.extend_from_slice(&0u32.to_le_bytes());
+    for (gsiv, flags) in [(29u32, 0), (30, ALWAYS_ON), (27, EDGE_ACTIVE_LOW), (26, 0)] {
+        body.extend_from_slice(&gsiv.to_le_bytes());
+        body.extend_from_slice(&flags.to_le_bytes());
+    }
+    body.extend_from_slice(&0x2a81_0000u64.to_le_bytes());
+    body.extend_from_slice(&(platform_timers.len() as u32).to_le_bytes());
+    body.extend_from_slice(&header_length.to_le_bytes());
+    if revision >= 3 {
+        body.extend_from_slice(&28u32.to_le_bytes());
+        body.extend_from_slice(&0u32.to_le_bytes());
+    }
+    body.extend_from_slice(&platform_timers.concat());
+    sdt(b"GTDT", revision, &body)
+}
+
+#[test]
+fn test_architected_timers() {
+    let fixture =
+        build_tables(&[gtdt(3, &[gt_block(0x2a83_0000, &[(57, 58)]), watchdog(0x2a44_0000, 0x2a45_0000, 59, 0)])]);
+    let info = GtdtInfo::new(&fixture.tables).unwrap();
+
+    assert_eq!(info.cnt_control_base, None);
+    assert_eq!(info.cnt_read_base, Some(0x2a81_0000));
+    assert_eq!(
+        info.secure_el1_timer,
+        Some(TimerInterrupt {
+            gsiv: 29,
+            trigger_mode: TriggerMode::Level,
+            polarity: Polarity::ActiveHigh,
+            always_on: false
+        })
+    );
+    assert!(info.non_secure_el1_timer.unwrap().always_on);
+    let virtual_el1_timer = info.virtual_el1_timer.unwrap();
+    assert_eq!(
+        (virtual_el1_timer.trigger_mode, virtual_el1_timer.polarity),
+        (TriggerMode::Edge, Polarity::ActiveLow)
+    );
+    assert_eq!(info.el2_timer.unwrap().gsiv, 26);
+    assert_eq!(info.virtual_el2_timer.unwrap().gsiv, 28);
+    assert_eq!((info.num_gt_blocks, info.num_watchdogs), (1, 1));
+
+    // The EL2 virtual timer is only reported from revision 3
+    let fixture = build_tables(&[gtdt(2, &[])]);
+    let info = GtdtInfo::new(&fixture.tables).unwrap();
+    assert_eq!(info.virtual_el2_timer, None);
+    assert_eq!(info.el2_timer.unwrap().gsiv, 26);
+    assert_eq!((info.num_gt_blocks, info.num_watchdogs), (0, 0));
+}
+
+#[test]
+fn test_platform_timers() {
+    // A platform timer of an unknown type is skipped
+    let unknown = vec![7, 8, 0, 0, 0, 0, 0, 0];
+    let fixture = build_tables(&[gtdt(
+        3,
+        &[gt_block(0x2a83_0000, &[(57, 0), (60, 61)]), unknown, watchdog(0x2a44_0000, 0x2a45_0000, 59, 0b110)],
+    )]);
+    let gtdt = fixture.tables.find_table::<Gtdt>().unwrap();
+
+    let timers: Vec<_> = gtdt.get().platform_timers().collect();
+    assert_eq!(timers.len(), 2);
+    let PlatformTimer::GtBlock(block) = timers[0] else { panic!("Expected a GT block") };
+    assert_eq!({ block.cnt_ctl_base }, 0x2a83_0000);
+
+    let frames = block.timers();
+    assert_eq!(frames.len(), 2);
+    assert_eq!({ frames[0].cnt_base }, 0x2a84_0000);
+    assert_eq!(frames[0].physical_timer().gsiv, 57);
+    assert_eq!(frames[0].physical_timer().trigger_mode, TriggerMode::Edge);
+    assert_eq!(frames[0].virtual_timer(), None);
+    assert!(!frames[0].is_secure() && !frames[0].always_on());
+    assert_eq!(frames[1].virtual_timer().unwrap().gsiv, 61);
+    assert!(frames[1].is_secure() && frames[1].always_on());
+
+    let watchdog = gtdt.get().watchdogs().next().unwrap();
+    assert_eq!({ watchdog.refresh_frame_address }, 0x2a44_0000);
+    assert_eq!({ watchdog.control_frame_address }, 0x2a45_0000);
+    assert_eq!(watchdog.interrupt().gsiv, 59);
+    assert_eq!(watchdog.interrupt().polarity, Polarity::ActiveLow);
+    assert!(watchdog.is_secure());
+}
+
+#[test]
+fn test_truncated_platform_timers() {
+    // The GT block's frames extend past the end of the block
+    let mut block = gt_block(0x2a83_0000, &[(57, 0)]);
+    block[12] = 2;
+    // The watchdog extends past the end of the table
+    let mut watchdog = watchdog(0x2a44_0000, 0x2a45_0000, 59, 0);
+    watchdog[1] = 40;
+    let fixture = build_tables(&[gtdt(3, &[block, watchdog])]);
+    let gtdt = fixture.tables.find_table::<Gtdt>().unwrap();
+
+    let block = gtdt.get().gt_blocks().next().unwrap();
+    assert!(block.timers().is_empty());
+    assert_eq!(gtdt.get().watchdogs().count(), 0);
+}
+
+#[test]
+fn test_short_platform_timers() {
+    // A GT block and a watchdog that are too short for their fields, and two bytes that are too
+    // short for a header
+    let mut short_block = gt_block(0x2a83_0000, &[]);
+    short_block.truncate(12);
+    short_block[1] = 12;
+    let mut short_watchdog = watchdog(0x2a44_0000, 0x2a45_0000, 59, 0);
+    short_watchdog.truncate(20);
+    short_watchdog[1] = 20;
+    let fixture = build_tables(&[gtdt(
+        3,
+        &[short_block, short_watchdog, watchdog(0x2a46_0000, 0x2a47_0000, 60, 0), vec![0, 0]],
+    )]);
+    let gtdt = fixture.tables.find_table::<Gtdt>().unwrap();
+
+    let timers: Vec<_> = gtdt.get().platform_timers().collect();
+    assert_eq!(timers.len(), 1);
+    let PlatformTimer::SbsaWatchdog(watchdog) = timers[0] else { panic!("Expected an SBSA watchdog") };
+    assert_eq!({ watchdog.refresh_frame_address }, 0x2a46_0000);
+}
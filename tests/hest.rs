@@ -0,0 +1,54 @@
+use acpi::sdt::hest::{ErrorSource, ErrorSourceFlags, Hest};
+use aml_test_tools::tables::{build_tables, sdt};
+use pci_types::PciAddress;
+
+/// An AER error source of the given type. Root ports have an extra field at the end.
+fn aer_source(typ: u16, source_id: u16, flags: u8, bus: u32, device: u16, function: u16) -> Vec<u8> {
+    let mut source = Vec::new();
+    source.extend_from_slice(&typ.to_le_bytes());
+    source.extend_from_slice(&source_id.to_le_bytes());
+    source.extend_from_slice(&[0, 0, flags, 1]);
+    source.extend_from_slice(&[0; 8]);
+    source.extend_from_slice(&bus.to_le_bytes());
+    source.extend_from_slice(&device.to_le_bytes());
+    source.extend_from_slice(&function.to_le_bytes());
+    source.extend_from_slice(&[0; 20]);
+    if typ == 6 {
+        source.extend_from_slice(&[0; 4]);
+    }
+    source
+}
+
+fn hest(sources: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = (sources.len() as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&sources.concat());
+    sdt(b"HEST", 1, &body)
+}
+
+#[test]
+fn test_aer_sources() {
+    let fixture = build_tables(&[hest(&[
+        aer_source(6, 0, ErrorSourceFlags::GLOBAL.bits(), 0, 0, 0),
+        aer_source(7, 1, 0, 0x0000_0103, 4, 1),
+        // An invalid device number
+        aer_source(7, 2, 0, 0, 0x20, 0),
+        // An invalid function number, which must not be truncated to a valid one
+        aer_source(7, 3, 0, 0, 0, 0x101),
+    ])]);
+    let hest = fixture.tables.find_table::<Hest>().unwrap();
+    assert_eq!(hest.get().error_sources().count(), 4);
+
+    let Some(ErrorSource::AerRootPort(root_port)) = hest.get().error_source(0) else {
+        panic!("Expected an AER root port")
+    };
+    assert!(root_port.enabled());
+    assert!(root_port.pci_address().is_none());
+
+    let address = |source_id| match hest.get().error_source(source_id) {
+        Some(ErrorSource::AerEndpoint(endpoint)) => endpoint.pci_address(),
+        _ => panic!("Expected an AER endpoint"),
+    };
+    assert_eq!(address(1), Some(PciAddress::new(1, 3, 4, 1)));
+    assert_eq!(address(2), None);
+    assert_eq!(address(3), None);
+}
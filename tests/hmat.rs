@@ -0,0 +1,120 @@
+use acpi::{
+    platform::numa::NumaInfo,
+    sdt::hmat::{Hmat, HmatDataType, HmatEntry},
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+fn memory_domain_attributes(flags: u16, initiator: u32, memory: u32) -> Vec<u8> {
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&40u32.to_le_bytes());
+    entry.extend_from_slice(&flags.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&initiator.to_le_bytes());
+    entry.extend_from_slice(&memory.to_le_bytes());
+    entry.extend_from_slice(&[0; 20]);
+    entry
+}
+
+/// A latency and bandwidth entry, where `matrix` has a row for each initiator.
+fn latency_bandwidth(
+    flags: u8,
+    data_type: u8,
+    entry_base_unit: u64,
+    initiators: &[u32],
+    targets: &[u32],
+    matrix: &[&[u16]],
+) -> Vec<u8> {
+    let length = 32 + (initiators.len() + targets.len()) * 4 + initiators.len() * targets.len() * 2;
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&1u16.to_le_bytes());
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&(length as u32).to_le_bytes());
+    entry.extend_from_slice(&[flags, data_type, 0, 0]);
+    entry.extend_from_slice(&(initiators.len() as u32).to_le_bytes());
+    entry.extend_from_slice(&(targets.len() as u32).to_le_bytes());
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&entry_base_unit.to_le_bytes());
+    entry.extend(initiators.iter().chain(targets).flat_map(|domain| domain.to_le_bytes()));
+    entry.extend(matrix.iter().flat_map(|row| row.iter()).flat_map(|value| value.to_le_bytes()));
+    entry
+}
+
+fn hmat(revision: u8, entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = 0u32.to_le_bytes().to_vec();
+    body.extend_from_slice(&entries.concat());
+    sdt(b"HMAT", revision, &body)
+}
+
+#[test]
+fn test_matrix_indexing() {
+    // More targets than initiators, so that swapping the indices would read the wrong entries
+    let fixture = build_tables(&[hmat(
+        2,
+        &[
+            latency_bandwidth(0, 0, 100, &[0, 1], &[0, 1, 2], &[&[10, 20, 30], &[40, 0, 0xffff]]),
+            latency_bandwidth(0, 3, 1, &[0, 1], &[0, 1, 2], &[&[1000, 2000, 3000], &[4000, 5000, 6000]]),
+            // A memory-side cache in front of the memory, rather than the memory itself
+            latency_bandwidth(1, 0, 100, &[0], &[0], &[&[1]]),
+            // The value overflows
+            latency_bandwidth(0, 1, u64::MAX, &[0], &[0], &[&[2]]),
+        ],
+    )]);
+
+    let hmat = fixture.tables.find_table::<Hmat>().unwrap();
+    let Some(HmatEntry::LatencyBandwidthInfo(entry)) = hmat.get().entries().next() else {
+        panic!("Expected a latency and bandwidth entry")
+    };
+    assert_eq!(entry.initiator_proximity_domains().collect::<Vec<_>>(), [0, 1]);
+    assert_eq!(entry.target_proximity_domains().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(entry.value(2, 0, 2), Some(3000));
+    assert_eq!(entry.value(2, 1, 0), Some(4000));
+    assert_eq!(entry.value(2, 2, 0), None);
+    assert_eq!(entry.value(2, 0, 3), None);
+
+    let numa = NumaInfo::new(fixture.tables);
+    let latency = |initiator, target| numa.memory_attribute(initiator, target, HmatDataType::AccessLatency);
+    assert_eq!(latency(0, 0), Some(1000));
+    assert_eq!(latency(0, 1), Some(2000));
+    assert_eq!(latency(1, 0), Some(4000));
+    // Unreachable, and not provided
+    assert_eq!(latency(1, 1), None);
+    assert_eq!(latency(1, 2), None);
+
+    assert_eq!(numa.memory_attribute(1, 2, HmatDataType::AccessBandwidth), Some(6000));
+    assert_eq!(numa.memory_attribute(0, 0, HmatDataType::ReadLatency), None);
+    assert_eq!(numa.latency_bandwidth.len(), 11);
+}
+
+#[test]
+fn test_revision_1() {
+    let fixture = build_tables(&[hmat(
+        1,
+        &[
+            memory_domain_attributes(0b11, 0, 1),
+            // The memory proximity domain is not valid
+            memory_domain_attributes(0b01, 0, 2),
+            latency_bandwidth(0, 0, 1, &[0], &[1], &[&[80]]),
+            latency_bandwidth(0, 3, 1, &[0], &[1], &[&[80]]),
+        ],
+    )]);
+    let numa = NumaInfo::new(fixture.tables);
+
+    assert_eq!(numa.memory_domain_attributes.len(), 1);
+    assert_eq!(numa.memory_domain_attributes[0].memory_proximity_domain, 1);
+    assert_eq!(numa.memory_domain_attributes[0].initiator_proximity_domain, Some(0));
+
+    // Latencies are in nanoseconds, but bandwidths are unchanged
+    assert_eq!(numa.memory_attribute(0, 1, HmatDataType::AccessLatency), Some(80_000));
+    assert_eq!(numa.memory_attribute(0, 1, HmatDataType::AccessBandwidth), Some(80));
+}
+
+#[test]
+fn test_revision_2_memory_domain_attributes() {
+    let fixture = build_tables(&[hmat(2, &[memory_domain_attributes(0, 0, 2)])]);
+    let numa = NumaInfo::new(fixture.tables);
+    assert_eq!(numa.memory_domain_attributes.len(), 1);
+    assert_eq!(numa.memory_domain_attributes[0].memory_proximity_domain, 2);
+    assert_eq!(numa.memory_domain_attributes[0].initiator_proximity_domain, None);
+}
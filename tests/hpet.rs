@@ -0,0 +1,131 @@
+use acpi::{
+    AcpiError,
+    sdt::hpet::{ComparatorMode, ComparatorRouting, HpetInfo, PageProtection},
+};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+
+const GENERAL_CONFIG: usize = 0x10 / 8;
+const GENERAL_INTERRUPT_STATUS: usize = 0x20 / 8;
+
+const fn comparator_config(n: usize) -> usize {
+    (0x100 + 0x20 * n) / 8
+}
+
+const fn comparator_value(n: usize) -> usize {
+    (0x108 + 0x20 * n) / 8
+}
+
+const fn comparator_fsb_route(n: usize) -> usize {
+    (0x110 + 0x20 * n) / 8
+}
+
+/// A simulated HPET register block, with three comparators and a 100MHz main counter. Comparator
+/// `0` is periodic-capable and can be routed to I/O APIC inputs `20..24` or over the FSB. The
+/// others can only be routed to input `2`, and are one-shot only.
+fn registers() -> Box<[u64; 0x80]> {
+    let mut registers = Box::new([0; 0x80]);
+    registers[0] = 10_000_000 << 32 | 0x8086 << 16 | 1 << 15 | 1 << 13 | 2 << 8 | 0x01;
+    registers[comparator_config(0)] = 0x00f0_0000 << 32 | 1 << 15 | 1 << 5 | 1 << 4;
+    registers[comparator_config(1)] = 1 << 34;
+    registers[comparator_config(2)] = 1 << 34;
+    registers
+}
+
+fn hpet(base_address: u64) -> Vec<u8> {
+    let mut body = (0x8086a201u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&gas(0, 64, 0, base_address));
+    body.push(0);
+    body.extend_from_slice(&0x80u16.to_le_bytes());
+    body.push(0x11);
+    sdt(b"HPET", 1, &body)
+}
+
+#[test]
+fn test_hpet_info() {
+    let mut registers = registers();
+    let base = registers.as_mut_ptr();
+    let fixture = build_tables(&[hpet(base as u64)]);
+    let info = HpetInfo::new(&fixture.tables).unwrap();
+
+    assert_eq!(info.hardware_rev, 1);
+    assert_eq!(info.num_comparators, 2);
+    assert!(info.main_counter_is_64bits);
+    assert!(info.legacy_irq_capable);
+    assert_eq!(info.pci_vendor_id, 0x8086);
+    assert_eq!(info.base_address, base as usize);
+    assert_eq!(info.clock_tick_unit, 0x80);
+    assert!(matches!(info.page_protection, PageProtection::Protected4K));
+
+    let hpet = unsafe { info.map(&fixture.handler) };
+    let capabilities = hpet.capabilities();
+    assert_eq!(capabilities.period_fs, 10_000_000);
+    assert_eq!(capabilities.frequency(), Some(100_000_000));
+    assert_eq!(capabilities.vendor_id, 0x8086);
+    assert!(capabilities.legacy_replacement_capable);
+    assert_eq!(capabilities.num_comparators, 3);
+    assert_eq!(capabilities.revision, 1);
+
+    let comparator = hpet.comparator_capabilities(0).unwrap();
+    assert_eq!(comparator.ioapic_routing, 0x00f0_0000);
+    assert!(comparator.fsb_capable && comparator.periodic_capable && comparator.is_64bits);
+    assert!(matches!(hpet.comparator_capabilities(3), Err(AcpiError::HpetComparatorUnsupported(3))));
+}
+
+#[test]
+fn test_general_config() {
+    let mut registers = registers();
+    let base = registers.as_mut_ptr();
+    let fixture = build_tables(&[hpet(base as u64)]);
+    let hpet = unsafe { HpetInfo::new(&fixture.tables).unwrap().map(&fixture.handler) };
+    let read = |index: usize| unsafe { base.add(index).read_volatile() };
+
+    hpet.set_enabled(true);
+    hpet.set_legacy_replacement(true);
+    hpet.set_enabled(false);
+    hpet.write_main_counter(0x1234);
+    assert_eq!(hpet.read_main_counter(), 0x1234);
+    hpet.clear_interrupt(2).unwrap();
+
+    assert_eq!(read(GENERAL_CONFIG), 0b10);
+    assert_eq!(read(GENERAL_INTERRUPT_STATUS), 0b100);
+}
+
+#[test]
+fn test_configure_comparators() {
+    let mut registers = registers();
+    let base = registers.as_mut_ptr();
+    let fixture = build_tables(&[hpet(base as u64)]);
+    let hpet = unsafe { HpetInfo::new(&fixture.tables).unwrap().map(&fixture.handler) };
+    let read = |index: usize| unsafe { base.add(index).read_volatile() };
+
+    /*
+     * The comparator is routed and enabled, and then `ValueSet` is used to write the first
+     * deadline followed by the period. The simulated value register is left holding the period.
+     */
+    let routing = ComparatorRouting::IoApic { input: 21, level_triggered: true };
+    hpet.configure_comparator(0, ComparatorMode::Periodic, routing, 1000, 500).unwrap();
+    assert_eq!(read(comparator_config(0)) & 0xffff_ffff, 1 << 15 | 1 << 6 | 1 << 5 | 1 << 4 | 21 << 9 | 0b1110);
+    assert_eq!(read(comparator_value(0)), 500);
+
+    let routing = ComparatorRouting::Fsb { address: 0xfee0_0000, value: 0x41 };
+    hpet.configure_comparator(0, ComparatorMode::OneShot, routing, 2000, 0).unwrap();
+    assert_eq!(read(comparator_fsb_route(0)), 0xfee0_0000 << 32 | 0x41);
+    let config = read(comparator_config(0));
+    assert_eq!(config & (1 << 14 | 1 << 3 | 1 << 2 | 1 << 1), 1 << 14 | 1 << 2);
+    assert_eq!(read(comparator_value(0)), 2000);
+
+    hpet.disable_comparator(0).unwrap();
+    assert_eq!(read(comparator_config(0)) & (1 << 2), 0);
+
+    // Comparator `1` is not periodic-capable, can't use the FSB, and can only use input `2`
+    let unsupported = |mode, routing| {
+        matches!(hpet.configure_comparator(1, mode, routing, 0, 0), Err(AcpiError::HpetComparatorUnsupported(1)))
+    };
+    let input = |input| ComparatorRouting::IoApic { input, level_triggered: false };
+    assert!(unsupported(ComparatorMode::Periodic, input(2)));
+    assert!(unsupported(ComparatorMode::OneShot, input(3)));
+    assert!(unsupported(ComparatorMode::OneShot, input(40)));
+    assert!(unsupported(ComparatorMode::OneShot, ComparatorRouting::Fsb { address: 0, value: 0 }));
+    hpet.configure_comparator(1, ComparatorMode::OneShot, input(2), 100, 0).unwrap();
+    assert_eq!(read(comparator_config(1)), 1 << 34 | 2 << 9 | 1 << 2);
+}
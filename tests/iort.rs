@@ -0,0 +1,177 @@
+use acpi::sdt::iort::{Iort, IortNode};
+use aml_test_tools::tables::{build_tables, sdt};
+use std::pin::Pin;
+
+const ITS_GROUP: u32 = 48;
+const SMMU: u32 = 72;
+const ROOT_COMPLEX: u32 = 160;
+const LOOPING_SMMU: u32 = 240;
+const LOOPING_ROOT_COMPLEX: u32 = 328;
+
+/// An ID mapping of `num_ids` IDs, starting at `input_base`.
+fn id_mapping(input_base: u32, num_ids: u32, output_base: u32, output_reference: u32) -> Vec<u8> {
+    [input_base, num_ids - 1, output_base, output_reference, 0]
+        .iter()
+        .flat_map(|field| field.to_le_bytes())
+        .collect()
+}
+
+fn node(typ: u8, identifier: u32, body: &[u8], id_mappings: &[Vec<u8>]) -> Vec<u8> {
+    let id_array_offset = 16 + body.len();
+    let length = id_array_offset + id_mappings.len() * 20;
+    let mut node = vec![typ];
+    node.extend_from_slice(&(length as u16).to_le_bytes());
+    node.push(0);
+    node.extend_from_slice(&identifier.to_le_bytes());
+    node.extend_from_slice(&(id_mappings.len() as u32).to_le_bytes());
+    node.extend_from_slice(&(id_array_offset as u32).to_le_bytes());
+    node.extend_from_slice(body);
+    node.extend_from_slice(&id_mappings.concat());
+    node
+}
+
+fn its_group(identifier: u32, its_ids: &[u32]) -> Vec<u8> {
+    let mut body = (its_ids.len() as u32).to_le_bytes().to_vec();
+    body.extend(its_ids.iter().flat_map(|id| id.to_le_bytes()));
+    node(0, identifier, &body, &[])
+}
+
+fn root_complex(identifier: u32, segment: u32, id_mappings: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![0; 24];
+    body[12..16].copy_from_slice(&segment.to_le_bytes());
+    node(2, identifier, &body, id_mappings)
+}
+
+fn smmu_v3(identifier: u32, base_address: u64, id_mappings: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![0; 52];
+    body[0..8].copy_from_slice(&base_address.to_le_bytes());
+    node(4, identifier, &body, id_mappings)
+}
+
+/// An SMMUv3 node whose proximity domain and device ID mapping index are both valid, and which
+/// has `body_length` bytes after the node header.
+fn smmu_v3_revision(
+    identifier: u32,
+    body_length: usize,
+    proximity_domain: u32,
+    id_mappings: &[Vec<u8>],
+) -> Vec<u8> {
+    let mut body = vec![0; 52];
+    body[8..12].copy_from_slice(&0b11000u32.to_le_bytes());
+    body[44..48].copy_from_slice(&proximity_domain.to_le_bytes());
+    body.truncate(body_length);
+    node(4, identifier, &body, id_mappings)
+}
+
+fn pmcg(identifier: u32, page0_base_address: u64, page1_base_address: Option<u64>) -> Vec<u8> {
+    let mut body = page0_base_address.to_le_bytes().to_vec();
+    body.extend_from_slice(&[0; 8]);
+    if let Some(page1_base_address) = page1_base_address {
+        body.extend_from_slice(&page1_base_address.to_le_bytes());
+    }
+    node(5, identifier, &body, &[])
+}
+
+fn iort() -> Vec<u8> {
+    iort_table(&[
+        its_group(0, &[7]),
+        smmu_v3(1, 0x0900_0000, &[id_mapping(0, 0x10000, 0x10000, ITS_GROUP)]),
+        root_complex(2, 0, &[id_mapping(0, 0x100, 0x100, SMMU), id_mapping(0x100, 0x100, 0x2000, ITS_GROUP)]),
+        smmu_v3(3, 0x0a00_0000, &[id_mapping(0, 0x10000, 0, LOOPING_SMMU)]),
+        root_complex(4, 1, &[id_mapping(0, 0x100, 0, LOOPING_SMMU)]),
+    ])
+}
+
+fn iort_table(nodes: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    body.extend_from_slice(&48u32.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&nodes.concat());
+    sdt(b"IORT", 5, &body)
+}
+
+fn offset_of(iort: Pin<&Iort>, node: &IortNode) -> u32 {
+    (node.header() as *const _ as usize - iort.get_ref() as *const Iort as usize) as u32
+}
+
+#[test]
+fn test_node_layout() {
+    let fixture = build_tables(&[iort()]);
+    let iort = fixture.tables.find_table::<Iort>().unwrap();
+    let offsets: Vec<u32> = iort.get().nodes().map(|node| offset_of(iort.get(), &node)).collect();
+    assert_eq!(offsets, [ITS_GROUP, SMMU, ROOT_COMPLEX, LOOPING_SMMU, LOOPING_ROOT_COMPLEX]);
+
+    let Some(IortNode::ItsGroup(its_group)) = iort.get().node_at(ITS_GROUP) else {
+        panic!("Expected an ITS group")
+    };
+    assert_eq!(its_group.its_identifiers().collect::<Vec<_>>(), [7]);
+}
+
+#[test]
+fn test_id_mapping() {
+    let fixture = build_tables(&[iort()]);
+    let iort = fixture.tables.find_table::<Iort>().unwrap();
+
+    // Through the SMMU, and then on to the ITS group
+    let (node, stream_id) = iort.get().pci_stream_id(0, 0x10).unwrap();
+    assert!(matches!(node, IortNode::SmmuV3(_)));
+    assert_eq!(offset_of(iort.get(), &node), SMMU);
+    assert_eq!(stream_id, 0x110);
+    let (its_group, device_id) = iort.get().pci_its_device_id(0, 0x10).unwrap();
+    assert_eq!({ its_group.header.identifier }, 0);
+    assert_eq!(device_id, 0x10110);
+
+    // Bypassing the SMMU
+    assert!(iort.get().pci_stream_id(0, 0x105).is_none());
+    assert_eq!(iort.get().pci_its_device_id(0, 0x105).unwrap().1, 0x2005);
+
+    // Outside of any mapping
+    assert!(iort.get().pci_stream_id(0, 0x200).is_none());
+    assert!(iort.get().pci_its_device_id(0, 0x200).is_none());
+
+    // No root complex for the segment
+    assert!(iort.get().pci_stream_id(2, 0).is_none());
+}
+
+#[test]
+fn test_id_mapping_loop() {
+    let fixture = build_tables(&[iort()]);
+    let iort = fixture.tables.find_table::<Iort>().unwrap();
+
+    let (node, _) = iort.get().pci_stream_id(1, 0x10).unwrap();
+    assert_eq!(offset_of(iort.get(), &node), LOOPING_SMMU);
+    assert!(iort.get().pci_its_device_id(1, 0x10).is_none());
+}
+
+#[test]
+fn test_node_revisions() {
+    let fixture = build_tables(&[iort_table(&[
+        // Nodes of older revisions, which end before their later fields
+        smmu_v3_revision(0, 44, 1, &[]),
+        pmcg(1, 0x0910_0000, None),
+        smmu_v3_revision(2, 48, 2, &[]),
+        smmu_v3_revision(3, 52, 3, &[id_mapping(0, 1, 0x20, ITS_GROUP)]),
+        pmcg(4, 0x0920_0000, Some(0x0921_0000)),
+    ])]);
+    let iort = fixture.tables.find_table::<Iort>().unwrap();
+    let nodes: Vec<_> = iort.get().nodes().collect();
+    assert_eq!(nodes.len(), 5);
+
+    let smmu = |index: usize| match nodes[index] {
+        IortNode::SmmuV3(smmu) => {
+            (smmu.proximity_domain(), smmu.msi_id_mapping().map(|mapping| mapping.output_base))
+        }
+        _ => panic!("Expected an SMMUv3 node"),
+    };
+    assert_eq!(smmu(0), (None, None));
+    assert_eq!(smmu(2), (Some(2), None));
+    assert_eq!(smmu(3), (Some(3), Some(0x20)));
+
+    let pmcg = |index: usize| match nodes[index] {
+        IortNode::Pmcg(pmcg) => ({ pmcg.page0_base_address }, pmcg.page1_base_address()),
+        _ => panic!("Expected a PMCG node"),
+    };
+    assert_eq!(pmcg(1), (0x0910_0000, None));
+    assert_eq!(pmcg(4), (0x0920_0000, Some(0x0921_0000)));
+}
@@ -0,0 +1,93 @@
+use acpi::sdt::ivrs::{DeviceEntry, Ivrs, IvrsEntry, SpecialDeviceVariety};
+use aml_test_tools::tables::{build_tables, sdt};
+use pci_types::PciAddress;
+
+fn ivrs(entries: &[Vec<u8>]) -> Vec<u8> {
+    // 48-bit guest virtual addresses, 52-bit physical addresses, and 64-bit virtual addresses
+    let iv_info: u32 = (64 << 15) | (52 << 8);
+    let mut body = Vec::new();
+    body.extend_from_slice(&iv_info.to_le_bytes());
+    body.extend_from_slice(&[0; 8]);
+    body.extend_from_slice(&entries.concat());
+    sdt(b"IVRS", 2, &body)
+}
+
+fn ivhd(
+    device_id: u16,
+    segment: u16,
+    base_address: u64,
+    device_entries: &[[u8; 4]],
+    special: &[[u8; 8]],
+) -> Vec<u8> {
+    let length = 24 + device_entries.len() * 4 + special.len() * 8;
+    let mut ivhd = vec![0x10, 0];
+    ivhd.extend_from_slice(&(length as u16).to_le_bytes());
+    ivhd.extend_from_slice(&device_id.to_le_bytes());
+    ivhd.extend_from_slice(&0x40u16.to_le_bytes());
+    ivhd.extend_from_slice(&base_address.to_le_bytes());
+    ivhd.extend_from_slice(&segment.to_le_bytes());
+    ivhd.extend_from_slice(&0u16.to_le_bytes());
+    ivhd.extend_from_slice(&0u32.to_le_bytes());
+    for entry in device_entries {
+        ivhd.extend_from_slice(entry);
+    }
+    for entry in special {
+        ivhd.extend_from_slice(entry);
+    }
+    ivhd
+}
+
+#[test]
+fn test_ivhd_device_entries() {
+    let fixture = build_tables(&[ivrs(&[ivhd(
+        0x0002,
+        0,
+        0xfeb8_0000,
+        &[
+            [2, 0x10, 0x00, 0],
+            // Padding entries are skipped
+            [0, 0, 0, 0],
+            [3, 0x00, 0x01, 0],
+            [4, 0xff, 0x01, 0],
+        ],
+        &[[0x48, 0, 0, 0xd7, 0x21, 0xa0, 0x00, 1]],
+    )])]);
+    let ivrs = fixture.tables.find_table::<Ivrs>().unwrap();
+    assert_eq!(ivrs.get().physical_address_size(), 52);
+    assert_eq!(ivrs.get().virtual_address_size(), 64);
+    assert_eq!(ivrs.get().guest_virtual_address_size(), 48);
+
+    let mut entries = ivrs.get().entries();
+    let Some(IvrsEntry::Ivhd10h(ivhd)) = entries.next() else { panic!("Expected an IVHD") };
+    assert!(entries.next().is_none());
+    assert_eq!(ivhd.pci_address(), PciAddress::new(0, 0, 0, 2));
+
+    let device_entries: Vec<_> = ivhd.device_entries().collect();
+    assert_eq!(device_entries.len(), 4);
+    assert!(matches!(device_entries[0], DeviceEntry::Select { device_id: 0x0010, .. }));
+    assert!(matches!(device_entries[1], DeviceEntry::StartOfRange { device_id: 0x0100, .. }));
+    assert!(matches!(device_entries[2], DeviceEntry::EndOfRange { device_id: 0x01ff }));
+    let DeviceEntry::Special { variety, handle, device_id, .. } = device_entries[3] else {
+        panic!("Expected a special device entry")
+    };
+    assert_eq!(variety, SpecialDeviceVariety::IoApic);
+    assert_eq!(handle, 0x21);
+    assert_eq!(device_id, 0x00a0);
+}
+
+#[test]
+fn test_short_entries() {
+    let mut short_ivhd = ivhd(0x0002, 0, 0xfeb8_0000, &[], &[]);
+    short_ivhd[0] = 0x11;
+    let short_ivmd = vec![0x20, 0, 8, 0, 0, 0, 0, 0];
+
+    // The type `11h` block is only as long as a type `10h` block, and the IVMD block is missing
+    // most of its fields. Neither is produced, and the two bytes after the last block are ignored.
+    let fixture =
+        build_tables(&[ivrs(&[short_ivhd, short_ivmd, ivhd(0x0003, 0, 0xfeb9_0000, &[], &[]), vec![0, 0]])]);
+    let ivrs = fixture.tables.find_table::<Ivrs>().unwrap();
+    let entries: Vec<_> = ivrs.get().entries().collect();
+    assert_eq!(entries.len(), 1);
+    let IvrsEntry::Ivhd10h(ivhd) = entries[0] else { panic!("Expected a type 10h IVHD block") };
+    assert_eq!({ ivhd.device_id }, 0x0003);
+}
@@ -0,0 +1,156 @@
+use acpi::{
+    platform::topology::{ProcessorTopology, TopologyLevel},
+    sdt::pptt::{CacheFlags, CacheType, ProcessorHierarchyFlags},
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+const L3: u32 = 36;
+const L2: u32 = 64;
+const L1D: u32 = 92;
+const L1I: u32 = 120;
+const PACKAGE: u32 = 148;
+const CLUSTER: u32 = 172;
+const CORE: u32 = 192;
+
+fn processor_node(
+    flags: ProcessorHierarchyFlags,
+    parent: u32,
+    acpi_processor_id: u32,
+    resources: &[u32],
+) -> Vec<u8> {
+    let length = 20 + resources.len() * 4;
+    let mut node = vec![0, length as u8, 0, 0];
+    node.extend_from_slice(&flags.bits().to_le_bytes());
+    node.extend_from_slice(&parent.to_le_bytes());
+    node.extend_from_slice(&acpi_processor_id.to_le_bytes());
+    node.extend_from_slice(&(resources.len() as u32).to_le_bytes());
+    node.extend(resources.iter().flat_map(|resource| resource.to_le_bytes()));
+    node
+}
+
+/// A unified cache, or a data cache if `data` is set. The cache ID is only included if `cache_id`
+/// is `Some`.
+fn cache(next_level: u32, size: u32, data: bool, cache_id: Option<u32>) -> Vec<u8> {
+    let mut flags = CacheFlags::SIZE_VALID | CacheFlags::CACHE_TYPE_VALID;
+    if cache_id.is_some() {
+        flags |= CacheFlags::CACHE_ID_VALID;
+    }
+    let attributes: u8 = if data { 0 } else { 0b1000 };
+    let length = if cache_id.is_some() { 28 } else { 24 };
+
+    let mut cache = vec![1, length, 0, 0];
+    cache.extend_from_slice(&flags.bits().to_le_bytes());
+    cache.extend_from_slice(&next_level.to_le_bytes());
+    cache.extend_from_slice(&size.to_le_bytes());
+    cache.extend_from_slice(&0u32.to_le_bytes());
+    cache.extend_from_slice(&[0, attributes]);
+    cache.extend_from_slice(&64u16.to_le_bytes());
+    if let Some(cache_id) = cache_id {
+        cache.extend_from_slice(&cache_id.to_le_bytes());
+    }
+    cache
+}
+
+fn pptt(entries: &[Vec<u8>]) -> Vec<u8> {
+    sdt(b"PPTT", 3, &entries.concat())
+}
+
+/// A package containing a single cluster of one core with two threads. The core has its own L1
+/// caches, which lead to an L2 and then to the L3 private to the package. Leaf nodes are not
+/// marked, as in older tables.
+fn two_thread_pptt() -> Vec<u8> {
+    let thread = ProcessorHierarchyFlags::PROCESSOR_IS_A_THREAD | ProcessorHierarchyFlags::ACPI_PROCESSOR_ID_VALID;
+    pptt(&[
+        cache(0, 0x0080_0000, false, Some(3)),
+        cache(L3, 0x0010_0000, false, Some(2)),
+        cache(L2, 0x8000, true, Some(1)),
+        cache(L2, 0x8000, false, Some(4)),
+        processor_node(
+            ProcessorHierarchyFlags::PHYSICAL_PACKAGE | ProcessorHierarchyFlags::ACPI_PROCESSOR_ID_VALID,
+            0,
+            0,
+            &[L3],
+        ),
+        processor_node(ProcessorHierarchyFlags::empty(), PACKAGE, 0, &[]),
+        processor_node(ProcessorHierarchyFlags::IDENTICAL_IMPLEMENTATION, CLUSTER, 0, &[L1D, L1I]),
+        processor_node(thread, CORE, 0, &[]),
+        processor_node(thread, CORE, 1, &[]),
+    ])
+}
+
+#[test]
+fn test_topology() {
+    let fixture = build_tables(&[two_thread_pptt()]);
+    let topology = ProcessorTopology::new(&fixture.tables).unwrap();
+
+    let levels: Vec<_> = topology.nodes.iter().map(|node| node.level).collect();
+    assert_eq!(
+        levels,
+        [
+            TopologyLevel::Package,
+            TopologyLevel::Cluster,
+            TopologyLevel::Core,
+            TopologyLevel::Thread,
+            TopologyLevel::Thread
+        ]
+    );
+    let leaves: Vec<_> = topology.nodes.iter().map(|node| node.is_leaf).collect();
+    assert_eq!(leaves, [false, false, false, true, true]);
+    assert!(topology.nodes[2].identical_implementation);
+
+    let thread = topology.processor(1).unwrap();
+    assert_eq!(thread, 4);
+    assert_eq!(topology.ancestors(thread).count(), 3);
+    assert_eq!(topology.ancestor_at(thread, TopologyLevel::Package).unwrap().acpi_processor_id, Some(0));
+    assert_eq!(topology.ancestor_at(thread, TopologyLevel::Cluster).unwrap().parent, Some(0));
+    assert!(topology.processor(2).is_none());
+}
+
+#[test]
+fn test_cache_levels() {
+    let fixture = build_tables(&[two_thread_pptt()]);
+    let topology = ProcessorTopology::new(&fixture.tables).unwrap();
+
+    let levels: Vec<_> = topology.caches.iter().map(|cache| (cache.cache_id, cache.level)).collect();
+    assert_eq!(levels, [(Some(3), 3), (Some(2), 2), (Some(1), 1), (Some(4), 1)]);
+    assert_eq!(topology.caches[2].cache_type, Some(CacheType::Data));
+    assert_eq!(topology.caches[2].size, Some(0x8000));
+    assert_eq!(topology.caches[2].line_size, None);
+    assert_eq!(topology.caches[1].next_level, Some(0));
+
+    // The L2 and L3 can be reached from both L1 caches, and the L3 also from the package, but are
+    // only produced once
+    let caches: Vec<_> = topology.processor_caches(0).map(|cache| cache.cache_id).collect();
+    assert_eq!(caches, [Some(1), Some(2), Some(3), Some(4)]);
+    assert_eq!(topology.processor_caches(2).count(), 0);
+}
+
+#[test]
+fn test_loops() {
+    const CACHE_A: u32 = 36;
+    const CACHE_B: u32 = 60;
+    const NODE_A: u32 = 84;
+    const NODE_B: u32 = 108;
+
+    let fixture = build_tables(&[pptt(&[
+        cache(CACHE_B, 0x8000, true, None),
+        cache(CACHE_A, 0x8000, false, None),
+        processor_node(ProcessorHierarchyFlags::empty(), NODE_B, 0, &[CACHE_A]),
+        processor_node(ProcessorHierarchyFlags::empty(), NODE_A, 0, &[]),
+        processor_node(
+            ProcessorHierarchyFlags::NODE_IS_A_LEAF | ProcessorHierarchyFlags::ACPI_PROCESSOR_ID_VALID,
+            NODE_A,
+            7,
+            &[],
+        ),
+    ])]);
+    let topology = ProcessorTopology::new(&fixture.tables).unwrap();
+
+    let processor = topology.processor(7).unwrap();
+    assert_eq!(topology.ancestors(processor).count(), topology.nodes.len());
+    assert!(topology.ancestor_at(processor, TopologyLevel::Package).is_none());
+
+    let levels: Vec<_> = topology.caches.iter().map(|cache| cache.level).collect();
+    assert_eq!(levels, [1, 2]);
+    assert_eq!(topology.processor_caches(7).count(), 2);
+}
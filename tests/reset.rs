@@ -0,0 +1,78 @@
+use acpi::{AcpiError, platform::AcpiPlatform};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+use pci_types::PciAddress;
+
+/// The `RESET_REG_SUP` bit of the FADT flags.
+const RESET_REG_SUP: u32 = 1 << 10;
+
+/// Build an FADT with PM1 register blocks in I/O space, and the given flags and reset register.
+fn fadt(flags: u32, reset_register: &[u8], reset_value: u8) -> Vec<u8> {
+    let mut body = vec![0; 276 - 36];
+    let mut set =
+        |offset: usize, bytes: &[u8]| body[(offset - 36)..(offset - 36 + bytes.len())].copy_from_slice(bytes);
+    set(56, &0x400u32.to_le_bytes());
+    set(64, &0x404u32.to_le_bytes());
+    set(88, &[4, 2]);
+    set(112, &flags.to_le_bytes());
+    set(116, reset_register);
+    set(128, &[reset_value]);
+    sdt(b"FACP", 6, &body)
+}
+
+fn madt() -> Vec<u8> {
+    sdt(b"APIC", 5, &[0; 8])
+}
+
+#[test]
+fn test_reset_io() {
+    let fixture = build_tables(&[fadt(RESET_REG_SUP, &gas(1, 8, 1, 0xcf9), 0x06), madt()]);
+    let handler = fixture.handler.clone();
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+
+    handler.take_io_writes();
+    platform.reset().unwrap();
+    assert_eq!(handler.take_io_writes(), [(0xcf9, 0x06)]);
+}
+
+#[test]
+fn test_reset_pci() {
+    // Device `0x1f`, function `3`, register `0x44`
+    let address = 0x1f << 32 | 3 << 16 | 0x44;
+    let fixture = build_tables(&[fadt(RESET_REG_SUP, &gas(2, 8, 1, address), 0x0e), madt()]);
+    let handler = fixture.handler.clone();
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+
+    platform.reset().unwrap();
+    assert_eq!(handler.take_pci_writes(), [(PciAddress::new(0, 0, 0x1f, 3), 0x44, 0x0e)]);
+}
+
+#[test]
+fn test_reset_memory() {
+    let mut register = Box::new(0u8);
+    let address = &mut *register as *mut u8 as u64;
+    let fixture = build_tables(&[fadt(RESET_REG_SUP, &gas(0, 8, 1, address), 0xa5), madt()]);
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+
+    platform.reset().unwrap();
+    assert_eq!(unsafe { (address as *const u8).read_volatile() }, 0xa5);
+}
+
+#[test]
+fn test_reset_unsupported() {
+    // The reset register is valid, but the flag is not set
+    let fixture = build_tables(&[fadt(0, &gas(1, 8, 1, 0xcf9), 0x06), madt()]);
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+    assert!(matches!(platform.reset(), Err(AcpiError::ResetUnsupported)));
+
+    // The reset register is in the embedded controller's address space
+    let fixture = build_tables(&[fadt(RESET_REG_SUP, &gas(3, 8, 1, 0x10), 0x06), madt()]);
+    let handler = fixture.handler.clone();
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+    assert!(matches!(platform.reset(), Err(AcpiError::ResetUnsupported)));
+
+    // The reset register is not provided
+    let fixture = build_tables(&[fadt(RESET_REG_SUP, &gas(1, 8, 1, 0), 0x06), madt()]);
+    let platform = AcpiPlatform::new(fixture.tables, fixture.handler).unwrap();
+    assert!(matches!(platform.reset(), Err(AcpiError::ResetUnsupported)));
+    assert_eq!(handler.take_io_writes(), []);
+}
@@ -0,0 +1,207 @@
+use acpi::{
+    AcpiError,
+    platform::{InterruptModel, ProcessorState},
+    sdt::{
+        madt::MadtError,
+        rhct::{MmuType, Rhct, RhctNode},
+    },
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+fn rintc(flags: u32, hart_id: u64, acpi_processor_uid: u32, imsic_base_address: u64) -> Vec<u8> {
+    let mut entry = vec![0x18, 36, 1, 0];
+    entry.extend_from_slice(&flags.to_le_bytes());
+    entry.extend_from_slice(&hart_id.to_le_bytes());
+    entry.extend_from_slice(&acpi_processor_uid.to_le_bytes());
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&imsic_base_address.to_le_bytes());
+    entry.extend_from_slice(&0x1000u32.to_le_bytes());
+    entry
+}
+
+fn imsic() -> Vec<u8> {
+    let mut entry = vec![0x19, 16, 1, 0];
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&255u16.to_le_bytes());
+    entry.extend_from_slice(&127u16.to_le_bytes());
+    entry.extend_from_slice(&[2, 3, 1, 24]);
+    entry
+}
+
+fn aplic(id: u8, num_sources: u16, global_system_interrupt_base: u32, address: u64) -> Vec<u8> {
+    let mut entry = vec![0x1a, 36, 1, id];
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(b"APLIC\0\0\0");
+    entry.extend_from_slice(&0u16.to_le_bytes());
+    entry.extend_from_slice(&num_sources.to_le_bytes());
+    entry.extend_from_slice(&global_system_interrupt_base.to_le_bytes());
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&0x8000u32.to_le_bytes());
+    entry
+}
+
+fn plic(id: u8, num_irqs: u16, global_system_interrupt_base: u32, address: u64) -> Vec<u8> {
+    let mut entry = vec![0x1b, 36, 1, id];
+    entry.extend_from_slice(b"PLIC\0\0\0\0");
+    entry.extend_from_slice(&num_irqs.to_le_bytes());
+    entry.extend_from_slice(&7u16.to_le_bytes());
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry.extend_from_slice(&0x400_0000u32.to_le_bytes());
+    entry.extend_from_slice(&address.to_le_bytes());
+    entry.extend_from_slice(&global_system_interrupt_base.to_le_bytes());
+    entry
+}
+
+fn madt(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![0; 8];
+    body.extend_from_slice(&entries.concat());
+    sdt(b"APIC", 7, &body)
+}
+
+fn isa_string_node(isa: &str) -> Vec<u8> {
+    let padded_length = (isa.len() + 1).next_multiple_of(2);
+    let mut node = 0u16.to_le_bytes().to_vec();
+    node.extend_from_slice(&(8 + padded_length as u16).to_le_bytes());
+    node.extend_from_slice(&1u16.to_le_bytes());
+    node.extend_from_slice(&(padded_length as u16).to_le_bytes());
+    node.extend_from_slice(isa.as_bytes());
+    node.resize(8 + padded_length, 0);
+    node
+}
+
+fn cmo_node(cbom: u8, cbop: u8, cboz: u8) -> Vec<u8> {
+    let mut node = 1u16.to_le_bytes().to_vec();
+    node.extend_from_slice(&10u16.to_le_bytes());
+    node.extend_from_slice(&1u16.to_le_bytes());
+    node.extend_from_slice(&[0, cbom, cbop, cboz]);
+    node
+}
+
+fn mmu_node(mmu_type: u8) -> Vec<u8> {
+    let mut node = 2u16.to_le_bytes().to_vec();
+    node.extend_from_slice(&8u16.to_le_bytes());
+    node.extend_from_slice(&1u16.to_le_bytes());
+    node.extend_from_slice(&[0, mmu_type]);
+    node
+}
+
+fn hart_info_node(acpi_processor_uid: u32, offsets: &[u32]) -> Vec<u8> {
+    let mut node = 0xffffu16.to_le_bytes().to_vec();
+    node.extend_from_slice(&(12 + offsets.len() as u16 * 4).to_le_bytes());
+    node.extend_from_slice(&1u16.to_le_bytes());
+    node.extend_from_slice(&(offsets.len() as u16).to_le_bytes());
+    node.extend_from_slice(&acpi_processor_uid.to_le_bytes());
+    node.extend(offsets.iter().flat_map(|offset| offset.to_le_bytes()));
+    node
+}
+
+fn rhct(flags: u32, nodes: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = flags.to_le_bytes().to_vec();
+    body.extend_from_slice(&10_000_000u64.to_le_bytes());
+    body.extend_from_slice(&(nodes.len() as u32).to_le_bytes());
+    body.extend_from_slice(&56u32.to_le_bytes());
+    body.extend_from_slice(&nodes.concat());
+    sdt(b"RHCT", 1, &body)
+}
+
+#[test]
+fn test_aia_model() {
+    let fixture = build_tables(&[madt(&[
+        rintc(0b01, 0, 0, 0x2800_0000),
+        // This hart is disabled, but can be brought online later
+        rintc(0b10, 1, 1, 0x2800_1000),
+        rintc(0b01, 2, 2, 0x2800_2000),
+        imsic(),
+        aplic(0, 96, 0, 0x0d00_0000),
+        aplic(1, 32, 96, 0x0e00_0000),
+    ])]);
+    let (model, processor_info) = InterruptModel::new(&fixture.tables).unwrap();
+
+    let InterruptModel::Riscv(riscv) = model else { panic!("Expected a RISC-V interrupt model") };
+    let imsic = riscv.imsic.unwrap();
+    assert_eq!((imsic.num_ids, imsic.num_guest_ids), (255, 127));
+    assert_eq!((imsic.guest_index_bits, imsic.hart_index_bits), (2, 3));
+    assert_eq!((imsic.group_index_bits, imsic.group_index_shift), (1, 24));
+
+    let aplics: Vec<_> = riscv.aplics.iter().map(|aplic| (aplic.id, aplic.global_system_interrupt_base)).collect();
+    assert_eq!(aplics, [(0, 0), (1, 96)]);
+    assert_eq!(&riscv.aplics[0].hardware_id, b"APLIC\0\0\0");
+    assert_eq!(riscv.aplics[1].address, 0x0e00_0000);
+    assert!(riscv.plics.is_empty());
+
+    let processor_info = processor_info.unwrap();
+    let boot_hart = processor_info.boot_processor;
+    assert_eq!(boot_hart.state, ProcessorState::Running);
+    assert_eq!(boot_hart.local_apic_id, 0);
+    assert_eq!(boot_hart.gicc, None);
+    assert_eq!(boot_hart.rintc.unwrap().imsic_base_address, 0x2800_0000);
+
+    let harts: Vec<_> = processor_info
+        .application_processors
+        .iter()
+        .map(|hart| (hart.rintc.unwrap().hart_id, hart.state, hart.rintc.unwrap().online_capable))
+        .collect();
+    assert_eq!(harts, [(1, ProcessorState::Disabled, true), (2, ProcessorState::Parked, false)]);
+}
+
+#[test]
+fn test_plic_model() {
+    let fixture = build_tables(&[madt(&[rintc(0b01, 0, 0, 0), plic(0, 53, 0, 0x0c00_0000)])]);
+    let (model, _) = InterruptModel::new(&fixture.tables).unwrap();
+
+    let InterruptModel::Riscv(riscv) = model else { panic!("Expected a RISC-V interrupt model") };
+    assert!(riscv.imsic.is_none());
+    assert!(riscv.aplics.is_empty());
+    assert_eq!(riscv.plics.len(), 1);
+    assert_eq!((riscv.plics[0].num_irqs, riscv.plics[0].max_priority), (53, 7));
+    assert_eq!((riscv.plics[0].address, riscv.plics[0].size), (0x0c00_0000, 0x400_0000));
+}
+
+#[test]
+fn test_invalid_riscv_model() {
+    // A RISC-V interrupt controller can't be mixed with GIC entries
+    let gicd = vec![0xc, 24, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0];
+    let fixture = build_tables(&[madt(&[rintc(0b01, 0, 0, 0), gicd])]);
+    assert!(matches!(
+        InterruptModel::new(&fixture.tables),
+        Err(AcpiError::InvalidMadt(MadtError::UnexpectedEntry))
+    ));
+}
+
+#[test]
+fn test_rhct() {
+    const ISA: u32 = 56;
+    const CMO: u32 = 82;
+    const MMU: u32 = 92;
+
+    let fixture = build_tables(&[rhct(
+        1,
+        &[
+            isa_string_node("rv64imafdc_zicbom"),
+            cmo_node(6, 0, 6),
+            mmu_node(1),
+            hart_info_node(0, &[ISA, CMO, MMU]),
+            // This hart refers to a node past the end of the table
+            hart_info_node(1, &[ISA, 0x1000]),
+        ],
+    )]);
+    let rhct = fixture.tables.find_table::<Rhct>().unwrap();
+    let rhct = rhct.get();
+    assert!(rhct.timer_cannot_wake_cpu());
+    assert_eq!({ rhct.time_base_frequency }, 10_000_000);
+    assert_eq!(rhct.nodes().count(), 5);
+
+    let capabilities: Vec<_> = rhct.hart_capabilities(0).collect();
+    assert_eq!(capabilities.len(), 3);
+    let RhctNode::IsaString(isa) = capabilities[0] else { panic!("Expected an ISA string node") };
+    assert_eq!(isa.isa(), Some("rv64imafdc_zicbom"));
+    let RhctNode::Cmo(cmo) = capabilities[1] else { panic!("Expected a CMO node") };
+    assert_eq!(cmo.zicbom_block_size(), Some(64));
+    assert_eq!(cmo.zicbop_block_size(), None);
+    assert_eq!(cmo.zicboz_block_size(), Some(64));
+    let RhctNode::Mmu(mmu) = capabilities[2] else { panic!("Expected an MMU node") };
+    assert_eq!(mmu.mmu_type(), MmuType::Sv48);
+
+    assert_eq!(rhct.hart_capabilities(1).count(), 1);
+    assert_eq!(rhct.hart_capabilities(2).count(), 0);
+}
@@ -0,0 +1,189 @@
+use acpi::{
+    AcpiError,
+    Handler,
+    address::{AddressSpace, GenericAddress, MappedGas},
+    aml::{
+        Interpreter,
+        sleep::{SleepState, SleepType},
+    },
+    registers::FixedRegisters,
+    sdt::facs::{Facs, OspmFlags, WakingVector},
+};
+use aml_test_tools::{TestResult, handlers::simulated_handler::SimulatedHandler, run_test_for_string};
+use std::sync::Arc;
+
+const PM1A_EVENT: u16 = SimulatedHandler::PM1A_EVENT_PORT;
+const PM1A_CONTROL: u16 = SimulatedHandler::PM1A_CONTROL_PORT;
+const PM1B_CONTROL: u16 = SimulatedHandler::PM1B_CONTROL_PORT;
+const DEBUG_PORT: u16 = 0x80;
+
+/// Each sleep method writes `0xN0 | Arg0` to the debug port, where `N` identifies the method.
+const AML: &str = r#"DefinitionBlock("", "DSDT", 2, "RSACPI", "SLEEP", 1) {
+    OperationRegion(DBG, SystemIO, 0x80, 1)
+    Field(DBG, ByteAcc, NoLock, Preserve) {
+        DBGB, 8
+    }
+
+    Name(\_S0, Package() { 0x01, 0x02, 0x00, 0x00 })
+    Name(\_S3, Package() { 0x05, 0x03, 0x00, 0x00 })
+    Name(\_S4, Package() { 0x0605 })
+    Name(\_S5, Package() { 0x07, 0x07, 0x00, 0x00 })
+
+    Method(\_TTS, 1, NotSerialized) {
+        DBGB = 0x10 | Arg0
+    }
+
+    Method(\_PTS, 1, NotSerialized) {
+        DBGB = 0x20 | Arg0
+    }
+
+    Method(\_GTS, 1, NotSerialized) {
+        DBGB = 0x30 | Arg0
+    }
+
+    Method(\_BFS, 1, NotSerialized) {
+        DBGB = 0x40 | Arg0
+    }
+
+    Method(\_WAK, 1, NotSerialized) {
+        DBGB = 0x50 | Arg0
+    }
+}
+"#;
+
+/// Create an interpreter with the given registers. The `GPE0` block is removed, so that arming
+/// and disarming wake GPEs does not access any registers.
+fn sleep_interpreter(
+    handler: &SimulatedHandler,
+    mut registers: FixedRegisters<SimulatedHandler>,
+) -> Interpreter<SimulatedHandler> {
+    registers.gpe0 = None;
+    #[allow(clippy::arc_with_non_send_sync)]
+    let mut interpreter = Interpreter::new(handler.clone(), 2, Arc::new(registers), None);
+    assert_eq!(run_test_for_string(AML, &mut interpreter), TestResult::Pass);
+    interpreter.initialize_gpes().unwrap();
+    handler.take_io_writes();
+    interpreter
+}
+
+fn slp_typ(pm1_control: u32) -> u32 {
+    (pm1_control >> 10) & 0b111
+}
+
+fn debug_writes(writes: &[(u16, u32)]) -> Vec<u32> {
+    writes.iter().filter(|(port, _)| *port == DEBUG_PORT).map(|(_, value)| *value).collect()
+}
+
+#[test]
+fn test_sleep_types() {
+    let handler = SimulatedHandler::new();
+    let interpreter = sleep_interpreter(&handler, handler.fixed_registers());
+
+    assert_eq!(interpreter.sleep_type(SleepState::S3).unwrap(), SleepType { slp_typa: 5, slp_typb: 3 });
+    // Older firmware packs both values into a single integer
+    assert_eq!(interpreter.sleep_type(SleepState::S4).unwrap(), SleepType { slp_typa: 5, slp_typb: 6 });
+    assert!(matches!(interpreter.sleep_type(SleepState::S1), Err(AcpiError::SleepStateUnsupported(1))));
+
+    // Unsupported states are rejected before the firmware is told about them
+    assert!(interpreter.prepare_for_sleep(SleepState::S1).is_err());
+    assert_eq!(handler.take_io_writes(), []);
+}
+
+#[test]
+fn test_sleep_and_wake() {
+    let handler = SimulatedHandler::new();
+    let interpreter = sleep_interpreter(&handler, handler.fixed_registers());
+    handler.set_io_port(PM1A_CONTROL, 0x0001);
+    handler.set_io_port(PM1B_CONTROL, 0x0001);
+
+    interpreter.prepare_for_sleep(SleepState::S3).unwrap();
+    assert_eq!(debug_writes(&handler.take_io_writes()), [0x13, 0x23]);
+
+    /*
+     * The wake status is cleared, and then `SLP_TYP` is written to each control register before
+     * `SLP_EN`. Other bits of the control registers are preserved. The simulated wake status is
+     * not cleared by the write, so this returns immediately, as if the system had woken.
+     */
+    interpreter.enter_sleep_state(SleepState::S3).unwrap();
+    assert_eq!(
+        handler.take_io_writes(),
+        [
+            (DEBUG_PORT, 0x33),
+            (PM1A_EVENT, 1 << 15),
+            (PM1A_CONTROL, 5 << 10 | 1),
+            (PM1B_CONTROL, 3 << 10 | 1),
+            (PM1A_CONTROL, 1 << 13 | 5 << 10 | 1),
+            (PM1B_CONTROL, 1 << 13 | 3 << 10 | 1),
+        ]
+    );
+
+    interpreter.leave_sleep_state(SleepState::S3).unwrap();
+    let writes = handler.take_io_writes();
+    assert_eq!(debug_writes(&writes), [0x43, 0x53, 0x10]);
+    // The `S0` sleep type is restored, with the separate values for `PM1a` and `PM1b`
+    assert_eq!(slp_typ(handler.io_port(PM1A_CONTROL)), 1);
+    assert_eq!(slp_typ(handler.io_port(PM1B_CONTROL)), 2);
+}
+
+#[test]
+fn test_hardware_reduced_sleep() {
+    const SLEEP_CONTROL: u16 = 0x408;
+    const SLEEP_STATUS: u16 = 0x409;
+
+    let handler = SimulatedHandler::new();
+    let mut registers = handler.fixed_registers();
+    let register = |address: u16| GenericAddress {
+        address_space: AddressSpace::SystemIo,
+        bit_width: 8,
+        bit_offset: 0,
+        access_size: 1,
+        address: address as u64,
+    };
+    registers.sleep_control = Some(unsafe { MappedGas::map_gas(register(SLEEP_CONTROL), &handler).unwrap() });
+    registers.sleep_status = Some(unsafe { MappedGas::map_gas(register(SLEEP_STATUS), &handler).unwrap() });
+    let interpreter = sleep_interpreter(&handler, registers);
+
+    interpreter.enter_sleep_state(SleepState::S5).unwrap();
+    assert_eq!(
+        handler.take_io_writes(),
+        [(DEBUG_PORT, 0x35), (SLEEP_STATUS, 1 << 7), (SLEEP_CONTROL, 1 << 5 | 7 << 2)]
+    );
+
+    // The PM1 control registers are not used, so the `S0` sleep type is not restored
+    interpreter.leave_sleep_state(SleepState::S5).unwrap();
+    assert_eq!(debug_writes(&handler.take_io_writes()), [0x45, 0x55, 0x10]);
+    assert_eq!(handler.io_port(PM1A_CONTROL), 0);
+}
+
+#[test]
+fn test_waking_vector() {
+    // A version 2 FACS, on a platform that supports waking in 64-bit mode
+    let mut facs = [0u64; 8];
+    let bytes = unsafe { std::slice::from_raw_parts_mut(facs.as_mut_ptr() as *mut u8, 64) };
+    bytes[0..4].copy_from_slice(b"FACS");
+    bytes[4..8].copy_from_slice(&64u32.to_le_bytes());
+    bytes[20..24].copy_from_slice(&0b10u32.to_le_bytes());
+    bytes[32] = 2;
+
+    let handler = SimulatedHandler::new();
+    let mapping = unsafe { handler.map_physical_region::<Facs>(facs.as_ptr() as usize, 64) };
+    #[allow(clippy::arc_with_non_send_sync)]
+    let interpreter = Interpreter::new(handler.clone(), 2, Arc::new(handler.fixed_registers()), Some(mapping));
+    let facs = || interpreter.facs().unwrap();
+
+    interpreter.set_waking_vector(WakingVector::RealMode(0x9_f000)).unwrap();
+    assert_eq!(facs().firmware_waking_vector(), 0x9_f000);
+    assert_eq!(facs().x_firmware_waking_vector(), 0);
+    assert_eq!(facs().waking_vector(), Some(WakingVector::RealMode(0x9_f000)));
+
+    interpreter.set_waking_vector(WakingVector::LongMode(0x1_2345_6000)).unwrap();
+    assert_eq!(facs().firmware_waking_vector(), 0);
+    assert_eq!(facs().x_firmware_waking_vector(), 0x1_2345_6000);
+    assert!(facs().ospm_flags().contains(OspmFlags::WAKE_64BIT));
+    assert_eq!(facs().waking_vector(), Some(WakingVector::LongMode(0x1_2345_6000)));
+
+    assert!(matches!(
+        interpreter.set_waking_vector(WakingVector::RealMode(0x10_0000)),
+        Err(AcpiError::UnsupportedWakingVector(_))
+    ));
+}
@@ -0,0 +1,108 @@
+use acpi::sdt::{
+    tcpa::Tcpa,
+    tpm2::{TcgPlatformClass, Tpm2, Tpm2PlatformParameters, Tpm2StartMethod},
+};
+use aml_test_tools::tables::{build_tables, sdt};
+
+/// Build a TPM2 with the given start method and parameters. The log area fields are only
+/// included if `log_area` is given.
+fn tpm2_table(start_method: u32, parameters: &[u8], log_area: Option<(u32, u64)>) -> Vec<u8> {
+    let mut body = 0u16.to_le_bytes().to_vec();
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&0xfed4_0040u64.to_le_bytes());
+    body.extend_from_slice(&start_method.to_le_bytes());
+    body.extend_from_slice(parameters);
+    if let Some((minimum_length, start_address)) = log_area {
+        body.resize(16 + 12, 0);
+        body.extend_from_slice(&minimum_length.to_le_bytes());
+        body.extend_from_slice(&start_address.to_le_bytes());
+    }
+    sdt(b"TPM2", if log_area.is_some() { 4 } else { 3 }, &body)
+}
+
+fn tcpa_client(minimum_length: u32, start_address: u64) -> Vec<u8> {
+    let mut body = 0u16.to_le_bytes().to_vec();
+    body.extend_from_slice(&minimum_length.to_le_bytes());
+    body.extend_from_slice(&start_address.to_le_bytes());
+    sdt(b"TCPA", 2, &body)
+}
+
+fn tcpa_server(platform_class: u16, minimum_length: u64, start_address: u64) -> Vec<u8> {
+    let mut body = platform_class.to_le_bytes().to_vec();
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&minimum_length.to_le_bytes());
+    body.extend_from_slice(&start_address.to_le_bytes());
+    body.extend_from_slice(&[0; 44]);
+    sdt(b"TCPA", 2, &body)
+}
+
+#[test]
+fn test_tpm2() {
+    let fixture = build_tables(&[tpm2_table(7, &[], None)]);
+    let tpm2 = fixture.tables.find_table::<Tpm2>().unwrap();
+    assert_eq!(tpm2.get().platform_class(), TcgPlatformClass::Client);
+    assert_eq!(tpm2.get().start_method(), Tpm2StartMethod::CommandResponseBuffer);
+    assert_eq!({ tpm2.get().control_area_address }, 0xfed4_0040);
+    assert!(matches!(tpm2.get().platform_parameters(), Tpm2PlatformParameters::None));
+    assert!(tpm2.get().log_area().is_none());
+
+    let mut parameters = 0x20u32.to_le_bytes().to_vec();
+    parameters.extend_from_slice(&[1, 0, 0, 0]);
+    parameters.extend_from_slice(&0xc400_0123u32.to_le_bytes());
+    let fixture = build_tables(&[tpm2_table(11, &parameters, None)]);
+    let tpm2 = fixture.tables.find_table::<Tpm2>().unwrap();
+    assert_eq!(tpm2.get().start_method(), Tpm2StartMethod::CommandResponseBufferWithArmSmc);
+    let Tpm2PlatformParameters::ArmSmc(smc) = tpm2.get().platform_parameters() else {
+        panic!("Expected Arm SMC parameters")
+    };
+    assert_eq!(({ smc.interrupt }, smc.interrupt_flags), (0x20, 1));
+    assert_eq!({ smc.smc_function_id }, 0xc400_0123);
+
+    // The parameters of other start methods are not decoded
+    let fixture = build_tables(&[tpm2_table(2, &[0xaa; 4], None)]);
+    let tpm2 = fixture.tables.find_table::<Tpm2>().unwrap();
+    assert_eq!(tpm2.get().start_method(), Tpm2StartMethod::Acpi);
+    assert!(matches!(tpm2.get().platform_parameters(), Tpm2PlatformParameters::Other([0xaa, 0xaa, 0xaa, 0xaa])));
+}
+
+#[test]
+fn test_tpm2_event_log() {
+    let mut log = Box::new([0u8; 64]);
+    log[0..4].copy_from_slice(b"Spec");
+    let start_address = log.as_mut_ptr() as u64;
+
+    let fixture = build_tables(&[tpm2_table(7, &[], Some((64, start_address)))]);
+    let tpm2 = fixture.tables.find_table::<Tpm2>().unwrap();
+    let log_area = tpm2.get().log_area().unwrap();
+    assert_eq!(log_area.start_address, start_address);
+    assert_eq!(log_area.minimum_length, 64);
+
+    let event_log = unsafe { log_area.map(&fixture.handler) };
+    assert_eq!(event_log.bytes().len(), 64);
+    assert_eq!(&event_log.bytes()[0..4], b"Spec");
+
+    // The firmware hasn't placed an event log
+    let fixture = build_tables(&[tpm2_table(7, &[], Some((0, 0)))]);
+    let tpm2 = fixture.tables.find_table::<Tpm2>().unwrap();
+    assert!(tpm2.get().log_area().is_none());
+}
+
+#[test]
+fn test_tcpa_event_log() {
+    let fixture = build_tables(&[tcpa_client(0x1_0000, 0x7f00_0000)]);
+    let tcpa = fixture.tables.find_table::<Tcpa>().unwrap();
+    assert_eq!(tcpa.get().platform_class(), TcgPlatformClass::Client);
+    let log_area = tcpa.get().log_area().unwrap();
+    assert_eq!((log_area.start_address, log_area.minimum_length), (0x7f00_0000, 0x1_0000));
+
+    let fixture = build_tables(&[tcpa_server(1, 0x2_0000_0000, 0x1_7f00_0000)]);
+    let tcpa = fixture.tables.find_table::<Tcpa>().unwrap();
+    assert_eq!(tcpa.get().platform_class(), TcgPlatformClass::Server);
+    let log_area = tcpa.get().log_area().unwrap();
+    assert_eq!((log_area.start_address, log_area.minimum_length), (0x1_7f00_0000, 0x2_0000_0000));
+
+    // The layout of the table is not known for reserved platform classes
+    let fixture = build_tables(&[tcpa_server(2, 0x1_0000, 0x7f00_0000)]);
+    let tcpa = fixture.tables.find_table::<Tcpa>().unwrap();
+    assert!(tcpa.get().log_area().is_none());
+}
@@ -0,0 +1,97 @@
+use acpi::{AcpiError, platform::wdat::Watchdog, sdt::wdat::WatchdogAction};
+use aml_test_tools::tables::{build_tables, gas, sdt};
+use pci_types::PciAddress;
+
+const RESET_PORT: u16 = 0x60;
+const COUNTDOWN_PORT: u16 = 0x64;
+const RUNNING_PORT: u16 = 0x68;
+
+const TIMER_PERIOD: u32 = 100;
+
+fn instruction(action: u8, instruction: u8, port: u16, value: u32, mask: u32) -> Vec<u8> {
+    let mut entry = vec![action, instruction, 0, 0];
+    entry.extend_from_slice(&gas(1, 32, 3, port as u64));
+    entry.extend_from_slice(&value.to_le_bytes());
+    entry.extend_from_slice(&mask.to_le_bytes());
+    entry
+}
+
+fn wdat(pci_address: (u16, u8, u8, u8), min_count: u32, max_count: u32) -> Vec<u8> {
+    const READ_VALUE: u8 = 0;
+    const READ_COUNTDOWN: u8 = 1;
+    const WRITE_VALUE: u8 = 2;
+    const WRITE_COUNTDOWN: u8 = 3;
+    const PRESERVE_REGISTER: u8 = 1 << 7;
+
+    let instructions = [
+        instruction(0x01, WRITE_VALUE | PRESERVE_REGISTER, RESET_PORT, 1, 1),
+        instruction(0x05, READ_COUNTDOWN, COUNTDOWN_PORT, 0, 0xffff),
+        instruction(0x06, WRITE_COUNTDOWN, COUNTDOWN_PORT, 0, 0xffff),
+        instruction(0x08, READ_VALUE, RUNNING_PORT, 1, 1),
+        instruction(0x09, WRITE_VALUE, RUNNING_PORT, 1, 1),
+        instruction(0x0b, WRITE_VALUE, RUNNING_PORT, 0, 1),
+    ];
+
+    let (segment, bus, device, function) = pci_address;
+    let mut body = Vec::new();
+    body.extend_from_slice(&32u32.to_le_bytes());
+    body.extend_from_slice(&segment.to_le_bytes());
+    body.extend_from_slice(&[bus, device, function, 0, 0, 0]);
+    body.extend_from_slice(&TIMER_PERIOD.to_le_bytes());
+    body.extend_from_slice(&max_count.to_le_bytes());
+    body.extend_from_slice(&min_count.to_le_bytes());
+    body.extend_from_slice(&[1, 0, 0, 0]);
+    body.extend_from_slice(&(instructions.len() as u32).to_le_bytes());
+    body.extend_from_slice(&instructions.concat());
+    sdt(b"WDAT", 1, &body)
+}
+
+#[test]
+fn test_watchdog_actions() {
+    let fixture = build_tables(&[wdat((0xffff, 0xff, 0xff, 0xff), 2, 50)]);
+    let watchdog = Watchdog::new(&fixture.tables).unwrap();
+    assert!(watchdog.enabled);
+    assert_eq!(watchdog.pci_address, None);
+
+    watchdog.start().unwrap();
+    assert!(watchdog.is_running().unwrap());
+    assert_eq!(fixture.handler.take_io_writes(), [(RUNNING_PORT, 1)]);
+
+    // The timeout is rounded down to a whole number of counts, and clamped to the supported range
+    assert_eq!(watchdog.set_timeout(1234).unwrap(), 1200);
+    assert_eq!(watchdog.set_timeout(1).unwrap(), 200);
+    assert_eq!(watchdog.set_timeout(1_000_000).unwrap(), 5000);
+    assert_eq!(
+        fixture.handler.take_io_writes(),
+        [(COUNTDOWN_PORT, 12), (COUNTDOWN_PORT, 2), (COUNTDOWN_PORT, 50)]
+    );
+    assert_eq!(watchdog.timeout().unwrap(), 5000);
+
+    // Other bits of the register are preserved
+    fixture.handler.set_io_port(RESET_PORT, 0xf0);
+    watchdog.ping().unwrap();
+    assert_eq!(fixture.handler.take_io_writes(), [(RESET_PORT, 0xf1)]);
+
+    watchdog.stop().unwrap();
+    assert!(!watchdog.is_running().unwrap());
+
+    assert!(matches!(watchdog.set_reboot(), Err(AcpiError::WatchdogActionUnsupported(WatchdogAction::SetReboot))));
+}
+
+#[test]
+fn test_min_count_above_max_count() {
+    let fixture = build_tables(&[wdat((0xffff, 0xff, 0xff, 0xff), 60, 50)]);
+    let watchdog = Watchdog::new(&fixture.tables).unwrap();
+    assert_eq!(watchdog.set_timeout(1000).unwrap(), 5000);
+}
+
+#[test]
+fn test_pci_address() {
+    let pci_address = |address| {
+        let fixture = build_tables(&[wdat(address, 2, 50)]);
+        Watchdog::new(&fixture.tables).unwrap().pci_address
+    };
+    assert_eq!(pci_address((0, 0, 0x1f, 3)), Some(PciAddress::new(0, 0, 0x1f, 3)));
+    assert_eq!(pci_address((0, 0xff, 0xff, 0xff)), None);
+    assert_eq!(pci_address((0, 0, 0, 8)), None);
+}
@@ -6,4 +6,5 @@ pub mod check_cmd_handler;
 pub mod listed_response_handler;
 pub mod logging_handler;
 pub mod null_handler;
+pub mod simulated_handler;
 pub mod std_test_handler;
@@ -0,0 +1,253 @@
+//! A [`Handler`] that simulates a small machine, for testing code that drives hardware.
+
+use acpi::{
+    Handle,
+    Handler,
+    PhysicalMapping,
+    address::{AddressSpace, GenericAddress, MappedGas},
+    aml::AmlError,
+    registers::{FixedRegisters, GpeRegisterBlock, Pm1ControlRegisterBlock, Pm1EventRegisterBlock},
+};
+use pci_types::PciAddress;
+use std::{
+    collections::BTreeMap,
+    ptr::NonNull,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Default, Debug)]
+pub struct SimulatedState {
+    /// The value of each I/O port. Ports that have not been written read as `0`.
+    pub io_ports: BTreeMap<u16, u32>,
+    /// Every write to an I/O port, in order.
+    pub io_writes: Vec<(u16, u32)>,
+    /// The value of each byte of PCI configuration space. Bytes that have not been written read as
+    /// `0`.
+    pub pci_config: BTreeMap<(PciAddress, u16), u8>,
+    /// Every write to PCI configuration space, in order.
+    pub pci_writes: Vec<(PciAddress, u16, u32)>,
+    /// The total time stalled or slept for, in microseconds.
+    pub stalled: u64,
+}
+
+/// A [`Handler`] that maps physical addresses one-to-one with virtual addresses, so tables and
+/// register blocks can be placed in ordinary memory. I/O ports and PCI configuration space are
+/// simulated, and writes to them are recorded so tests can check the order they are made in.
+///
+/// Clones share the same state, so a test can keep a clone to inspect and drive the state of a
+/// handler it has given away.
+#[derive(Clone, Default, Debug)]
+pub struct SimulatedHandler {
+    pub state: Arc<Mutex<SimulatedState>>,
+}
+
+impl SimulatedHandler {
+    /// The I/O port of the 32-bit `PM1a_EVT` register block of [`SimulatedHandler::fixed_registers`].
+    pub const PM1A_EVENT_PORT: u16 = 0x400;
+    /// The I/O port of the 16-bit `PM1a_CNT` register.
+    pub const PM1A_CONTROL_PORT: u16 = 0x404;
+    /// The I/O port of the 16-bit `PM1b_CNT` register.
+    pub const PM1B_CONTROL_PORT: u16 = 0x406;
+    /// The I/O port of the `GPE0` block, which has two status registers followed by two enable
+    /// registers.
+    pub const GPE0_PORT: u16 = 0x420;
+
+    pub fn new() -> SimulatedHandler {
+        SimulatedHandler::default()
+    }
+
+    /// Build a set of fixed hardware registers in this handler's I/O ports, as firmware would
+    /// describe them in the FADT. There are `PM1a` and `PM1b` control registers, but only a `PM1a`
+    /// event register block, and a 16-event `GPE0` block.
+    pub fn fixed_registers(&self) -> FixedRegisters<SimulatedHandler> {
+        let io = |address: u16, bit_width: u8, access_size: u8| GenericAddress {
+            address_space: AddressSpace::SystemIo,
+            bit_width,
+            bit_offset: 0,
+            access_size,
+            address: address as u64,
+        };
+
+        unsafe {
+            FixedRegisters {
+                pm1_event_registers: Pm1EventRegisterBlock {
+                    pm1_event_length: 4,
+                    pm1a: MappedGas::map_gas(io(Self::PM1A_EVENT_PORT, 32, 3), self).unwrap(),
+                    pm1b: None,
+                },
+                pm1_control_registers: Pm1ControlRegisterBlock {
+                    pm1a: MappedGas::map_gas(io(Self::PM1A_CONTROL_PORT, 16, 2), self).unwrap(),
+                    pm1b: Some(MappedGas::map_gas(io(Self::PM1B_CONTROL_PORT, 16, 2), self).unwrap()),
+                },
+                gpe0: Some(GpeRegisterBlock::new(io(Self::GPE0_PORT, 8, 1), 4, self.clone()).unwrap()),
+                gpe1: None,
+                gpe1_base: 0,
+                sleep_control: None,
+                sleep_status: None,
+            }
+        }
+    }
+
+    pub fn set_io_port(&self, port: u16, value: u32) {
+        self.state.lock().unwrap().io_ports.insert(port, value);
+    }
+
+    pub fn io_port(&self, port: u16) -> u32 {
+        self.state.lock().unwrap().io_ports.get(&port).copied().unwrap_or(0)
+    }
+
+    /// Take the writes to I/O ports made since the last call.
+    pub fn take_io_writes(&self) -> Vec<(u16, u32)> {
+        std::mem::take(&mut self.state.lock().unwrap().io_writes)
+    }
+
+    pub fn set_pci_u8(&self, address: PciAddress, offset: u16, value: u8) {
+        self.state.lock().unwrap().pci_config.insert((address, offset), value);
+    }
+
+    /// Take the writes to PCI configuration space made since the last call.
+    pub fn take_pci_writes(&self) -> Vec<(PciAddress, u16, u32)> {
+        std::mem::take(&mut self.state.lock().unwrap().pci_writes)
+    }
+
+    pub fn stalled(&self) -> u64 {
+        self.state.lock().unwrap().stalled
+    }
+
+    fn write_io(&self, port: u16, value: u32) {
+        let mut state = self.state.lock().unwrap();
+        state.io_ports.insert(port, value);
+        state.io_writes.push((port, value));
+    }
+
+    fn read_pci(&self, address: PciAddress, offset: u16, bytes: u16) -> u32 {
+        let state = self.state.lock().unwrap();
+        (0..bytes).fold(0, |value, i| {
+            value | (state.pci_config.get(&(address, offset + i)).copied().unwrap_or(0) as u32) << (i * 8)
+        })
+    }
+
+    fn write_pci(&self, address: PciAddress, offset: u16, bytes: u16, value: u32) {
+        let mut state = self.state.lock().unwrap();
+        for i in 0..bytes {
+            state.pci_config.insert((address, offset + i), (value >> (i * 8)) as u8);
+        }
+        state.pci_writes.push((address, offset, value));
+    }
+}
+
+impl Handler for SimulatedHandler {
+    unsafe fn map_physical_region<T>(&self, physical_address: usize, size: usize) -> PhysicalMapping<Self, T> {
+        PhysicalMapping {
+            physical_start: physical_address,
+            virtual_start: NonNull::new(physical_address as *mut T).unwrap(),
+            region_length: size,
+            mapped_length: size,
+            handler: self.clone(),
+        }
+    }
+
+    fn unmap_physical_region<T>(_region: &PhysicalMapping<Self, T>) {}
+
+    fn read_u8(&self, address: usize) -> u8 {
+        unsafe { (address as *const u8).read_volatile() }
+    }
+
+    fn read_u16(&self, address: usize) -> u16 {
+        unsafe { (address as *const u16).read_unaligned() }
+    }
+
+    fn read_u32(&self, address: usize) -> u32 {
+        unsafe { (address as *const u32).read_unaligned() }
+    }
+
+    fn read_u64(&self, address: usize) -> u64 {
+        unsafe { (address as *const u64).read_unaligned() }
+    }
+
+    fn write_u8(&self, address: usize, value: u8) {
+        unsafe { (address as *mut u8).write_volatile(value) }
+    }
+
+    fn write_u16(&self, address: usize, value: u16) {
+        unsafe { (address as *mut u16).write_unaligned(value) }
+    }
+
+    fn write_u32(&self, address: usize, value: u32) {
+        unsafe { (address as *mut u32).write_unaligned(value) }
+    }
+
+    fn write_u64(&self, address: usize, value: u64) {
+        unsafe { (address as *mut u64).write_unaligned(value) }
+    }
+
+    fn read_io_u8(&self, port: u16) -> u8 {
+        self.io_port(port) as u8
+    }
+
+    fn read_io_u16(&self, port: u16) -> u16 {
+        self.io_port(port) as u16
+    }
+
+    fn read_io_u32(&self, port: u16) -> u32 {
+        self.io_port(port)
+    }
+
+    fn write_io_u8(&self, port: u16, value: u8) {
+        self.write_io(port, value as u32)
+    }
+
+    fn write_io_u16(&self, port: u16, value: u16) {
+        self.write_io(port, value as u32)
+    }
+
+    fn write_io_u32(&self, port: u16, value: u32) {
+        self.write_io(port, value)
+    }
+
+    fn read_pci_u8(&self, address: PciAddress, offset: u16) -> u8 {
+        self.read_pci(address, offset, 1) as u8
+    }
+
+    fn read_pci_u16(&self, address: PciAddress, offset: u16) -> u16 {
+        self.read_pci(address, offset, 2) as u16
+    }
+
+    fn read_pci_u32(&self, address: PciAddress, offset: u16) -> u32 {
+        self.read_pci(address, offset, 4)
+    }
+
+    fn write_pci_u8(&self, address: PciAddress, offset: u16, value: u8) {
+        self.write_pci(address, offset, 1, value as u32)
+    }
+
+    fn write_pci_u16(&self, address: PciAddress, offset: u16, value: u16) {
+        self.write_pci(address, offset, 2, value as u32)
+    }
+
+    fn write_pci_u32(&self, address: PciAddress, offset: u16, value: u32) {
+        self.write_pci(address, offset, 4, value)
+    }
+
+    fn nanos_since_boot(&self) -> u64 {
+        self.stalled() * 1000
+    }
+
+    fn stall(&self, microseconds: u64) {
+        self.state.lock().unwrap().stalled += microseconds;
+    }
+
+    fn sleep(&self, milliseconds: u64) {
+        self.stall(milliseconds * 1000);
+    }
+
+    fn create_mutex(&self) -> Handle {
+        Handle(0)
+    }
+
+    fn acquire(&self, _mutex: Handle, _timeout: u16) -> Result<(), AmlError> {
+        Ok(())
+    }
+
+    fn release(&self, _mutex: Handle) {}
+}
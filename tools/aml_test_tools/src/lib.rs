@@ -4,6 +4,7 @@
 //! As always, feel free to offer PRs for improvements.
 
 pub mod handlers;
+pub mod tables;
 
 use acpi::{
     Handler,
@@ -164,6 +165,11 @@ where
             },
             pm1b: None,
         },
+        gpe0: None,
+        gpe1: None,
+        gpe1_base: 0,
+        sleep_control: None,
+        sleep_status: None,
     });
 
     // This PhysicalMapping is dropped when the interpreter is dropped, and if you use logging in
@@ -0,0 +1,70 @@
+//! Helpers for testing the parsing of static tables. Tables are built from bytes, and laid out in
+//! memory behind an XSDT that points at them, to be found through a [`SimulatedHandler`].
+
+use crate::handlers::simulated_handler::SimulatedHandler;
+use acpi::AcpiTables;
+
+/// A set of tables in memory, along with the [`AcpiTables`] that finds them.
+pub struct TestTables {
+    pub tables: AcpiTables<SimulatedHandler>,
+    pub handler: SimulatedHandler,
+    _memory: Box<[u64]>,
+}
+
+/// Lay out the given tables in memory, behind an XSDT.
+pub fn build_tables(tables: &[Vec<u8>]) -> TestTables {
+    /*
+     * Entries of the XSDT are read as `u64`s, so place it so that they are aligned.
+     */
+    const XSDT_OFFSET: usize = 4;
+    let xsdt_length = 36 + tables.len() * 8;
+
+    let mut offsets = Vec::new();
+    let mut length = (XSDT_OFFSET + xsdt_length).next_multiple_of(8);
+    for table in tables {
+        offsets.push(length);
+        length = (length + table.len()).next_multiple_of(8);
+    }
+
+    let mut memory = vec![0u64; length / 8].into_boxed_slice();
+    let base = memory.as_mut_ptr() as *mut u8;
+    let bytes = unsafe { std::slice::from_raw_parts_mut(base, length) };
+
+    let entries: Vec<u8> =
+        offsets.iter().flat_map(|&offset| (base as u64 + offset as u64).to_le_bytes()).collect();
+    bytes[XSDT_OFFSET..(XSDT_OFFSET + xsdt_length)].copy_from_slice(&sdt(b"XSDT", 1, &entries));
+    for (table, &offset) in tables.iter().zip(&offsets) {
+        bytes[offset..(offset + table.len())].copy_from_slice(table);
+    }
+
+    let handler = SimulatedHandler::new();
+    let tables = unsafe { AcpiTables::from_rsdt(handler.clone(), 2, base as usize + XSDT_OFFSET) }.unwrap();
+    TestTables { tables, handler, _memory: memory }
+}
+
+/// Build a table with the given signature and revision, with a valid header and checksum.
+pub fn sdt(signature: &[u8; 4], revision: u8, body: &[u8]) -> Vec<u8> {
+    let length = 36 + body.len() as u32;
+    let mut table = Vec::with_capacity(length as usize);
+    table.extend_from_slice(signature);
+    table.extend_from_slice(&length.to_le_bytes());
+    table.push(revision);
+    table.push(0);
+    table.extend_from_slice(b"RUSTOS");
+    table.extend_from_slice(b"TESTTABL");
+    table.extend_from_slice(&1u32.to_le_bytes());
+    table.extend_from_slice(b"RUST");
+    table.extend_from_slice(&1u32.to_le_bytes());
+    table.extend_from_slice(body);
+
+    let sum = table.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+    table[9] = 0u8.wrapping_sub(sum);
+    table
+}
+
+/// Build a raw Generic Address Structure.
+pub fn gas(address_space: u8, bit_width: u8, access_size: u8, address: u64) -> Vec<u8> {
+    let mut gas = vec![address_space, bit_width, 0, access_size];
+    gas.extend_from_slice(&address.to_le_bytes());
+    gas
+}